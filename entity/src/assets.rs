@@ -14,6 +14,14 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::alerts::Entity")]
+    Alerts,
+    #[sea_orm(has_many = "super::chat_rooms::Entity")]
+    ChatRooms,
+    #[sea_orm(has_many = "super::league_balances::Entity")]
+    LeagueBalances,
+    #[sea_orm(has_many = "super::league_trades::Entity")]
+    LeagueTrades,
     #[sea_orm(has_many = "super::orders::Entity")]
     Orders,
     #[sea_orm(has_many = "super::price_snapshot::Entity")]
@@ -24,6 +32,30 @@ pub enum Relation {
     UserBalances,
 }
 
+impl Related<super::alerts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Alerts.def()
+    }
+}
+
+impl Related<super::chat_rooms::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChatRooms.def()
+    }
+}
+
+impl Related<super::league_balances::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LeagueBalances.def()
+    }
+}
+
+impl Related<super::league_trades::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LeagueTrades.def()
+    }
+}
+
 impl Related<super::orders::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Orders.def()
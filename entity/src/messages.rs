@@ -12,6 +12,12 @@ pub struct Model {
     #[sea_orm(column_type = "Text")]
     pub text: String,
     pub created_at: DateTime,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub attachment: Option<Json>,
+    pub edited_at: Option<DateTime>,
+    pub deleted_at: Option<DateTime>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub deleted_text: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
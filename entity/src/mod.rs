@@ -2,11 +2,32 @@
 
 pub mod prelude;
 
+pub mod alerts;
 pub mod assets;
+pub mod bot_configs;
+pub mod chat_reads;
+pub mod chat_room_members;
+pub mod chat_room_messages;
+pub mod chat_rooms;
+pub mod chat_sanctions;
 pub mod events;
+pub mod league_balances;
+pub mod league_members;
+pub mod league_messages;
+pub mod league_trades;
+pub mod leagues;
+pub mod message_reports;
 pub mod messages;
+pub mod net_worth_snapshots;
+pub mod notifications;
 pub mod orders;
 pub mod price_snapshot;
+pub mod script_logs;
+pub mod season_results;
+pub mod seasons;
 pub mod trades;
+pub mod user_achievements;
 pub mod user_balances;
+pub mod user_blocks;
+pub mod user_scripts;
 pub mod users;
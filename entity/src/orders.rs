@@ -19,6 +19,8 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::alerts::Entity")]
+    Alerts,
     #[sea_orm(
         belongs_to = "super::assets::Entity",
         from = "Column::AssetId",
@@ -37,6 +39,12 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::alerts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Alerts.def()
+    }
+}
+
 impl Related<super::assets::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Assets.def()
@@ -1,10 +1,31 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.4
 
+pub use super::alerts::Entity as Alerts;
 pub use super::assets::Entity as Assets;
+pub use super::bot_configs::Entity as BotConfigs;
+pub use super::chat_reads::Entity as ChatReads;
+pub use super::chat_room_members::Entity as ChatRoomMembers;
+pub use super::chat_room_messages::Entity as ChatRoomMessages;
+pub use super::chat_rooms::Entity as ChatRooms;
+pub use super::chat_sanctions::Entity as ChatSanctions;
 pub use super::events::Entity as Events;
+pub use super::league_balances::Entity as LeagueBalances;
+pub use super::league_members::Entity as LeagueMembers;
+pub use super::league_messages::Entity as LeagueMessages;
+pub use super::league_trades::Entity as LeagueTrades;
+pub use super::leagues::Entity as Leagues;
+pub use super::message_reports::Entity as MessageReports;
 pub use super::messages::Entity as Messages;
+pub use super::net_worth_snapshots::Entity as NetWorthSnapshots;
+pub use super::notifications::Entity as Notifications;
 pub use super::orders::Entity as Orders;
 pub use super::price_snapshot::Entity as PriceSnapshot;
+pub use super::script_logs::Entity as ScriptLogs;
+pub use super::season_results::Entity as SeasonResults;
+pub use super::seasons::Entity as Seasons;
 pub use super::trades::Entity as Trades;
+pub use super::user_achievements::Entity as UserAchievements;
 pub use super::user_balances::Entity as UserBalances;
+pub use super::user_blocks::Entity as UserBlocks;
+pub use super::user_scripts::Entity as UserScripts;
 pub use super::users::Entity as Users;
@@ -13,6 +13,7 @@ pub struct Model {
     pub price: Decimal,
     pub amount: Decimal,
     pub created_at: DateTime,
+    pub total: Option<Decimal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -15,16 +15,103 @@ pub struct Model {
     pub balance: Decimal,
     pub is_bot: bool,
     pub created_at: DateTime,
+    pub role: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::alerts::Entity")]
+    Alerts,
+    #[sea_orm(has_one = "super::bot_configs::Entity")]
+    BotConfigs,
+    #[sea_orm(has_many = "super::chat_room_members::Entity")]
+    ChatRoomMembers,
+    #[sea_orm(has_many = "super::chat_room_messages::Entity")]
+    ChatRoomMessages,
+    #[sea_orm(has_many = "super::chat_rooms::Entity")]
+    ChatRooms,
+    #[sea_orm(has_many = "super::league_members::Entity")]
+    LeagueMembers,
+    #[sea_orm(has_many = "super::league_messages::Entity")]
+    LeagueMessages,
+    #[sea_orm(has_many = "super::leagues::Entity")]
+    Leagues,
+    #[sea_orm(has_many = "super::net_worth_snapshots::Entity")]
+    NetWorthSnapshots,
+    #[sea_orm(has_many = "super::notifications::Entity")]
+    Notifications,
     #[sea_orm(has_many = "super::orders::Entity")]
     Orders,
+    #[sea_orm(has_many = "super::season_results::Entity")]
+    SeasonResults,
     #[sea_orm(has_many = "super::trades::Entity")]
     Trades,
+    #[sea_orm(has_many = "super::user_achievements::Entity")]
+    UserAchievements,
     #[sea_orm(has_many = "super::user_balances::Entity")]
     UserBalances,
+    #[sea_orm(has_many = "super::user_scripts::Entity")]
+    UserScripts,
+}
+
+impl Related<super::alerts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Alerts.def()
+    }
+}
+
+impl Related<super::bot_configs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BotConfigs.def()
+    }
+}
+
+impl Related<super::chat_room_members::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChatRoomMembers.def()
+    }
+}
+
+impl Related<super::chat_room_messages::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChatRoomMessages.def()
+    }
+}
+
+impl Related<super::chat_rooms::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChatRooms.def()
+    }
+}
+
+impl Related<super::league_members::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LeagueMembers.def()
+    }
+}
+
+impl Related<super::league_messages::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::LeagueMessages.def()
+    }
+}
+
+impl Related<super::leagues::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Leagues.def()
+    }
+}
+
+impl Related<super::net_worth_snapshots::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NetWorthSnapshots.def()
+    }
+}
+
+impl Related<super::notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notifications.def()
+    }
 }
 
 impl Related<super::orders::Entity> for Entity {
@@ -33,16 +120,34 @@ impl Related<super::orders::Entity> for Entity {
     }
 }
 
+impl Related<super::season_results::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SeasonResults.def()
+    }
+}
+
 impl Related<super::trades::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Trades.def()
     }
 }
 
+impl Related<super::user_achievements::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserAchievements.def()
+    }
+}
+
 impl Related<super::user_balances::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::UserBalances.def()
     }
 }
 
+impl Related<super::user_scripts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserScripts.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,12 +1,48 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
+mod m20250601_000002_create_bot_configs;
+mod m20250601_000003_add_roles_and_bot_stats;
+mod m20250601_000004_create_user_scripts;
+mod m20250601_000005_add_trade_totals;
+mod m20250601_000006_create_net_worth_snapshots;
+mod m20250601_000007_create_seasons;
+mod m20250601_000008_create_leagues;
+mod m20250601_000009_create_user_achievements;
+mod m20250601_000010_create_alerts;
+mod m20250601_000011_create_notifications;
+mod m20250601_000012_create_chat_reads;
+mod m20250601_000013_create_chat_rooms;
+mod m20250601_000014_create_chat_moderation;
+mod m20250601_000015_extend_messages;
+mod m20250601_000016_backfill_trade_totals;
+mod m20250601_000017_drop_message_notifications;
+mod m20250601_000018_add_deleted_message_text;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20250601_000002_create_bot_configs::Migration),
+            Box::new(m20250601_000003_add_roles_and_bot_stats::Migration),
+            Box::new(m20250601_000004_create_user_scripts::Migration),
+            Box::new(m20250601_000005_add_trade_totals::Migration),
+            Box::new(m20250601_000006_create_net_worth_snapshots::Migration),
+            Box::new(m20250601_000007_create_seasons::Migration),
+            Box::new(m20250601_000008_create_leagues::Migration),
+            Box::new(m20250601_000009_create_user_achievements::Migration),
+            Box::new(m20250601_000010_create_alerts::Migration),
+            Box::new(m20250601_000011_create_notifications::Migration),
+            Box::new(m20250601_000012_create_chat_reads::Migration),
+            Box::new(m20250601_000013_create_chat_rooms::Migration),
+            Box::new(m20250601_000014_create_chat_moderation::Migration),
+            Box::new(m20250601_000015_extend_messages::Migration),
+            Box::new(m20250601_000016_backfill_trade_totals::Migration),
+            Box::new(m20250601_000017_drop_message_notifications::Migration),
+            Box::new(m20250601_000018_add_deleted_message_text::Migration),
+        ]
     }
 }
@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BotConfigs::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(BotConfigs::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(BotConfigs::UserId).integer().not_null().unique_key())
+                    .col(ColumnDef::new(BotConfigs::Strategy).string().not_null())
+                    .col(ColumnDef::new(BotConfigs::Params).json_binary().not_null().default("{}"))
+                    .col(ColumnDef::new(BotConfigs::Enabled).boolean().not_null().default(true))
+                    .col(ColumnDef::new(BotConfigs::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(BotConfigs::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(BotConfigs::Table, BotConfigs::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(BotConfigs::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum BotConfigs {
+    Table,
+    Id,
+    UserId,
+    Strategy,
+    Params,
+    Enabled,
+    CreatedAt,
+    UpdatedAt,
+}
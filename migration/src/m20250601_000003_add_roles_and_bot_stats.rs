@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Users::Role).string().not_null().default("user"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BotConfigs::Table)
+                    .add_column_if_not_exists(ColumnDef::new(BotConfigs::Allocation).decimal().not_null().default(10000))
+                    .add_column_if_not_exists(ColumnDef::new(BotConfigs::LastError).text().null())
+                    .add_column_if_not_exists(ColumnDef::new(BotConfigs::LastErrorAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BotConfigs::Table)
+                    .drop_column(BotConfigs::Allocation)
+                    .drop_column(BotConfigs::LastError)
+                    .drop_column(BotConfigs::LastErrorAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(Table::alter().table(Users::Table).drop_column(Users::Role).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Role,
+}
+
+#[derive(DeriveIden)]
+enum BotConfigs {
+    Table,
+    Allocation,
+    LastError,
+    LastErrorAt,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserScripts::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(UserScripts::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(UserScripts::UserId).integer().not_null())
+                    .col(ColumnDef::new(UserScripts::Name).string().not_null())
+                    .col(ColumnDef::new(UserScripts::Source).text().not_null())
+                    .col(ColumnDef::new(UserScripts::Enabled).boolean().not_null().default(false))
+                    .col(ColumnDef::new(UserScripts::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(UserScripts::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(UserScripts::Table, UserScripts::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ScriptLogs::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ScriptLogs::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(ScriptLogs::ScriptId).integer().not_null())
+                    .col(ColumnDef::new(ScriptLogs::Level).string().not_null())
+                    .col(ColumnDef::new(ScriptLogs::Message).text().not_null())
+                    .col(ColumnDef::new(ScriptLogs::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(ScriptLogs::Table, ScriptLogs::ScriptId).to(UserScripts::Table, UserScripts::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(ScriptLogs::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(UserScripts::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum UserScripts {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Source,
+    Enabled,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ScriptLogs {
+    Table,
+    Id,
+    ScriptId,
+    Level,
+    Message,
+    CreatedAt,
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Money that actually moved, commission included. Older rows keep NULL.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Trades::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Trades::Total).decimal().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(Trades::Table).drop_column(Trades::Total).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Trades {
+    Table,
+    Total,
+}
@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NetWorthSnapshots::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(NetWorthSnapshots::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(NetWorthSnapshots::UserId).integer().not_null())
+                    .col(ColumnDef::new(NetWorthSnapshots::Cash).decimal().not_null())
+                    .col(ColumnDef::new(NetWorthSnapshots::InOrders).decimal().not_null())
+                    .col(ColumnDef::new(NetWorthSnapshots::Assets).decimal().not_null())
+                    .col(ColumnDef::new(NetWorthSnapshots::Total).decimal().not_null())
+                    .col(ColumnDef::new(NetWorthSnapshots::Place).integer().null())
+                    .col(ColumnDef::new(NetWorthSnapshots::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(NetWorthSnapshots::Table, NetWorthSnapshots::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_net_worth_snapshots_user_created")
+                    .table(NetWorthSnapshots::Table)
+                    .col(NetWorthSnapshots::UserId)
+                    .col(NetWorthSnapshots::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(NetWorthSnapshots::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum NetWorthSnapshots {
+    Table,
+    Id,
+    UserId,
+    Cash,
+    InOrders,
+    Assets,
+    Total,
+    Place,
+    CreatedAt,
+}
@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Seasons::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Seasons::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Seasons::Name).string().not_null())
+                    .col(ColumnDef::new(Seasons::StartsAt).timestamp().not_null())
+                    .col(ColumnDef::new(Seasons::EndsAt).timestamp().not_null())
+                    .col(ColumnDef::new(Seasons::FinishedAt).timestamp().null())
+                    .col(ColumnDef::new(Seasons::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SeasonResults::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(SeasonResults::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(SeasonResults::SeasonId).integer().not_null())
+                    .col(ColumnDef::new(SeasonResults::UserId).integer().not_null())
+                    .col(ColumnDef::new(SeasonResults::Place).integer().not_null())
+                    .col(ColumnDef::new(SeasonResults::TotalBalance).decimal().not_null())
+                    .col(ColumnDef::new(SeasonResults::ReturnPercent).decimal().not_null())
+                    .foreign_key(ForeignKey::create().from(SeasonResults::Table, SeasonResults::SeasonId).to(Seasons::Table, Seasons::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(SeasonResults::Table, SeasonResults::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_season_results_season_user")
+                    .table(SeasonResults::Table)
+                    .col(SeasonResults::SeasonId)
+                    .col(SeasonResults::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(SeasonResults::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Seasons::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Seasons {
+    Table,
+    Id,
+    Name,
+    StartsAt,
+    EndsAt,
+    FinishedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum SeasonResults {
+    Table,
+    Id,
+    SeasonId,
+    UserId,
+    Place,
+    TotalBalance,
+    ReturnPercent,
+}
@@ -0,0 +1,197 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Leagues::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Leagues::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Leagues::Name).string().not_null())
+                    .col(ColumnDef::new(Leagues::OwnerId).integer().not_null())
+                    .col(ColumnDef::new(Leagues::InviteCode).string().not_null().unique_key())
+                    .col(ColumnDef::new(Leagues::StartsAt).timestamp().not_null())
+                    .col(ColumnDef::new(Leagues::EndsAt).timestamp().not_null())
+                    .col(ColumnDef::new(Leagues::StartingCapital).decimal().not_null())
+                    .col(ColumnDef::new(Leagues::AssetIds).json_binary().not_null())
+                    .col(ColumnDef::new(Leagues::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(Leagues::Table, Leagues::OwnerId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(LeagueMembers::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(LeagueMembers::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(LeagueMembers::LeagueId).integer().not_null())
+                    .col(ColumnDef::new(LeagueMembers::UserId).integer().not_null())
+                    .col(ColumnDef::new(LeagueMembers::Balance).decimal().not_null())
+                    .col(ColumnDef::new(LeagueMembers::JoinedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(LeagueMembers::Table, LeagueMembers::LeagueId).to(Leagues::Table, Leagues::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(LeagueMembers::Table, LeagueMembers::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_league_members_league_user")
+                    .table(LeagueMembers::Table)
+                    .col(LeagueMembers::LeagueId)
+                    .col(LeagueMembers::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(LeagueBalances::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(LeagueBalances::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(LeagueBalances::MemberId).integer().not_null())
+                    .col(ColumnDef::new(LeagueBalances::AssetId).integer().not_null())
+                    .col(ColumnDef::new(LeagueBalances::Amount).decimal().not_null())
+                    .foreign_key(ForeignKey::create().from(LeagueBalances::Table, LeagueBalances::MemberId).to(LeagueMembers::Table, LeagueMembers::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(LeagueBalances::Table, LeagueBalances::AssetId).to(Assets::Table, Assets::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_league_balances_member_asset")
+                    .table(LeagueBalances::Table)
+                    .col(LeagueBalances::MemberId)
+                    .col(LeagueBalances::AssetId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(LeagueTrades::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(LeagueTrades::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(LeagueTrades::MemberId).integer().not_null())
+                    .col(ColumnDef::new(LeagueTrades::AssetId).integer().not_null())
+                    .col(ColumnDef::new(LeagueTrades::TradeType).string().not_null())
+                    .col(ColumnDef::new(LeagueTrades::Price).decimal().not_null())
+                    .col(ColumnDef::new(LeagueTrades::Amount).decimal().not_null())
+                    .col(ColumnDef::new(LeagueTrades::Total).decimal().not_null())
+                    .col(ColumnDef::new(LeagueTrades::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(LeagueTrades::Table, LeagueTrades::MemberId).to(LeagueMembers::Table, LeagueMembers::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(LeagueTrades::Table, LeagueTrades::AssetId).to(Assets::Table, Assets::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(LeagueMessages::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(LeagueMessages::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(LeagueMessages::LeagueId).integer().not_null())
+                    .col(ColumnDef::new(LeagueMessages::FromId).integer().not_null())
+                    .col(ColumnDef::new(LeagueMessages::Text).text().not_null())
+                    .col(ColumnDef::new(LeagueMessages::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(LeagueMessages::Table, LeagueMessages::LeagueId).to(Leagues::Table, Leagues::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(LeagueMessages::Table, LeagueMessages::FromId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(LeagueMessages::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(LeagueTrades::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(LeagueBalances::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(LeagueMembers::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Leagues::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Assets {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Leagues {
+    Table,
+    Id,
+    Name,
+    OwnerId,
+    InviteCode,
+    StartsAt,
+    EndsAt,
+    StartingCapital,
+    AssetIds,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LeagueMembers {
+    Table,
+    Id,
+    LeagueId,
+    UserId,
+    Balance,
+    JoinedAt,
+}
+
+#[derive(DeriveIden)]
+enum LeagueBalances {
+    Table,
+    Id,
+    MemberId,
+    AssetId,
+    Amount,
+}
+
+#[derive(DeriveIden)]
+enum LeagueTrades {
+    Table,
+    Id,
+    MemberId,
+    AssetId,
+    TradeType,
+    Price,
+    Amount,
+    Total,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LeagueMessages {
+    Table,
+    Id,
+    LeagueId,
+    FromId,
+    Text,
+    CreatedAt,
+}
@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserAchievements::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(UserAchievements::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(UserAchievements::UserId).integer().not_null())
+                    .col(ColumnDef::new(UserAchievements::Code).string().not_null())
+                    .col(ColumnDef::new(UserAchievements::AwardedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(UserAchievements::Table, UserAchievements::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_achievements_user_code")
+                    .table(UserAchievements::Table)
+                    .col(UserAchievements::UserId)
+                    .col(UserAchievements::Code)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(UserAchievements::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum UserAchievements {
+    Table,
+    Id,
+    UserId,
+    Code,
+    AwardedAt,
+}
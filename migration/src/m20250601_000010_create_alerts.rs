@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Alerts::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Alerts::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Alerts::UserId).integer().not_null())
+                    .col(ColumnDef::new(Alerts::Kind).string().not_null())
+                    .col(ColumnDef::new(Alerts::AssetId).integer().null())
+                    .col(ColumnDef::new(Alerts::OrderId).integer().null())
+                    .col(ColumnDef::new(Alerts::Threshold).decimal().null())
+                    .col(ColumnDef::new(Alerts::WindowMinutes).integer().null())
+                    .col(ColumnDef::new(Alerts::Email).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Alerts::Active).boolean().not_null().default(true))
+                    .col(ColumnDef::new(Alerts::TriggeredAt).timestamp().null())
+                    .col(ColumnDef::new(Alerts::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(Alerts::Table, Alerts::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(Alerts::Table, Alerts::AssetId).to(Assets::Table, Assets::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(Alerts::Table, Alerts::OrderId).to(Orders::Table, Orders::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_alerts_active")
+                    .table(Alerts::Table)
+                    .col(Alerts::Active)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Alerts::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Assets {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Orders {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Alerts {
+    Table,
+    Id,
+    UserId,
+    Kind,
+    AssetId,
+    OrderId,
+    Threshold,
+    WindowMinutes,
+    Email,
+    Active,
+    TriggeredAt,
+    CreatedAt,
+}
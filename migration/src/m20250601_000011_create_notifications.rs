@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notifications::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Notifications::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Notifications::UserId).integer().not_null())
+                    .col(ColumnDef::new(Notifications::Kind).string().not_null())
+                    .col(ColumnDef::new(Notifications::Payload).json_binary().not_null())
+                    .col(ColumnDef::new(Notifications::Read).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Notifications::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(Notifications::Table, Notifications::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notifications_user_read")
+                    .table(Notifications::Table)
+                    .col(Notifications::UserId)
+                    .col(Notifications::Read)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Notifications::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Notifications {
+    Table,
+    Id,
+    UserId,
+    Kind,
+    Payload,
+    Read,
+    CreatedAt,
+}
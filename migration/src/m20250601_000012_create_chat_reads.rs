@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatReads::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ChatReads::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(ChatReads::UserId).integer().not_null())
+                    .col(ColumnDef::new(ChatReads::PeerId).integer().not_null())
+                    .col(ColumnDef::new(ChatReads::LastReadMessageId).integer().not_null())
+                    .col(ColumnDef::new(ChatReads::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(ChatReads::Table, ChatReads::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(ChatReads::Table, ChatReads::PeerId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_reads_user_peer")
+                    .table(ChatReads::Table)
+                    .col(ChatReads::UserId)
+                    .col(ChatReads::PeerId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_messages_recipient_from")
+                    .table(Messages::Table)
+                    .col(Messages::RecipientId)
+                    .col(Messages::FromId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_messages_recipient_from").table(Messages::Table).to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(ChatReads::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Messages {
+    Table,
+    FromId,
+    RecipientId,
+}
+
+#[derive(DeriveIden)]
+enum ChatReads {
+    Table,
+    Id,
+    UserId,
+    PeerId,
+    LastReadMessageId,
+    UpdatedAt,
+}
@@ -0,0 +1,133 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatRooms::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ChatRooms::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(ChatRooms::Kind).string().not_null())
+                    .col(ColumnDef::new(ChatRooms::Name).string().not_null())
+                    .col(ColumnDef::new(ChatRooms::AssetId).integer().null().unique_key())
+                    .col(ColumnDef::new(ChatRooms::OwnerId).integer().null())
+                    .col(ColumnDef::new(ChatRooms::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(ChatRooms::Table, ChatRooms::AssetId).to(Assets::Table, Assets::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(ChatRooms::Table, ChatRooms::OwnerId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::SetNull))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatRoomMembers::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ChatRoomMembers::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(ChatRoomMembers::RoomId).integer().not_null())
+                    .col(ColumnDef::new(ChatRoomMembers::UserId).integer().not_null())
+                    .col(ColumnDef::new(ChatRoomMembers::Role).string().not_null().default("member"))
+                    .col(ColumnDef::new(ChatRoomMembers::JoinedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(ChatRoomMembers::Table, ChatRoomMembers::RoomId).to(ChatRooms::Table, ChatRooms::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(ChatRoomMembers::Table, ChatRoomMembers::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_room_members_room_user")
+                    .table(ChatRoomMembers::Table)
+                    .col(ChatRoomMembers::RoomId)
+                    .col(ChatRoomMembers::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatRoomMessages::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ChatRoomMessages::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(ChatRoomMessages::RoomId).integer().not_null())
+                    .col(ColumnDef::new(ChatRoomMessages::FromId).integer().not_null())
+                    .col(ColumnDef::new(ChatRoomMessages::Text).text().not_null())
+                    .col(ColumnDef::new(ChatRoomMessages::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(ChatRoomMessages::Table, ChatRoomMessages::RoomId).to(ChatRooms::Table, ChatRooms::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(ChatRoomMessages::Table, ChatRoomMessages::FromId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_room_messages_room")
+                    .table(ChatRoomMessages::Table)
+                    .col(ChatRoomMessages::RoomId)
+                    .col(ChatRoomMessages::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(ChatRoomMessages::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(ChatRoomMembers::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(ChatRooms::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Assets {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ChatRooms {
+    Table,
+    Id,
+    Kind,
+    Name,
+    AssetId,
+    OwnerId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ChatRoomMembers {
+    Table,
+    Id,
+    RoomId,
+    UserId,
+    Role,
+    JoinedAt,
+}
+
+#[derive(DeriveIden)]
+enum ChatRoomMessages {
+    Table,
+    Id,
+    RoomId,
+    FromId,
+    Text,
+    CreatedAt,
+}
@@ -0,0 +1,163 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserBlocks::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(UserBlocks::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(UserBlocks::UserId).integer().not_null())
+                    .col(ColumnDef::new(UserBlocks::BlockedId).integer().not_null())
+                    .col(ColumnDef::new(UserBlocks::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(UserBlocks::Table, UserBlocks::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(UserBlocks::Table, UserBlocks::BlockedId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_blocks_user_blocked")
+                    .table(UserBlocks::Table)
+                    .col(UserBlocks::UserId)
+                    .col(UserBlocks::BlockedId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessageReports::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MessageReports::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(MessageReports::ReporterId).integer().not_null())
+                    .col(ColumnDef::new(MessageReports::ReportedId).integer().not_null())
+                    .col(ColumnDef::new(MessageReports::MessageType).string().not_null())
+                    .col(ColumnDef::new(MessageReports::MessageId).integer().not_null())
+                    .col(ColumnDef::new(MessageReports::Text).text().not_null())
+                    .col(ColumnDef::new(MessageReports::Reason).string().not_null())
+                    .col(ColumnDef::new(MessageReports::Status).string().not_null().default("open"))
+                    .col(ColumnDef::new(MessageReports::ModeratorId).integer().null())
+                    .col(ColumnDef::new(MessageReports::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(MessageReports::ResolvedAt).timestamp().null())
+                    .foreign_key(ForeignKey::create().from(MessageReports::Table, MessageReports::ReporterId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(MessageReports::Table, MessageReports::ReportedId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(MessageReports::Table, MessageReports::ModeratorId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::SetNull))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_message_reports_reporter_message")
+                    .table(MessageReports::Table)
+                    .col(MessageReports::ReporterId)
+                    .col(MessageReports::MessageType)
+                    .col(MessageReports::MessageId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_message_reports_status")
+                    .table(MessageReports::Table)
+                    .col(MessageReports::Status)
+                    .col(MessageReports::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatSanctions::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ChatSanctions::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(ChatSanctions::UserId).integer().not_null())
+                    .col(ColumnDef::new(ChatSanctions::Kind).string().not_null())
+                    .col(ColumnDef::new(ChatSanctions::Reason).string().not_null())
+                    .col(ColumnDef::new(ChatSanctions::ModeratorId).integer().null())
+                    .col(ColumnDef::new(ChatSanctions::ExpiresAt).timestamp().null())
+                    .col(ColumnDef::new(ChatSanctions::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(ForeignKey::create().from(ChatSanctions::Table, ChatSanctions::UserId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::Cascade))
+                    .foreign_key(ForeignKey::create().from(ChatSanctions::Table, ChatSanctions::ModeratorId).to(Users::Table, Users::Id).on_delete(ForeignKeyAction::SetNull))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_sanctions_user")
+                    .table(ChatSanctions::Table)
+                    .col(ChatSanctions::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(ChatSanctions::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(MessageReports::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(UserBlocks::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum UserBlocks {
+    Table,
+    Id,
+    UserId,
+    BlockedId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum MessageReports {
+    Table,
+    Id,
+    ReporterId,
+    ReportedId,
+    MessageType,
+    MessageId,
+    Text,
+    Reason,
+    Status,
+    ModeratorId,
+    CreatedAt,
+    ResolvedAt,
+}
+
+#[derive(DeriveIden)]
+enum ChatSanctions {
+    Table,
+    Id,
+    UserId,
+    Kind,
+    Reason,
+    ModeratorId,
+    ExpiresAt,
+    CreatedAt,
+}
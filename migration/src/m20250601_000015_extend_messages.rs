@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Deleted messages keep their row with an empty text, so ids in read markers and
+        // reports stay valid.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Messages::Attachment).json_binary().null())
+                    .add_column_if_not_exists(ColumnDef::new(Messages::EditedAt).timestamp().null())
+                    .add_column_if_not_exists(ColumnDef::new(Messages::DeletedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Messages::Table)
+                    .drop_column(Messages::Attachment)
+                    .drop_column(Messages::EditedAt)
+                    .drop_column(Messages::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Messages {
+    Table,
+    Attachment,
+    EditedAt,
+    DeletedAt,
+}
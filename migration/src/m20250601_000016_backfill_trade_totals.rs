@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Trades of the old `/order/buy` and `/order/sell` wrote the order total into `price`.
+        // They are found by the executed order they copied price and amount from; market fills
+        // already stored the unit price.
+        db.execute_unprepared(
+            r#"
+            UPDATE trades t
+            SET total = t.price, price = ROUND(t.price / t.amount, 3)
+            WHERE t.total IS NULL
+              AND t.amount > 0
+              AND EXISTS (
+                  SELECT 1 FROM orders o
+                  WHERE o.asset_id = t.asset_id
+                    AND o.status = 'done'
+                    AND o.price = t.price
+                    AND o.amount = t.amount
+              )
+            "#,
+        )
+        .await?;
+
+        // The rest are market fills, the commission they paid is not known anymore
+        db.execute_unprepared("UPDATE trades SET total = ROUND(price * amount, 3) WHERE total IS NULL")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Old and new rows can't be told apart after the backfill, prices stay per unit
+        Ok(())
+    }
+}
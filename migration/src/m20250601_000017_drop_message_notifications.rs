@@ -0,0 +1,21 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Chat messages are no longer copied into notifications, old copies would outlive
+        // edits and deletions of the original message
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM notifications WHERE kind = 'message'")
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
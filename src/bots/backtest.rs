@@ -0,0 +1,363 @@
+use crate::bots::strategy::{BotAction, Strategy, StrategyContext};
+use crate::structs::order_structs::OrderType;
+use crate::utils::app_error::AppError;
+use crate::utils::take_commission::take_commission;
+use crate::{COMMISSION_MARKET_BUY, COMMISSION_MARKET_SELL, COMMISSION_ORDER_BUY, COMMISSION_ORDER_SELL};
+use chrono::{DateTime, NaiveDateTime};
+use entity::{orders, price_snapshot};
+use redis::{AsyncCommands, Client};
+use sea_orm::prelude::Decimal;
+use sea_orm::{ColumnTrait, DbConn, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+pub const MAX_TICKS: usize = 20_000;
+const MAX_CURVE_POINTS: usize = 500;
+
+/// Prices of the backtested assets at one moment, carried forward for assets without a
+/// fresh point so every tick has the latest known price.
+pub struct PricePoint {
+    pub timestamp: i64,
+    pub prices: HashMap<i32, Decimal>,
+}
+
+#[derive(Serialize)]
+pub struct EquityPoint {
+    pub timestamp: i64,
+    pub equity: Decimal,
+}
+
+#[derive(Serialize)]
+pub struct BacktestReport {
+    pub initial_cash: Decimal,
+    pub final_equity: Decimal,
+    /// Percent
+    pub total_return: f64,
+    /// Percent, the worst fall from a previous equity peak
+    pub max_drawdown: f64,
+    /// Mean over standard deviation of tick returns, not annualized
+    pub sharpe: f64,
+    pub trade_count: u64,
+    /// Actions the simulated account couldn't afford or that referred to unknown orders
+    pub rejected_actions: u64,
+    pub ticks: usize,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+/// Merges `price_snapshot` rows and the Redis minute history into one timeline. Every
+/// source is read at most `MAX_TICKS + 1` points at a time, a longer range is refused
+/// before the rest of it is loaded.
+pub async fn load_history(
+    db: &DbConn,
+    cache: &Client,
+    asset_ids: &[i32],
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<PricePoint>, AppError> {
+    let mut points: BTreeMap<i64, HashMap<i32, Decimal>> = BTreeMap::new();
+
+    let snapshots = price_snapshot::Entity::find()
+        .filter(price_snapshot::Column::AssetId.is_in(asset_ids.to_vec()))
+        .filter(price_snapshot::Column::CreatedAt.between(from, to))
+        .order_by_asc(price_snapshot::Column::CreatedAt)
+        .limit((MAX_TICKS + 1) as u64)
+        .all(db)
+        .await?;
+    for snapshot in snapshots {
+        points
+            .entry(snapshot.created_at.and_utc().timestamp())
+            .or_default()
+            .insert(snapshot.asset_id, snapshot.price);
+    }
+
+    __check_ticks(points.len())?;
+
+    let mut redis_conn = cache.get_multiplexed_async_connection().await?;
+    for asset_id in asset_ids {
+        let history: Vec<(String, i64)> = redis_conn
+            .zrangebyscore_limit_withscores(
+                format!("asset_price_history:{asset_id}"),
+                from.and_utc().timestamp(),
+                to.and_utc().timestamp(),
+                0,
+                (MAX_TICKS + 1) as isize,
+            )
+            .await?;
+        for (item, timestamp) in history {
+            if let Some(price) = item
+                .split_once(':')
+                .and_then(|(price, _)| Decimal::from_str(price).ok())
+            {
+                points.entry(timestamp).or_default().insert(*asset_id, price);
+            }
+        }
+        __check_ticks(points.len())?;
+    }
+
+    let mut last: HashMap<i32, Decimal> = HashMap::new();
+    Ok(points
+        .into_iter()
+        .map(|(timestamp, prices)| {
+            last.extend(prices);
+            PricePoint {
+                timestamp,
+                prices: last.clone(),
+            }
+        })
+        .collect())
+}
+
+fn __check_ticks(ticks: usize) -> Result<(), AppError> {
+    if ticks > MAX_TICKS {
+        return Err(AppError::Validation(format!(
+            "Range has more than {MAX_TICKS} price points"
+        )));
+    }
+    Ok(())
+}
+
+struct SimulatedAccount {
+    cash: Decimal,
+    holdings: HashMap<i32, Decimal>,
+    orders: Vec<orders::Model>,
+    next_order_id: i32,
+    trade_count: u64,
+    rejected_actions: u64,
+}
+
+impl SimulatedAccount {
+    fn holding(&mut self, asset_id: i32) -> &mut Decimal {
+        self.holdings.entry(asset_id).or_default()
+    }
+
+    fn equity(&self, prices: &HashMap<i32, Decimal>) -> Decimal {
+        let price = |asset_id: &i32| prices.get(asset_id).copied().unwrap_or_default();
+        let assets: Decimal = self
+            .holdings
+            .iter()
+            .map(|(asset_id, amount)| *amount * price(asset_id))
+            .sum();
+        let in_orders: Decimal = self
+            .orders
+            .iter()
+            .map(|order| match order.order_type.as_str() {
+                "buy" => order.price,
+                _ => order.amount * price(&order.asset_id),
+            })
+            .sum();
+        (self.cash + assets + in_orders).round_dp(3)
+    }
+
+    /// Resting orders fill at their own price once the market crosses it, the same way a
+    /// market order walking the book would take them.
+    fn fill_orders(&mut self, prices: &HashMap<i32, Decimal>) {
+        let (filled, pending): (Vec<orders::Model>, Vec<orders::Model>) =
+            std::mem::take(&mut self.orders).into_iter().partition(|order| {
+                let Some(price) = prices.get(&order.asset_id) else {
+                    return false;
+                };
+                let unit_price = order.price / order.amount;
+                match order.order_type.as_str() {
+                    "buy" => *price <= unit_price,
+                    _ => *price >= unit_price,
+                }
+            });
+        self.orders = pending;
+
+        for order in filled {
+            match order.order_type.as_str() {
+                "buy" => {
+                    let received = take_commission(order.amount, *COMMISSION_ORDER_BUY);
+                    *self.holding(order.asset_id) += received.amount;
+                }
+                _ => {
+                    let received = take_commission(order.price, *COMMISSION_ORDER_SELL);
+                    self.cash += received.amount;
+                }
+            }
+            self.trade_count += 1;
+        }
+    }
+
+    fn apply(&mut self, action: BotAction, prices: &HashMap<i32, Decimal>, timestamp: i64) -> bool {
+        match action {
+            BotAction::MarketBuy { asset_id, amount } => {
+                let Some(price) = prices.get(&asset_id) else {
+                    return false;
+                };
+                let cost = (*price * amount).round_dp(3);
+                if amount <= Decimal::ZERO || self.cash < cost {
+                    return false;
+                }
+                self.cash -= cost;
+                *self.holding(asset_id) += take_commission(amount, *COMMISSION_MARKET_BUY).amount;
+                self.trade_count += 1;
+            }
+            BotAction::MarketSell { asset_id, amount } => {
+                let Some(price) = prices.get(&asset_id).copied() else {
+                    return false;
+                };
+                if amount <= Decimal::ZERO || *self.holding(asset_id) < amount {
+                    return false;
+                }
+                *self.holding(asset_id) -= amount;
+                let value = (price * amount).round_dp(3);
+                self.cash += take_commission(value, *COMMISSION_MARKET_SELL).amount;
+                self.trade_count += 1;
+            }
+            BotAction::PlaceOrder {
+                side,
+                asset_id,
+                amount,
+                unit_price,
+            } => {
+                let amount = amount.round_dp(3);
+                let price = (unit_price * amount).round_dp(3);
+                if amount <= Decimal::ZERO || price <= Decimal::ZERO {
+                    return false;
+                }
+                match side {
+                    OrderType::Buy if self.cash < price => return false,
+                    OrderType::Buy => self.cash -= price,
+                    OrderType::Sell if *self.holding(asset_id) < amount => return false,
+                    OrderType::Sell => *self.holding(asset_id) -= amount,
+                }
+                let created_at = DateTime::from_timestamp(timestamp, 0)
+                    .unwrap_or_default()
+                    .naive_utc();
+                self.next_order_id += 1;
+                self.orders.push(orders::Model {
+                    id: self.next_order_id,
+                    user_id: 0,
+                    asset_id,
+                    order_type: match side {
+                        OrderType::Buy => "buy".into(),
+                        OrderType::Sell => "sell".into(),
+                    },
+                    price,
+                    amount,
+                    status: "pending".into(),
+                    created_at,
+                    updated_at: created_at,
+                });
+            }
+            BotAction::CancelOrder { order_id } => {
+                let Some(index) = self.orders.iter().position(|order| order.id == order_id) else {
+                    return false;
+                };
+                let order = self.orders.remove(index);
+                match order.order_type.as_str() {
+                    "buy" => self.cash += order.price,
+                    _ => *self.holding(order.asset_id) += order.amount,
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Replays the history through the strategy with a simulated account. Fills are instant
+/// and never move the price, so results are an upper bound for large sizes.
+pub fn run_backtest(
+    strategy: &mut dyn Strategy,
+    history: &[PricePoint],
+    initial_cash: Decimal,
+) -> BacktestReport {
+    let mut account = SimulatedAccount {
+        cash: initial_cash,
+        holdings: HashMap::new(),
+        orders: Vec::new(),
+        next_order_id: 0,
+        trade_count: 0,
+        rejected_actions: 0,
+    };
+    let mut curve: Vec<EquityPoint> = Vec::with_capacity(history.len());
+
+    for point in history {
+        account.fill_orders(&point.prices);
+        let ctx = StrategyContext {
+            balance: account.cash,
+            holdings: account.holdings.clone(),
+            prices: point.prices.clone(),
+            open_orders: account.orders.clone(),
+        };
+        for action in strategy.on_tick(&ctx) {
+            if !account.apply(action, &point.prices, point.timestamp) {
+                account.rejected_actions += 1;
+            }
+        }
+        curve.push(EquityPoint {
+            timestamp: point.timestamp,
+            equity: account.equity(&point.prices),
+        });
+    }
+
+    let final_equity = curve.last().map(|point| point.equity).unwrap_or(initial_cash);
+    let equity: Vec<f64> = curve
+        .iter()
+        .map(|point| f64::try_from(point.equity).unwrap_or_default())
+        .collect();
+    let initial = f64::try_from(initial_cash).unwrap_or_default();
+
+    BacktestReport {
+        initial_cash,
+        final_equity,
+        total_return: if initial > 0.0 {
+            (f64::try_from(final_equity).unwrap_or_default() - initial) / initial * 100.0
+        } else {
+            0.0
+        },
+        max_drawdown: __max_drawdown(&equity),
+        sharpe: __sharpe(&equity),
+        trade_count: account.trade_count,
+        rejected_actions: account.rejected_actions,
+        ticks: history.len(),
+        equity_curve: __downsample(curve),
+    }
+}
+
+fn __max_drawdown(equity: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+    for value in equity {
+        peak = peak.max(*value);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - value) / peak * 100.0);
+        }
+    }
+    drawdown
+}
+
+fn __sharpe(equity: &[f64]) -> f64 {
+    let returns: Vec<f64> = equity
+        .windows(2)
+        .filter(|pair| pair[0] > 0.0)
+        .map(|pair| pair[1] / pair[0] - 1.0)
+        .collect();
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|value| (value - mean).powi(2)).sum::<f64>()
+        / (returns.len() - 1) as f64;
+    if variance > 0.0 {
+        mean / variance.sqrt()
+    } else {
+        0.0
+    }
+}
+
+fn __downsample(curve: Vec<EquityPoint>) -> Vec<EquityPoint> {
+    if curve.len() <= MAX_CURVE_POINTS {
+        return curve;
+    }
+    let step = curve.len().div_ceil(MAX_CURVE_POINTS);
+    let last = curve.len() - 1;
+    curve
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| index % step == 0 || *index == last)
+        .map(|(_, point)| point)
+        .collect()
+}
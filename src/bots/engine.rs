@@ -0,0 +1,324 @@
+use crate::bots::script::ScriptStrategy;
+use crate::bots::strategy::{build_strategy, BotAction, Strategy, StrategyContext, StrategyLog};
+use crate::routes::order_cancel::__cancel_order;
+use crate::routes::order_create::{__create_buy_order, __create_sell_order};
+use crate::structs::order_structs::{MarketMode, OrderType};
+use crate::utils::app_error::AppError;
+use crate::utils::market_execution::{execute_market_order, MarketOrder};
+use crate::utils::price_calculation::PriceTick;
+use entity::{bot_configs, orders, script_logs, user_balances, user_scripts, users};
+use chrono::Utc;
+use migration::Expr;
+use redis::Client;
+use sea_orm::prelude::{DateTime, Decimal};
+use sea_orm::{ColumnTrait, DbConn, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set};
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Log rows kept per script, older ones are deleted as new ones are written
+const SCRIPT_LOGS_KEPT: u64 = 1000;
+
+struct BotRuntime {
+    strategy: Box<dyn Strategy>,
+    /// `updated_at` of the config or script the strategy was built from, a newer one rebuilds it
+    config_version: DateTime,
+}
+
+/// Runs every enabled bot and user script once per price tick. Strategies keep their state
+/// between ticks and are rebuilt when their config or source changes.
+pub async fn run_bots(db: DbConn, cache: Client, mut ticks: broadcast::Receiver<PriceTick>) {
+    let mut bots: HashMap<i32, BotRuntime> = HashMap::new();
+    let mut scripts: HashMap<i32, BotRuntime> = HashMap::new();
+    loop {
+        let tick = match ticks.recv().await {
+            Ok(tick) => tick,
+            Err(RecvError::Lagged(skipped)) => {
+                eprintln!("Bot engine skipped {skipped} price ticks");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if let Err(err) = __run_tick(&db, &cache, &mut bots, &tick).await {
+            eprintln!("Bot engine error: {err:?}");
+        }
+        if let Err(err) = __run_scripts(&db, &cache, &mut scripts, &tick).await {
+            eprintln!("Script engine error: {err:?}");
+        }
+    }
+}
+
+async fn __run_tick(
+    db: &DbConn,
+    cache: &Client,
+    bots: &mut HashMap<i32, BotRuntime>,
+    tick: &PriceTick,
+) -> Result<(), AppError> {
+    let configs = bot_configs::Entity::find()
+        .filter(bot_configs::Column::Enabled.eq(true))
+        .all(db)
+        .await?;
+    bots.retain(|user_id, _| configs.iter().any(|config| config.user_id == *user_id));
+
+    for config in configs {
+        let outdated = bots
+            .get(&config.user_id)
+            .is_none_or(|bot| bot.config_version != config.updated_at);
+        if outdated {
+            match build_strategy(&config.strategy, &config.params) {
+                Ok(strategy) => {
+                    bots.insert(
+                        config.user_id,
+                        BotRuntime {
+                            strategy,
+                            config_version: config.updated_at,
+                        },
+                    );
+                }
+                Err(err) => {
+                    __record_error(db, config.user_id, &err).await?;
+                    bots.remove(&config.user_id);
+                    continue;
+                }
+            }
+        }
+
+        let Some(bot) = bots.get_mut(&config.user_id) else {
+            continue;
+        };
+        let ctx = match load_context(db, config.user_id, tick).await {
+            Ok(ctx) => ctx,
+            Err(err) => {
+                __record_error(db, config.user_id, &err).await?;
+                continue;
+            }
+        };
+        for action in bot.strategy.on_tick(&ctx) {
+            if let Err(err) = execute_action(db, cache, config.user_id, action).await {
+                __record_error(db, config.user_id, &err).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn __run_scripts(
+    db: &DbConn,
+    cache: &Client,
+    runtimes: &mut HashMap<i32, BotRuntime>,
+    tick: &PriceTick,
+) -> Result<(), AppError> {
+    let scripts = user_scripts::Entity::find()
+        .filter(user_scripts::Column::Enabled.eq(true))
+        .all(db)
+        .await?;
+    runtimes.retain(|script_id, _| scripts.iter().any(|script| script.id == *script_id));
+
+    for script in scripts {
+        let outdated = runtimes
+            .get(&script.id)
+            .is_none_or(|runtime| runtime.config_version != script.updated_at);
+        if outdated {
+            let source = script.source.clone();
+            let built = tokio::task::spawn_blocking(move || ScriptStrategy::new(&source))
+                .await
+                .map_err(AppError::internal)?;
+            match built {
+                Ok(strategy) => {
+                    runtimes.insert(
+                        script.id,
+                        BotRuntime {
+                            strategy: Box::new(strategy),
+                            config_version: script.updated_at,
+                        },
+                    );
+                }
+                Err(err) => {
+                    // Retrying a script that can't start would only flood its log
+                    user_scripts::Entity::update_many()
+                        .col_expr(user_scripts::Column::Enabled, Expr::value(false))
+                        .filter(user_scripts::Column::Id.eq(script.id))
+                        .exec(db)
+                        .await?;
+                    let log = StrategyLog {
+                        level: "error",
+                        message: format!("{err}, script disabled"),
+                    };
+                    __write_script_logs(db, script.id, vec![log]).await?;
+                    runtimes.remove(&script.id);
+                    continue;
+                }
+            }
+        }
+
+        let ctx = match load_context(db, script.user_id, tick).await {
+            Ok(ctx) => ctx,
+            Err(err) => {
+                eprintln!("Script {}: {err:?}", script.id);
+                continue;
+            }
+        };
+        let Some(mut runtime) = runtimes.remove(&script.id) else {
+            continue;
+        };
+        // Rhai is CPU bound, running it on the async workers would stall every request
+        let ran = tokio::task::spawn_blocking(move || {
+            let actions = runtime.strategy.on_tick(&ctx);
+            let logs = runtime.strategy.drain_logs();
+            (runtime, actions, logs)
+        })
+        .await;
+        let (runtime, actions, mut logs) = match ran {
+            Ok(ran) => ran,
+            Err(err) => {
+                // The runtime is lost with the panicked task, the next tick builds it again
+                eprintln!("Script {}: {err}", script.id);
+                continue;
+            }
+        };
+        runtimes.insert(script.id, runtime);
+        for action in actions {
+            if let Err(err) = execute_action(db, cache, script.user_id, action).await {
+                logs.push(StrategyLog {
+                    level: "error",
+                    message: err.to_string(),
+                });
+            }
+        }
+        __write_script_logs(db, script.id, logs).await?;
+    }
+    Ok(())
+}
+
+async fn __write_script_logs(
+    db: &DbConn,
+    script_id: i32,
+    logs: Vec<StrategyLog>,
+) -> Result<(), AppError> {
+    if logs.is_empty() {
+        return Ok(());
+    }
+    script_logs::Entity::insert_many(logs.into_iter().map(|log| script_logs::ActiveModel {
+        script_id: Set(script_id),
+        level: Set(log.level.into()),
+        message: Set(log.message),
+        ..Default::default()
+    }))
+    .exec(db)
+    .await?;
+
+    // Older rows than the newest `SCRIPT_LOGS_KEPT` are dropped
+    let oldest_kept: Option<i32> = script_logs::Entity::find()
+        .filter(script_logs::Column::ScriptId.eq(script_id))
+        .select_only()
+        .column(script_logs::Column::Id)
+        .order_by_desc(script_logs::Column::Id)
+        .offset(SCRIPT_LOGS_KEPT - 1)
+        .into_tuple()
+        .one(db)
+        .await?;
+    if let Some(oldest_kept) = oldest_kept {
+        script_logs::Entity::delete_many()
+            .filter(script_logs::Column::ScriptId.eq(script_id))
+            .filter(script_logs::Column::Id.lt(oldest_kept))
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Keeps the latest failure next to the config, where the admin API can show it.
+async fn __record_error(db: &DbConn, user_id: i32, err: &AppError) -> Result<(), AppError> {
+    let message = match err {
+        AppError::Internal(message) => message.clone(),
+        err => err.to_string(),
+    };
+    eprintln!("Bot {user_id}: {message}");
+    bot_configs::Entity::update_many()
+        .col_expr(bot_configs::Column::LastError, Expr::value(message))
+        .col_expr(bot_configs::Column::LastErrorAt, Expr::value(Utc::now().naive_utc()))
+        .filter(bot_configs::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn load_context(
+    db: &DbConn,
+    user_id: i32,
+    tick: &PriceTick,
+) -> Result<StrategyContext, AppError> {
+    let user = users::Entity::find_by_id(user_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
+    let holdings = user_balances::Entity::find()
+        .filter(user_balances::Column::UserId.eq(user_id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|balance| (balance.asset_id, balance.amount))
+        .collect();
+    let open_orders = orders::Entity::find()
+        .filter(orders::Column::UserId.eq(user_id))
+        .filter(orders::Column::Status.eq("pending"))
+        .all(db)
+        .await?;
+
+    Ok(StrategyContext {
+        balance: user.balance,
+        holdings,
+        prices: tick.prices.clone(),
+        open_orders,
+    })
+}
+
+/// Sends a strategy decision through the same code paths the HTTP handlers use.
+pub async fn execute_action(
+    db: &DbConn,
+    cache: &Client,
+    user_id: i32,
+    action: BotAction,
+) -> Result<(), AppError> {
+    match action {
+        BotAction::MarketBuy { asset_id, amount } | BotAction::MarketSell { asset_id, amount } => {
+            let side = if matches!(action, BotAction::MarketBuy { .. }) {
+                OrderType::Buy
+            } else {
+                OrderType::Sell
+            };
+            execute_market_order(
+                db,
+                cache,
+                side,
+                MarketOrder {
+                    user_id,
+                    asset_id,
+                    amount: amount.round_dp(3),
+                    mode: MarketMode::Book,
+                    max_slippage: None,
+                    limit_price: None,
+                },
+            )
+            .await?;
+        }
+        BotAction::PlaceOrder {
+            side,
+            asset_id,
+            amount,
+            unit_price,
+        } => {
+            let amount = amount.round_dp(3);
+            let price = (unit_price * amount).round_dp(3);
+            if !amount.is_sign_positive() || amount.is_zero() || price <= Decimal::ZERO {
+                return Err(AppError::Validation("Wrong order".into()));
+            }
+            match side {
+                OrderType::Buy => __create_buy_order(user_id, asset_id, amount, price, db, cache).await?,
+                OrderType::Sell => __create_sell_order(user_id, asset_id, amount, price, db, cache).await?,
+            };
+        }
+        BotAction::CancelOrder { order_id } => __cancel_order(user_id, order_id, db, cache).await?,
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use crate::bots::strategy::{BotAction, Strategy, StrategyContext};
+use crate::structs::order_structs::OrderType;
+use crate::utils::quote::side_name;
+use entity::orders;
+use sea_orm::prelude::Decimal;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct MarketMakerParams {
+    pub assets: Option<Vec<i32>>,
+    /// Distance between the bid and the ask in percent of the price
+    pub spread: Decimal,
+    pub size: Decimal,
+    /// No more bids once the bot holds this much of an asset, orders included
+    pub max_inventory: Decimal,
+    /// No more asks once an ask would take the inventory below this
+    pub min_inventory: Decimal,
+    /// How far in percent both quotes move away from a one-sided inventory
+    pub skew: Decimal,
+    /// A resting quote closer than this many percent to the new one is left alone
+    pub tolerance: Decimal,
+}
+
+impl Default for MarketMakerParams {
+    fn default() -> Self {
+        Self {
+            assets: None,
+            spread: Decimal::ONE,
+            size: Decimal::ONE,
+            max_inventory: Decimal::from(10),
+            min_inventory: Decimal::ZERO,
+            skew: Decimal::new(5, 1),
+            tolerance: Decimal::new(1, 1),
+        }
+    }
+}
+
+/// Keeps a bid and an ask around the current price of every asset. Quotes lean towards
+/// the middle of the inventory range, so a bot that bought too much sells cheaper and
+/// bids lower until it is back in balance.
+pub struct MarketMaker {
+    params: MarketMakerParams,
+}
+
+struct Quote {
+    side: OrderType,
+    unit_price: Decimal,
+}
+
+impl MarketMaker {
+    pub fn new(params: MarketMakerParams) -> Self {
+        Self { params }
+    }
+
+    fn quotes(&self, price: Decimal, inventory: Decimal) -> Vec<Quote> {
+        let params = &self.params;
+        let hundred = Decimal::from(100);
+        let half_range = (params.max_inventory - params.min_inventory) / Decimal::TWO;
+        let imbalance = if half_range > Decimal::ZERO {
+            let target = params.min_inventory + half_range;
+            ((inventory - target) / half_range).clamp(-Decimal::ONE, Decimal::ONE)
+        } else {
+            Decimal::ZERO
+        };
+        let mid = price * (Decimal::ONE - params.skew / hundred * imbalance);
+        let half_spread = params.spread / hundred / Decimal::TWO;
+
+        let mut quotes = Vec::with_capacity(2);
+        if inventory + params.size <= params.max_inventory {
+            quotes.push(Quote {
+                side: OrderType::Buy,
+                unit_price: (mid * (Decimal::ONE - half_spread)).round_dp(3),
+            });
+        }
+        if inventory - params.size >= params.min_inventory {
+            quotes.push(Quote {
+                side: OrderType::Sell,
+                unit_price: (mid * (Decimal::ONE + half_spread)).round_dp(3),
+            });
+        }
+        quotes
+            .into_iter()
+            .filter(|quote| quote.unit_price > Decimal::ZERO)
+            .collect()
+    }
+
+    fn is_fresh(&self, order: &orders::Model, quote: &Quote, price: Decimal) -> bool {
+        if order.amount != self.params.size || order.amount.is_zero() {
+            return false;
+        }
+        let unit_price = order.price / order.amount;
+        (unit_price - quote.unit_price).abs() / price * Decimal::from(100) <= self.params.tolerance
+    }
+}
+
+impl Strategy for MarketMaker {
+    fn on_tick(&mut self, ctx: &StrategyContext) -> Vec<BotAction> {
+        let mut cancels = Vec::new();
+        let mut places = Vec::new();
+        let mut cash = ctx.balance;
+        let mut kept: Vec<i32> = Vec::new();
+        let watched = ctx.watched_prices(&self.params.assets);
+
+        for (asset_id, price) in &watched {
+            let asset_orders: Vec<&orders::Model> = ctx
+                .open_orders
+                .iter()
+                .filter(|order| order.asset_id == *asset_id)
+                .collect();
+            let reserved: Decimal = asset_orders
+                .iter()
+                .filter(|order| order.order_type == "sell")
+                .map(|order| order.amount)
+                .sum();
+            let inventory = ctx.holding(*asset_id) + reserved;
+
+            for quote in self.quotes(*price, inventory) {
+                let resting = asset_orders.iter().find(|order| {
+                    order.order_type == side_name(&quote.side)
+                        && !kept.contains(&order.id)
+                        && self.is_fresh(order, &quote, *price)
+                });
+                match resting {
+                    Some(order) => kept.push(order.id),
+                    None => places.push((*asset_id, quote)),
+                }
+            }
+        }
+
+        for order in &ctx.open_orders {
+            if kept.contains(&order.id) {
+                continue;
+            }
+            // The reserve comes back before the new quotes are placed
+            if order.order_type == "buy" {
+                cash += order.price;
+            }
+            cancels.push(BotAction::CancelOrder { order_id: order.id });
+        }
+
+        let size = self.params.size;
+        let mut actions = cancels;
+        for (asset_id, quote) in places {
+            match quote.side {
+                OrderType::Buy if cash < quote.unit_price * size => continue,
+                OrderType::Buy => cash -= quote.unit_price * size,
+                OrderType::Sell => {}
+            }
+            actions.push(BotAction::PlaceOrder {
+                side: quote.side,
+                asset_id,
+                amount: size,
+                unit_price: quote.unit_price,
+            });
+        }
+        actions
+    }
+}
@@ -0,0 +1,79 @@
+use crate::bots::strategy::{BotAction, Strategy, StrategyContext};
+use sea_orm::prelude::Decimal;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct MeanReversionParams {
+    pub assets: Option<Vec<i32>>,
+    /// Number of ticks in the moving average
+    pub window: usize,
+    /// Distance from the average in percent that triggers a trade
+    pub deviation: Decimal,
+    pub size: Decimal,
+}
+
+impl Default for MeanReversionParams {
+    fn default() -> Self {
+        Self {
+            assets: None,
+            window: 20,
+            deviation: Decimal::from(2),
+            size: Decimal::ONE,
+        }
+    }
+}
+
+/// Buys below the moving average and sells above it, betting the price comes back.
+pub struct MeanReversion {
+    params: MeanReversionParams,
+    history: HashMap<i32, VecDeque<Decimal>>,
+}
+
+impl MeanReversion {
+    pub fn new(params: MeanReversionParams) -> Self {
+        Self {
+            params,
+            history: HashMap::new(),
+        }
+    }
+}
+
+impl Strategy for MeanReversion {
+    fn on_tick(&mut self, ctx: &StrategyContext) -> Vec<BotAction> {
+        let mut actions = Vec::new();
+        let mut cash = ctx.balance;
+        let window = self.params.window.max(1);
+
+        for (asset_id, price) in ctx.watched_prices(&self.params.assets) {
+            let history = self.history.entry(asset_id).or_default();
+            history.push_back(price);
+            if history.len() > window {
+                history.pop_front();
+            }
+            if history.len() < window {
+                continue;
+            }
+            let average = history.iter().sum::<Decimal>() / Decimal::from(history.len());
+            if average.is_zero() {
+                continue;
+            }
+
+            let deviation = (price - average) / average * Decimal::from(100);
+            if deviation <= -self.params.deviation && cash >= price * self.params.size {
+                cash -= price * self.params.size;
+                actions.push(BotAction::MarketBuy {
+                    asset_id,
+                    amount: self.params.size,
+                });
+            } else if deviation >= self.params.deviation {
+                let amount = self.params.size.min(ctx.holding(asset_id));
+                if amount.is_sign_positive() && !amount.is_zero() {
+                    actions.push(BotAction::MarketSell { asset_id, amount });
+                }
+            }
+        }
+        actions
+    }
+}
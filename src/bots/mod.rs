@@ -0,0 +1,8 @@
+pub mod engine;
+pub mod strategy;
+pub mod market_maker;
+pub mod momentum;
+pub mod mean_reversion;
+pub mod noise;
+pub mod script;
+pub mod backtest;
@@ -0,0 +1,78 @@
+use crate::bots::strategy::{BotAction, Strategy, StrategyContext};
+use sea_orm::prelude::Decimal;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct MomentumParams {
+    pub assets: Option<Vec<i32>>,
+    /// Number of ticks the price change is measured over
+    pub lookback: usize,
+    /// Change in percent that counts as a trend
+    pub threshold: Decimal,
+    pub size: Decimal,
+}
+
+impl Default for MomentumParams {
+    fn default() -> Self {
+        Self {
+            assets: None,
+            lookback: 5,
+            threshold: Decimal::ONE,
+            size: Decimal::ONE,
+        }
+    }
+}
+
+/// Buys into rising prices and sells into falling ones.
+pub struct Momentum {
+    params: MomentumParams,
+    history: HashMap<i32, VecDeque<Decimal>>,
+}
+
+impl Momentum {
+    pub fn new(params: MomentumParams) -> Self {
+        Self {
+            params,
+            history: HashMap::new(),
+        }
+    }
+}
+
+impl Strategy for Momentum {
+    fn on_tick(&mut self, ctx: &StrategyContext) -> Vec<BotAction> {
+        let mut actions = Vec::new();
+        let mut cash = ctx.balance;
+        let lookback = self.params.lookback.max(1);
+
+        for (asset_id, price) in ctx.watched_prices(&self.params.assets) {
+            let history = self.history.entry(asset_id).or_default();
+            history.push_back(price);
+            if history.len() > lookback + 1 {
+                history.pop_front();
+            }
+            let Some(oldest) = history.front().copied().filter(|_| history.len() > lookback) else {
+                continue;
+            };
+            if oldest.is_zero() {
+                continue;
+            }
+
+            let change = (price - oldest) / oldest * Decimal::from(100);
+            if change >= self.params.threshold && cash >= price * self.params.size {
+                cash -= price * self.params.size;
+                actions.push(BotAction::MarketBuy {
+                    asset_id,
+                    amount: self.params.size,
+                });
+            } else if change <= -self.params.threshold {
+                let amount = self.params.size.min(ctx.holding(asset_id));
+                if amount.is_sign_positive() && !amount.is_zero() {
+                    actions.push(BotAction::MarketSell { asset_id, amount });
+                }
+            }
+        }
+        actions
+    }
+}
@@ -0,0 +1,64 @@
+use crate::bots::strategy::{BotAction, Strategy, StrategyContext};
+use sea_orm::prelude::Decimal;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct NoiseParams {
+    pub assets: Option<Vec<i32>>,
+    /// Chance to trade an asset on a tick, from 0 to 1
+    pub probability: f64,
+    pub max_size: Decimal,
+}
+
+impl Default for NoiseParams {
+    fn default() -> Self {
+        Self {
+            assets: None,
+            probability: 0.2,
+            max_size: Decimal::ONE,
+        }
+    }
+}
+
+/// Trades random amounts in random directions to keep the market moving.
+pub struct Noise {
+    params: NoiseParams,
+}
+
+impl Noise {
+    pub fn new(params: NoiseParams) -> Self {
+        Self { params }
+    }
+}
+
+impl Strategy for Noise {
+    fn on_tick(&mut self, ctx: &StrategyContext) -> Vec<BotAction> {
+        let mut actions = Vec::new();
+        let mut cash = ctx.balance;
+
+        for (asset_id, price) in ctx.watched_prices(&self.params.assets) {
+            if rand::random::<f64>() >= self.params.probability {
+                continue;
+            }
+            let share = Decimal::from_f64_retain(rand::random::<f64>()).unwrap_or_default();
+            let amount = (self.params.max_size * share).round_dp(3);
+            if amount.is_zero() {
+                continue;
+            }
+
+            if rand::random::<bool>() {
+                if cash >= price * amount {
+                    cash -= price * amount;
+                    actions.push(BotAction::MarketBuy { asset_id, amount });
+                }
+            } else {
+                let amount = amount.min(ctx.holding(asset_id));
+                if amount.is_sign_positive() && !amount.is_zero() {
+                    actions.push(BotAction::MarketSell { asset_id, amount });
+                }
+            }
+        }
+        actions
+    }
+}
@@ -0,0 +1,242 @@
+use crate::bots::strategy::{BotAction, Strategy, StrategyContext, StrategyLog};
+use crate::structs::order_structs::OrderType;
+use crate::utils::app_error::AppError;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, INT};
+use sea_orm::prelude::Decimal;
+use std::sync::{Arc, Mutex};
+
+pub const MAX_SCRIPT_SIZE: usize = 16 * 1024;
+/// Rhai counts every expression and statement as an operation, this caps CPU per tick
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_ACTIONS: usize = 20;
+const MAX_LOGS: usize = 50;
+
+#[derive(Default)]
+struct ScriptOutput {
+    actions: Vec<BotAction>,
+    logs: Vec<StrategyLog>,
+}
+
+impl ScriptOutput {
+    fn log(&mut self, level: &'static str, message: &str) {
+        if self.logs.len() < MAX_LOGS {
+            self.logs.push(StrategyLog {
+                level,
+                message: message.chars().take(1000).collect(),
+            });
+        }
+    }
+}
+
+/// A player's own strategy written in Rhai. The script defines `fn on_tick(ctx)` and may
+/// define `fn init()` returning the initial `this` object kept between ticks. Trading is
+/// only possible through `buy`, `sell`, `place_buy`, `place_sell` and `cancel`.
+pub struct ScriptStrategy {
+    engine: Engine,
+    ast: AST,
+    state: Dynamic,
+    output: Arc<Mutex<ScriptOutput>>,
+}
+
+fn __sandbox() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(4096)
+        .set_max_array_size(1024)
+        .set_max_map_size(1024)
+        .set_max_variables(256)
+        .set_max_functions(64)
+        .set_max_modules(0)
+        .set_module_resolver(DummyModuleResolver::new())
+        .disable_symbol("eval");
+    engine
+}
+
+/// Checks the size and syntax of an uploaded script without running it.
+pub fn compile_script(source: &str) -> Result<AST, AppError> {
+    __compile(&__sandbox(), source)
+}
+
+fn __compile(engine: &Engine, source: &str) -> Result<AST, AppError> {
+    if source.len() > MAX_SCRIPT_SIZE {
+        return Err(AppError::Validation(format!(
+            "Script is larger than {MAX_SCRIPT_SIZE} bytes"
+        )));
+    }
+    let ast = engine
+        .compile(source)
+        .map_err(|err| AppError::Validation(format!("Script error: {err}")))?;
+    if !ast
+        .iter_functions()
+        .any(|function| function.name == "on_tick" && function.params.len() == 1)
+    {
+        return Err(AppError::Validation("Script must define fn on_tick(ctx)".into()));
+    }
+    Ok(ast)
+}
+
+fn __number(value: &Dynamic) -> Option<Decimal> {
+    let value = value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|value| value as f64))?;
+    Decimal::from_f64_retain(value).map(|value| value.round_dp(3))
+}
+
+fn __float(value: Decimal) -> Dynamic {
+    Dynamic::from_float(f64::try_from(value).unwrap_or_default())
+}
+
+impl ScriptStrategy {
+    pub fn new(source: &str) -> Result<Self, AppError> {
+        let mut engine = __sandbox();
+        let output = Arc::new(Mutex::new(ScriptOutput::default()));
+
+        let push = {
+            let output = output.clone();
+            move |action: BotAction| -> Result<(), Box<EvalAltResult>> {
+                let mut output = output.lock().unwrap();
+                if output.actions.len() >= MAX_ACTIONS {
+                    return Err(format!("More than {MAX_ACTIONS} actions in one tick").into());
+                }
+                output.actions.push(action);
+                Ok(())
+            }
+        };
+        let amount = |value: &Dynamic| -> Result<Decimal, Box<EvalAltResult>> {
+            __number(value)
+                .filter(|value| value.is_sign_positive() && !value.is_zero())
+                .ok_or_else(|| "Amount and price must be positive numbers".into())
+        };
+
+        let action = push.clone();
+        engine.register_fn("buy", move |asset_id: INT, value: Dynamic| {
+            action(BotAction::MarketBuy {
+                asset_id: asset_id as i32,
+                amount: amount(&value)?,
+            })
+        });
+        let action = push.clone();
+        engine.register_fn("sell", move |asset_id: INT, value: Dynamic| {
+            action(BotAction::MarketSell {
+                asset_id: asset_id as i32,
+                amount: amount(&value)?,
+            })
+        });
+        for (name, side) in [("place_buy", OrderType::Buy), ("place_sell", OrderType::Sell)] {
+            let action = push.clone();
+            engine.register_fn(name, move |asset_id: INT, value: Dynamic, price: Dynamic| {
+                action(BotAction::PlaceOrder {
+                    side,
+                    asset_id: asset_id as i32,
+                    amount: amount(&value)?,
+                    unit_price: amount(&price)?,
+                })
+            });
+        }
+        let action = push.clone();
+        engine.register_fn("cancel", move |order_id: INT| {
+            action(BotAction::CancelOrder {
+                order_id: order_id as i32,
+            })
+        });
+
+        let logs = output.clone();
+        engine.register_fn("log", move |message: &str| {
+            logs.lock().unwrap().log("info", message);
+        });
+        let logs = output.clone();
+        engine.on_print(move |message| logs.lock().unwrap().log("info", message));
+        let logs = output.clone();
+        engine.on_debug(move |message, _, _| logs.lock().unwrap().log("debug", message));
+
+        let ast = __compile(&engine, source)?;
+        let state = if ast.iter_functions().any(|function| function.name == "init") {
+            engine
+                .call_fn_with_options::<Dynamic>(
+                    CallFnOptions::new().eval_ast(false),
+                    &mut Scope::new(),
+                    &ast,
+                    "init",
+                    (),
+                )
+                .map_err(|err| AppError::Validation(format!("Script init failed: {err}")))?
+        } else {
+            Dynamic::from_map(Map::new())
+        };
+
+        Ok(Self {
+            engine,
+            ast,
+            state,
+            output,
+        })
+    }
+
+    fn context(ctx: &StrategyContext) -> Map {
+        let mut asset_ids: Vec<i32> = ctx.prices.keys().copied().collect();
+        asset_ids.sort();
+        let assets: Array = asset_ids
+            .into_iter()
+            .map(|asset_id| {
+                let mut asset = Map::new();
+                asset.insert("id".into(), Dynamic::from_int(asset_id as INT));
+                asset.insert("price".into(), __float(ctx.prices[&asset_id]));
+                asset.insert("holding".into(), __float(ctx.holding(asset_id)));
+                Dynamic::from_map(asset)
+            })
+            .collect();
+        let orders: Array = ctx
+            .open_orders
+            .iter()
+            .map(|order| {
+                let mut item = Map::new();
+                item.insert("id".into(), Dynamic::from_int(order.id as INT));
+                item.insert("asset_id".into(), Dynamic::from_int(order.asset_id as INT));
+                item.insert("side".into(), order.order_type.clone().into());
+                item.insert("amount".into(), __float(order.amount));
+                item.insert("price".into(), __float(order.price / order.amount));
+                Dynamic::from_map(item)
+            })
+            .collect();
+
+        let mut map = Map::new();
+        map.insert("balance".into(), __float(ctx.balance));
+        map.insert("assets".into(), assets.into());
+        map.insert("orders".into(), orders.into());
+        map
+    }
+}
+
+impl Strategy for ScriptStrategy {
+    fn on_tick(&mut self, ctx: &StrategyContext) -> Vec<BotAction> {
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            CallFnOptions::new()
+                .eval_ast(false)
+                .bind_this_ptr(&mut self.state),
+            &mut Scope::new(),
+            &self.ast,
+            "on_tick",
+            (Self::context(ctx),),
+        );
+
+        let mut output = self.output.lock().unwrap();
+        let actions = std::mem::take(&mut output.actions);
+        match result {
+            Ok(_) => actions,
+            Err(err) => {
+                // A failed tick places nothing, half of a decision is worse than none
+                output.log("error", &err.to_string());
+                Vec::new()
+            }
+        }
+    }
+
+    fn drain_logs(&mut self) -> Vec<StrategyLog> {
+        std::mem::take(&mut self.output.lock().unwrap().logs)
+    }
+}
@@ -0,0 +1,101 @@
+use crate::bots::market_maker::MarketMaker;
+use crate::bots::mean_reversion::MeanReversion;
+use crate::bots::momentum::Momentum;
+use crate::bots::noise::Noise;
+use crate::structs::order_structs::OrderType;
+use crate::utils::app_error::AppError;
+use entity::orders;
+use sea_orm::prelude::Decimal;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Everything a strategy may look at on a tick. Built fresh from the database for every tick.
+pub struct StrategyContext {
+    pub balance: Decimal,
+    /// Free asset amounts, the part locked in sell orders is not included
+    pub holdings: HashMap<i32, Decimal>,
+    pub prices: HashMap<i32, Decimal>,
+    /// Pending orders of this bot
+    pub open_orders: Vec<orders::Model>,
+}
+
+impl StrategyContext {
+    pub fn holding(&self, asset_id: i32) -> Decimal {
+        self.holdings.get(&asset_id).copied().unwrap_or_default()
+    }
+
+    /// Prices of the assets the strategy trades, all of them when `assets` is not set.
+    pub fn watched_prices(&self, assets: &Option<Vec<i32>>) -> Vec<(i32, Decimal)> {
+        let mut prices: Vec<(i32, Decimal)> = self
+            .prices
+            .iter()
+            .filter(|(asset_id, _)| assets.as_ref().is_none_or(|assets| assets.contains(asset_id)))
+            .map(|(asset_id, price)| (*asset_id, *price))
+            .collect();
+        prices.sort_by_key(|(asset_id, _)| *asset_id);
+        prices
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotAction {
+    MarketBuy {
+        asset_id: i32,
+        amount: Decimal,
+    },
+    MarketSell {
+        asset_id: i32,
+        amount: Decimal,
+    },
+    /// `unit_price` is per one asset, the engine converts it to the order total
+    PlaceOrder {
+        side: OrderType,
+        asset_id: i32,
+        amount: Decimal,
+        unit_price: Decimal,
+    },
+    CancelOrder {
+        order_id: i32,
+    },
+}
+
+pub struct StrategyLog {
+    pub level: &'static str,
+    pub message: String,
+}
+
+pub trait Strategy: Send {
+    fn on_tick(&mut self, ctx: &StrategyContext) -> Vec<BotAction>;
+
+    /// Messages the strategy produced since the last call, only scripts write any.
+    fn drain_logs(&mut self) -> Vec<StrategyLog> {
+        Vec::new()
+    }
+}
+
+pub const STRATEGIES: [&str; 4] = ["market_maker", "momentum", "mean_reversion", "noise"];
+
+pub fn build_strategy(name: &str, params: &Value) -> Result<Box<dyn Strategy>, AppError> {
+    Ok(match name {
+        "market_maker" => Box::new(MarketMaker::new(__params(params)?)),
+        "momentum" => Box::new(Momentum::new(__params(params)?)),
+        "mean_reversion" => Box::new(MeanReversion::new(__params(params)?)),
+        "noise" => Box::new(Noise::new(__params(params)?)),
+        _ => {
+            return Err(AppError::Validation(format!(
+                "Unknown strategy {name}, expected one of {}",
+                STRATEGIES.join(", ")
+            )))
+        }
+    })
+}
+
+fn __params<T: DeserializeOwned>(params: &Value) -> Result<T, AppError> {
+    let params = if params.is_null() {
+        Value::Object(Default::default())
+    } else {
+        params.clone()
+    };
+    serde_json::from_value(params).map_err(|err| AppError::Validation(format!("Wrong params: {err}")))
+}
@@ -1,29 +1,47 @@
-mod macros;
+mod bots;
+mod middleware;
 mod routes;
 mod structs;
 mod traits;
 mod utils;
 
+use crate::bots::engine::run_bots;
+use crate::middleware::idempotency::idempotency;
+use crate::middleware::rate_limit::{rate_limit, RateLimiter};
 use crate::routes::prelude::*;
+use crate::routes::notifications::NotificationSession;
 use crate::routes::private_chat::ChatSession;
+use crate::routes::ws::StreamSession;
+use crate::utils::achievements::run_achievements;
+use crate::utils::alerts::run_alerts;
+use crate::utils::app_error::AppError;
+use crate::utils::chat_filter::ChatFilter;
 use crate::utils::establish_connection::establish_connection;
 use crate::utils::init_assets::initialize_assets;
+use crate::utils::leaderboard::update_leaderboards;
 use crate::utils::limited_list_with_timeout::LimitedListWithTimeout;
+use crate::utils::mail::Mailer;
+use crate::utils::price_broadcaster::{publish_market_data, PriceBroadcaster};
 use crate::utils::price_calculation::calculate_asset_prices;
+use crate::utils::net_worth_snapshot::{prune_net_worth, save_net_worth_to_db};
+use crate::utils::notifications::prune_notifications;
 use crate::utils::prices_snapshot::save_prices_to_db;
+use crate::utils::pubsub::run_ws_relay;
+use crate::utils::seasons::run_seasons;
 use crate::utils::seed_assets::seed_assets;
 use actix::Addr;
 use actix_cors::Cors;
-use actix_files;
+use actix_web::middleware::from_fn;
 use actix_web::{web, App, HttpServer};
 use dotenv::dotenv;
 use lazy_static::lazy_static;
+use redis::aio::MultiplexedConnection;
 use redis::Client;
 use sea_orm::prelude::Decimal;
 use sea_orm::DbConn;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{broadcast, Mutex, RwLock};
 use tokio::task;
 use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
 use utoipa::{Modify, OpenApi};
@@ -31,6 +49,15 @@ use utoipa_swagger_ui::SwaggerUi;
 
 lazy_static! {
     static ref CHAT_SESSIONS: RwLock<HashMap<i32, Addr<ChatSession>>> = RwLock::new(HashMap::new());
+    static ref NOTIFICATION_SESSIONS: RwLock<HashMap<i32, Addr<NotificationSession>>> =
+        RwLock::new(HashMap::new());
+    static ref PRICE_BROADCASTER: Addr<PriceBroadcaster> = PriceBroadcaster::spawn();
+    /// `/api/v1/ws` sessions by the key of the topic they subscribed to.
+    static ref STREAM_SUBSCRIPTIONS: RwLock<HashMap<String, HashSet<Addr<StreamSession>>>> =
+        RwLock::new(HashMap::new());
+    /// Multiplexed connection every publisher shares, opened on first use and after errors.
+    static ref PUBSUB_CONNECTION: tokio::sync::Mutex<Option<MultiplexedConnection>> =
+        tokio::sync::Mutex::new(None);
     static ref COMMISSION_MARKET_BUY: Decimal = Decimal::from_f64_retain(0.1).unwrap();
     static ref COMMISSION_MARKET_SELL: Decimal = Decimal::from_f64_retain(0.1).unwrap();
     static ref COMMISSION_ORDER_BUY: Decimal = Decimal::from_f64_retain(0.1).unwrap();
@@ -45,6 +72,8 @@ struct AppState {
     jwt_secret: String,
     recover_from: String,
     recover_password: String,
+    rate_limiter: RateLimiter,
+    chat_filter: ChatFilter,
 }
 
 pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
@@ -61,9 +90,31 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     initialize_assets(db.as_ref()).await?;
     seed_assets(db.as_ref()).await?;
 
+    let (price_ticks, _) = broadcast::channel(16);
+    task::spawn(run_bots(
+        db.as_ref().clone(),
+        cache.as_ref().clone(),
+        price_ticks.subscribe(),
+    ));
+    task::spawn(run_alerts(
+        db.as_ref().clone(),
+        cache.as_ref().clone(),
+        price_ticks.subscribe(),
+        Mailer {
+            from: recover_from.clone(),
+            password: recover_password.clone(),
+        },
+    ));
+    task::spawn(publish_market_data(
+        db.clone(),
+        cache.clone(),
+        price_ticks.subscribe(),
+    ));
+    task::spawn(run_ws_relay(cache.as_ref().clone()));
     task::spawn(calculate_asset_prices(
         db.as_ref().clone(),
         cache.as_ref().clone(),
+        price_ticks,
         10,
     ));
     task::spawn(save_prices_to_db(
@@ -71,6 +122,28 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         cache.as_ref().clone(),
         10_800,
     ));
+    task::spawn(save_net_worth_to_db(
+        db.as_ref().clone(),
+        cache.as_ref().clone(),
+        3_600,
+    ));
+    task::spawn(update_leaderboards(
+        db.as_ref().clone(),
+        cache.as_ref().clone(),
+        60,
+    ));
+    task::spawn(run_seasons(
+        db.as_ref().clone(),
+        cache.as_ref().clone(),
+        60,
+    ));
+    task::spawn(run_achievements(
+        db.as_ref().clone(),
+        cache.as_ref().clone(),
+        60,
+    ));
+    task::spawn(prune_notifications(db.as_ref().clone(), 3_600));
+    task::spawn(prune_net_worth(db.as_ref().clone(), 3_600));
 
     let app_state = web::Data::new(AppState {
         db,
@@ -78,6 +151,8 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         jwt_secret,
         recover_from,
         recover_password,
+        rate_limiter: RateLimiter::from_env(),
+        chat_filter: ChatFilter::from_env(),
     });
 
     #[derive(OpenApi)]
@@ -92,10 +167,33 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
             market::market,
             private_chat::chat_ws,
             chat_history::chat_history,
+            chat_read::chat_read,
+            chat_message::chat_message_edit,
+            chat_message::chat_message_delete,
+            chat_rooms::room_create,
+            chat_rooms::rooms_list,
+            chat_rooms::asset_room_info,
+            chat_rooms::room_info,
+            chat_rooms::room_join,
+            chat_rooms::room_leave,
+            chat_rooms::room_member_add,
+            chat_rooms::room_member_remove,
+            chat_rooms::room_member_role,
+            chat_rooms::room_history,
+            chat_moderation::chat_block,
+            chat_moderation::chat_unblock,
+            chat_moderation::chat_blocks_list,
+            chat_moderation::chat_report,
+            moderation::moderation_reports,
+            moderation::moderation_report_resolve,
+            moderation::moderation_sanction,
+            moderation::moderation_sanctions,
+            moderation::moderation_sanction_lift,
             user_orders::user_orders,
             user_orders::user_orders_by_user,
             market_buy::market_buy,
             market_sell::market_sell,
+            market_quote::market_quote,
             top_users::top_users,
             order_buy::order_buy,
             order_sell::order_sell,
@@ -107,6 +205,39 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
             get_events::get_events,
             create_bot::create_bot,
             get_bots::get_bots,
+            admin_bots::admin_bot_config,
+            admin_bots::admin_bot_pause,
+            admin_bots::admin_bot_resume,
+            admin_bots::admin_bot_allocation,
+            admin_bots::admin_bot_stats,
+            user_scripts::script_upload,
+            user_scripts::script_update,
+            user_scripts::script_enabled,
+            user_scripts::user_scripts_list,
+            user_scripts::script_logs_list,
+            backtest::backtest,
+            portfolio::user_portfolio,
+            equity_history::equity_history,
+            seasons::seasons_list,
+            seasons::season_results_list,
+            seasons::season_history,
+            admin_seasons::admin_season_create,
+            admin_seasons::admin_season_end,
+            leagues::league_create,
+            leagues::league_join,
+            leagues::leagues_list,
+            leagues::league_info,
+            leagues::league_market_buy,
+            leagues::league_market_sell,
+            leagues::league_leaderboard,
+            leagues::league_chat_history,
+            notifications::notifications_ws,
+            notifications::notifications_list,
+            notifications::notifications_read,
+            ws::stream_ws,
+            alerts::alert_create,
+            alerts::alerts_list,
+            alerts::alert_delete,
             get_user_place::get_user_place,
             get_chats::get_chats,
             recover_account::recover_account,
@@ -118,6 +249,7 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
             (name="Authorization", description="Auth methods"),
             (name="User", description="User methods"),
             (name="Market", description="Market methods"),
+            (name="Moderation", description="Chat moderation methods"),
 
         )
     )]
@@ -146,6 +278,15 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
     HttpServer::new(move || {
         let mut app = App::new()
             .app_data(app_state.clone())
+            .app_data(web::JsonConfig::default().error_handler(|err, _| {
+                AppError::Validation(err.to_string()).into()
+            }))
+            .app_data(web::QueryConfig::default().error_handler(|err, _| {
+                AppError::Validation(err.to_string()).into()
+            }))
+            .app_data(web::PathConfig::default().error_handler(|err, _| {
+                AppError::Validation(err.to_string()).into()
+            }))
             .service(register::register)
             .service(login::login)
             .service(refresh::refresh)
@@ -154,10 +295,33 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
             .service(market::market)
             .service(private_chat::chat_ws)
             .service(chat_history::chat_history)
+            .service(chat_read::chat_read)
+            .service(chat_message::chat_message_edit)
+            .service(chat_message::chat_message_delete)
+            .service(chat_rooms::room_create)
+            .service(chat_rooms::rooms_list)
+            .service(chat_rooms::asset_room_info)
+            .service(chat_rooms::room_info)
+            .service(chat_rooms::room_join)
+            .service(chat_rooms::room_leave)
+            .service(chat_rooms::room_member_add)
+            .service(chat_rooms::room_member_remove)
+            .service(chat_rooms::room_member_role)
+            .service(chat_rooms::room_history)
+            .service(chat_moderation::chat_block)
+            .service(chat_moderation::chat_unblock)
+            .service(chat_moderation::chat_blocks_list)
+            .service(chat_moderation::chat_report)
+            .service(moderation::moderation_reports)
+            .service(moderation::moderation_report_resolve)
+            .service(moderation::moderation_sanction)
+            .service(moderation::moderation_sanctions)
+            .service(moderation::moderation_sanction_lift)
             .service(user_orders::user_orders)
             .service(user_orders::user_orders_by_user)
             .service(market_buy::market_buy)
             .service(market_sell::market_sell)
+            .service(market_quote::market_quote)
             .service(top_users::top_users)
             .service(order_buy::order_buy)
             .service(order_sell::order_sell)
@@ -169,6 +333,39 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
             .service(get_events::get_events)
             .service(create_bot::create_bot)
             .service(get_bots::get_bots)
+            .service(admin_bots::admin_bot_config)
+            .service(admin_bots::admin_bot_pause)
+            .service(admin_bots::admin_bot_resume)
+            .service(admin_bots::admin_bot_allocation)
+            .service(admin_bots::admin_bot_stats)
+            .service(user_scripts::script_upload)
+            .service(user_scripts::script_update)
+            .service(user_scripts::script_enabled)
+            .service(user_scripts::user_scripts_list)
+            .service(user_scripts::script_logs_list)
+            .service(backtest::backtest)
+            .service(portfolio::user_portfolio)
+            .service(equity_history::equity_history)
+            .service(seasons::seasons_list)
+            .service(seasons::season_results_list)
+            .service(seasons::season_history)
+            .service(admin_seasons::admin_season_create)
+            .service(admin_seasons::admin_season_end)
+            .service(leagues::league_create)
+            .service(leagues::league_join)
+            .service(leagues::leagues_list)
+            .service(leagues::league_info)
+            .service(leagues::league_market_buy)
+            .service(leagues::league_market_sell)
+            .service(leagues::league_leaderboard)
+            .service(leagues::league_chat_history)
+            .service(notifications::notifications_ws)
+            .service(notifications::notifications_list)
+            .service(notifications::notifications_read)
+            .service(ws::stream_ws)
+            .service(alerts::alert_create)
+            .service(alerts::alerts_list)
+            .service(alerts::alert_delete)
             .service(get_user_place::get_user_place)
             .service(get_chats::get_chats)
             .service(recover_account::recover_account)
@@ -183,6 +380,8 @@ pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         app.service(actix_files::Files::new("/", "./web_view/dist").index_file("index.html"))
+            .wrap(from_fn(idempotency))
+            .wrap(from_fn(rate_limit))
             .wrap(Cors::permissive())
     })
     .bind(format!("{host}:{port}"))?
@@ -1,3 +0,0 @@
-pub mod try_or_http_err;
-pub mod unwrap_or_http_err;
-pub mod extract_or_http_err;
\ No newline at end of file
@@ -0,0 +1,183 @@
+use crate::middleware::rate_limit::request_subject;
+use crate::utils::app_error::AppError;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::body::{to_bytes, BoxBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::error::PayloadError;
+use actix_web::http::{Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::web::Bytes;
+use actix_web::{web, Error, HttpResponse, ResponseError};
+use futures::{stream, Stream};
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+const IN_PROGRESS: &str = "in_progress";
+
+const IDEMPOTENT_PATHS: [&str; 6] = [
+    "/api/v1/market/buy",
+    "/api/v1/market/sell",
+    "/api/v1/order/create",
+    "/api/v1/order/buy",
+    "/api/v1/order/sell",
+    "/api/v1/order/cancel",
+];
+
+#[derive(Serialize, Deserialize)]
+struct StoredResponse {
+    request_body: String,
+    status: u16,
+    body: String,
+}
+
+/// How long a key and its response are kept, `IDEMPOTENCY_TTL` seconds (a day by default).
+pub fn idempotency_ttl() -> u64 {
+    std::env::var("IDEMPOTENCY_TTL")
+        .ok()
+        .and_then(|ttl| ttl.parse().ok())
+        .filter(|ttl| *ttl > 0)
+        .unwrap_or(86_400)
+}
+
+pub async fn idempotency(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let key = req
+        .headers()
+        .get(IDEMPOTENCY_HEADER)
+        .and_then(|key| key.to_str().ok())
+        .map(|key| key.trim().to_string());
+    let state = req.app_data::<web::Data<AppState>>().cloned();
+
+    let (key, state) = match (key, state) {
+        (Some(key), Some(state))
+            if req.method() == Method::POST && IDEMPOTENT_PATHS.contains(&req.path()) =>
+        {
+            (key, state)
+        }
+        _ => return next.call(req).await.map(ServiceResponse::map_into_boxed_body),
+    };
+    if key.is_empty() || key.len() > 255 {
+        let response = AppError::Validation("Idempotency-Key must be 1-255 characters".into()).error_response();
+        return Ok(req.into_response(response));
+    }
+
+    let subject = request_subject(&req, &state);
+    if !subject.starts_with("user:") {
+        return next.call(req).await.map(ServiceResponse::map_into_boxed_body);
+    }
+    let redis_key = format!("idempotency:{subject}:{}:{key}", req.path());
+
+    let request_body = req.extract::<Bytes>().await?;
+    req.set_payload(__payload_from_bytes(request_body.clone()));
+    let request_body = String::from_utf8_lossy(&request_body).to_string();
+
+    // Fails closed: without Redis a retry could execute the order twice, so requests that
+    // ask for idempotency are refused until it is back. Requests without the header still run.
+    let lock = async {
+        let mut redis_conn = state.cache.get_multiplexed_async_connection().await?;
+        let locked = redis::cmd("SET")
+            .arg(&redis_key)
+            .arg(IN_PROGRESS)
+            .arg("NX")
+            .arg("EX")
+            .arg(idempotency_ttl())
+            .query_async::<Option<String>>(&mut redis_conn)
+            .await?
+            .is_some();
+        Ok::<_, redis::RedisError>((redis_conn, locked))
+    };
+    let (mut redis_conn, locked) = match lock.await {
+        Ok(lock) => lock,
+        Err(err) => {
+            eprintln!("Idempotency store is unavailable: {err}");
+            let response = AppError::ServiceUnavailable(
+                "Idempotency-Key can't be honoured right now, retry later",
+            )
+            .error_response();
+            return Ok(req.into_response(response));
+        }
+    };
+
+    if !locked {
+        let stored: Option<String> = redis_conn.get(&redis_key).await.map_err(AppError::from)?;
+        let response = match stored.as_deref() {
+            None | Some(IN_PROGRESS) => {
+                AppError::IdempotencyConflict("A request with this Idempotency-Key is still in progress").error_response()
+            }
+            Some(stored) => {
+                let stored: StoredResponse = serde_json::from_str(stored).map_err(AppError::internal)?;
+                if stored.request_body != request_body {
+                    AppError::IdempotencyConflict("Idempotency-Key was already used with a different request").error_response()
+                } else {
+                    HttpResponse::build(StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK))
+                        .content_type("application/json")
+                        .insert_header((REPLAYED_HEADER, "true"))
+                        .body(stored.body)
+                }
+            }
+        };
+        return Ok(req.into_response(response));
+    }
+
+    // Server errors are stored like any other response. The order handlers may have reserved
+    // funds before failing, running them again on retry could reserve twice.
+    let response = match next.call(req).await {
+        Ok(response) => response,
+        Err(err) => {
+            let error = AppError::internal(&err);
+            let stored = StoredResponse {
+                request_body,
+                status: error.status_code().as_u16(),
+                body: serde_json::to_string(&CommonResponse::<()> {
+                    status: ResponseStatus::Error,
+                    data: (),
+                    error: Some(error.to_string()),
+                    code: Some(error.code()),
+                })
+                .map_err(AppError::internal)?,
+            };
+            __store(&mut redis_conn, &redis_key, &stored).await;
+            return Err(err);
+        }
+    };
+
+    let status = response.status();
+
+    let (http_req, http_res) = response.into_parts();
+    let (http_res, body) = http_res.into_parts();
+    let body = to_bytes(body).await.map_err(|err| AppError::internal(err.into()))?;
+
+    let stored = StoredResponse {
+        request_body,
+        status: status.as_u16(),
+        body: String::from_utf8_lossy(&body).to_string(),
+    };
+    __store(&mut redis_conn, &redis_key, &stored).await;
+
+    Ok(ServiceResponse::new(http_req, http_res.set_body(BoxBody::new(body))))
+}
+
+async fn __store(redis_conn: &mut MultiplexedConnection, redis_key: &str, stored: &StoredResponse) {
+    let stored = match serde_json::to_string(stored) {
+        Ok(stored) => stored,
+        Err(err) => return eprintln!("Failed to store idempotent response: {err}"),
+    };
+    if let Err(err) = redis_conn
+        .set_ex::<_, _, ()>(redis_key, stored, idempotency_ttl())
+        .await
+    {
+        eprintln!("Failed to store idempotent response: {err}");
+    }
+}
+
+fn __payload_from_bytes(bytes: Bytes) -> Payload {
+    let stream = stream::once(async move { Ok::<Bytes, PayloadError>(bytes) });
+    Payload::from(Box::pin(stream) as Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>)
+}
@@ -0,0 +1,192 @@
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::Claims;
+use crate::AppState;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, ResponseError};
+use chrono::Utc;
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use lazy_static::lazy_static;
+use redis::{Client, Script};
+use std::net::IpAddr;
+
+lazy_static! {
+    static ref TOKEN_BUCKET: Script = Script::new(
+        r#"
+        local capacity = tonumber(ARGV[1])
+        local refill = tonumber(ARGV[2])
+        local now = tonumber(ARGV[3])
+        local data = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+        local tokens = tonumber(data[1]) or capacity
+        local ts = tonumber(data[2]) or now
+        tokens = math.min(capacity, tokens + math.max(0, now - ts) * refill)
+        local retry = 0
+        if tokens >= 1 then
+            tokens = tokens - 1
+        else
+            retry = math.ceil((1 - tokens) / refill)
+        end
+        redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+        redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / refill))
+        return retry
+        "#
+    );
+}
+
+/// Token bucket: `capacity` requests at once, refilled evenly over `period_secs`.
+#[derive(Debug, Clone)]
+pub struct RateLimitRule {
+    pub group: &'static str,
+    pub prefixes: Vec<&'static str>,
+    pub capacity: u32,
+    pub period_secs: u32,
+}
+
+impl RateLimitRule {
+    /// Reads `RATE_LIMIT_<GROUP>` in the `capacity/period_secs` form, e.g. `RATE_LIMIT_TRADE=30/60`.
+    fn from_env(group: &'static str, prefixes: Vec<&'static str>, capacity: u32, period_secs: u32) -> Self {
+        let (capacity, period_secs) = std::env::var(format!("RATE_LIMIT_{}", group.to_uppercase()))
+            .ok()
+            .and_then(|value| {
+                let (capacity, period) = value.split_once('/')?;
+                Some((capacity.trim().parse().ok()?, period.trim().parse().ok()?))
+            })
+            .filter(|(capacity, period)| *capacity > 0 && *period > 0)
+            .unwrap_or((capacity, period_secs));
+
+        Self {
+            group,
+            prefixes,
+            capacity,
+            period_secs,
+        }
+    }
+}
+
+pub struct RateLimiter {
+    rules: Vec<RateLimitRule>,
+    /// Proxies whose `X-Forwarded-For`/`Forwarded` headers are believed, from `TRUSTED_PROXIES`
+    trusted_proxies: Vec<IpAddr>,
+}
+
+impl RateLimiter {
+    /// Rules are checked in order and the first matching prefix wins.
+    pub fn from_env() -> Self {
+        Self {
+            rules: vec![
+                RateLimitRule::from_env("recover", vec!["/api/v1/recover/send"], 3, 600),
+                RateLimitRule::from_env(
+                    "auth",
+                    vec!["/api/v1/auth/", "/api/v1/recover/", "/api/v1/change/password"],
+                    10,
+                    60,
+                ),
+                RateLimitRule::from_env("trade", vec!["/api/v1/market/buy", "/api/v1/market/sell", "/api/v1/order/", "/api/v1/leagues/market/"], 30, 60),
+                RateLimitRule::from_env("backtest", vec!["/api/v1/backtest"], 5, 60),
+                RateLimitRule::from_env("chat", vec!["/api/v1/chat/private"], 20, 60),
+                RateLimitRule::from_env("chat_message", vec![], 60, 60),
+                RateLimitRule::from_env("chat_typing", vec![], 120, 60),
+                RateLimitRule::from_env("default", vec!["/api/"], 300, 60),
+            ],
+            trusted_proxies: std::env::var("TRUSTED_PROXIES")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|addr| addr.trim().parse().ok())
+                .collect(),
+        }
+    }
+
+    pub fn rule_for_path(&self, path: &str) -> Option<&RateLimitRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.prefixes.iter().any(|prefix| path.starts_with(prefix)))
+    }
+
+    /// Address of the socket peer. Forwarded headers are client controlled, they are only
+    /// read when the peer is one of the trusted proxies.
+    pub fn client_ip(&self, req: &ServiceRequest) -> String {
+        let Some(peer) = req.peer_addr().map(|addr| addr.ip()) else {
+            return "unknown".to_string();
+        };
+        if self.trusted_proxies.contains(&peer) {
+            if let Some(forwarded) = req.connection_info().realip_remote_addr() {
+                return forwarded.to_string();
+            }
+        }
+        peer.to_string()
+    }
+
+    pub fn rule(&self, group: &str) -> Option<&RateLimitRule> {
+        self.rules.iter().find(|rule| rule.group == group)
+    }
+
+    /// Takes one token for `subject` and returns the seconds to wait when the bucket is empty.
+    ///
+    /// Fails open on purpose: when Redis is unreachable every request is let through and the
+    /// error is logged. Rate limiting protects the API from abuse, it must not take it down
+    /// together with the cache.
+    pub async fn check(&self, cache: &Client, rule: &RateLimitRule, subject: &str) -> Option<u64> {
+        let result: Result<u64, redis::RedisError> = async {
+            let mut redis_conn = cache.get_multiplexed_async_connection().await?;
+            TOKEN_BUCKET
+                .key(format!("rate_limit:{}:{}", rule.group, subject))
+                .arg(rule.capacity)
+                .arg(rule.capacity as f64 / (rule.period_secs as f64 * 1000.0))
+                .arg(Utc::now().timestamp_millis())
+                .invoke_async(&mut redis_conn)
+                .await
+        }
+        .await;
+
+        match result {
+            Ok(0) => None,
+            Ok(retry_ms) => Some(retry_ms.div_ceil(1000)),
+            Err(err) => {
+                eprintln!("Rate limiter is unavailable: {err}");
+                None
+            }
+        }
+    }
+}
+
+/// `user:<id>` for a valid access token, `ip:<addr>` otherwise.
+pub fn request_subject(req: &ServiceRequest, state: &AppState) -> String {
+    let jwt_secret = state.jwt_secret.as_str();
+    let user_id = req
+        .headers()
+        .get("Authorization")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .and_then(|token| {
+            decode::<Claims>(
+                token.trim(),
+                &DecodingKey::from_secret(jwt_secret.as_ref()),
+                &Validation::default(),
+            )
+            .ok()
+        })
+        .filter(|token| token.claims.token_type == "access")
+        .map(|token| token.claims.sub);
+
+    match user_id {
+        Some(user_id) => format!("user:{user_id}"),
+        None => format!("ip:{}", state.rate_limiter.client_ip(req)),
+    }
+}
+
+pub async fn rate_limit<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    if let Some(state) = req.app_data::<web::Data<AppState>>().cloned() {
+        if let Some(rule) = state.rate_limiter.rule_for_path(req.path()) {
+            let subject = request_subject(&req, &state);
+            if let Some(retry_after) = state.rate_limiter.check(&state.cache, rule, &subject).await {
+                let response = AppError::RateLimited(retry_after).error_response();
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+        }
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
@@ -0,0 +1,299 @@
+use crate::bots::strategy::build_strategy;
+use crate::utils::admin::AdminToken;
+use crate::utils::app_error::AppError;
+use crate::utils::net_worth::{net_worth, NetWorth};
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, post, web, HttpResponse};
+use chrono::Utc;
+use entity::{bot_configs, orders, trades, users};
+use sea_orm::prelude::{DateTime, Decimal};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DbConn, EntityTrait, FromQueryResult, IntoActiveModel,
+    PaginatorTrait, QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[utoipa::path(
+    request_body = BotConfigInput,
+    params(BotPath),
+    tag="Admin",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/admin/bots/{bot_id}/config")]
+pub async fn admin_bot_config(
+    state: web::Data<AppState>,
+    path: web::Path<BotPath>,
+    input: web::Json<BotConfigInput>,
+    _admin: AdminToken,
+) -> Result<HttpResponse, AppError> {
+    let bot = __find_bot(state.db.as_ref(), path.bot_id).await?;
+    let input = input.into_inner();
+    let params = input.params.unwrap_or(serde_json::json!({}));
+    build_strategy(&input.strategy, &params)?;
+
+    let config = bot_configs::Entity::find()
+        .filter(bot_configs::Column::UserId.eq(bot.id))
+        .one(state.db.as_ref())
+        .await?;
+    match config {
+        Some(config) => {
+            let mut active_config = config.into_active_model();
+            active_config.strategy = Set(input.strategy);
+            active_config.params = Set(params);
+            active_config.last_error = Set(None);
+            active_config.last_error_at = Set(None);
+            active_config.updated_at = Set(Utc::now().naive_utc());
+            active_config.update(state.db.as_ref()).await?;
+        }
+        None => {
+            bot_configs::ActiveModel {
+                user_id: Set(bot.id),
+                strategy: Set(input.strategy),
+                params: Set(params),
+                allocation: Set(bot.balance),
+                ..Default::default()
+            }
+            .insert(state.db.as_ref())
+            .await?;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(BotPath),
+    tag="Admin",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/admin/bots/{bot_id}/pause")]
+pub async fn admin_bot_pause(
+    state: web::Data<AppState>,
+    path: web::Path<BotPath>,
+    _admin: AdminToken,
+) -> Result<HttpResponse, AppError> {
+    __set_enabled(state.db.as_ref(), path.bot_id, false).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(BotPath),
+    tag="Admin",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/admin/bots/{bot_id}/resume")]
+pub async fn admin_bot_resume(
+    state: web::Data<AppState>,
+    path: web::Path<BotPath>,
+    _admin: AdminToken,
+) -> Result<HttpResponse, AppError> {
+    __set_enabled(state.db.as_ref(), path.bot_id, true).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+/// Sets how much money the bot was given in total. The difference to the previous
+/// allocation is added to or taken from its balance, and P&L is counted against it.
+#[utoipa::path(
+    request_body = BotAllocationInput,
+    params(BotPath),
+    tag="Admin",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/admin/bots/{bot_id}/allocation")]
+pub async fn admin_bot_allocation(
+    state: web::Data<AppState>,
+    path: web::Path<BotPath>,
+    input: web::Json<BotAllocationInput>,
+    _admin: AdminToken,
+) -> Result<HttpResponse, AppError> {
+    let allocation = Decimal::from_f64_retain(input.allocation)
+        .map(|allocation| allocation.round_dp(3))
+        .filter(|allocation| !allocation.is_sign_negative())
+        .ok_or(AppError::Validation("Wrong allocation".into()))?;
+
+    let txn = state.db.begin().await?;
+    let bot = users::Entity::find_by_id(path.bot_id)
+        .filter(users::Column::IsBot.eq(true))
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NotFound("No bot"))?;
+    let config = bot_configs::Entity::find()
+        .filter(bot_configs::Column::UserId.eq(bot.id))
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NotFound("Bot has no strategy"))?;
+
+    let new_balance = (bot.balance + allocation - config.allocation).round_dp(3);
+    if new_balance.is_sign_negative() {
+        return Err(AppError::InsufficientFunds);
+    }
+    let mut active_bot = bot.into_active_model();
+    active_bot.balance = Set(new_balance);
+    active_bot.update(&txn).await?;
+    let mut active_config = config.into_active_model();
+    active_config.allocation = Set(allocation);
+    active_config.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(BotPath),
+    tag="Admin",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/admin/bots/{bot_id}")]
+pub async fn admin_bot_stats(
+    state: web::Data<AppState>,
+    path: web::Path<BotPath>,
+    _admin: AdminToken,
+) -> Result<HttpResponse, AppError> {
+    let bot = __find_bot(state.db.as_ref(), path.bot_id).await?;
+    let config = bot_configs::Entity::find()
+        .filter(bot_configs::Column::UserId.eq(bot.id))
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Bot has no strategy"))?;
+
+    let worth = net_worth(state.db.as_ref(), &state.cache, bot.id).await?;
+    let open_orders = orders::Entity::find()
+        .filter(orders::Column::UserId.eq(bot.id))
+        .filter(orders::Column::Status.eq("pending"))
+        .select_only()
+        .column(orders::Column::Id)
+        .column(orders::Column::AssetId)
+        .column(orders::Column::OrderType)
+        .column(orders::Column::Price)
+        .column(orders::Column::Amount)
+        .column(orders::Column::CreatedAt)
+        .into_model::<BotOrderResponse>()
+        .all(state.db.as_ref())
+        .await?;
+    let trade_count = trades::Entity::find()
+        .filter(trades::Column::UserId.eq(bot.id))
+        .count(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<BotStatsResponse> {
+        status: ResponseStatus::Ok,
+        data: BotStatsResponse {
+            bot_id: bot.id,
+            username: bot.username,
+            strategy: config.strategy,
+            params: config.params,
+            enabled: config.enabled,
+            allocation: config.allocation,
+            pnl: (worth.total - config.allocation).round_dp(3),
+            net_worth: worth,
+            open_orders,
+            trade_count,
+            last_error: config.last_error,
+            last_error_at: config.last_error_at,
+        },
+        error: None,
+        code: None,
+    }))
+}
+
+async fn __find_bot(db: &DbConn, bot_id: i32) -> Result<users::Model, AppError> {
+    users::Entity::find_by_id(bot_id)
+        .filter(users::Column::IsBot.eq(true))
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("No bot"))
+}
+
+async fn __set_enabled(db: &DbConn, bot_id: i32, enabled: bool) -> Result<(), AppError> {
+    let config = bot_configs::Entity::find()
+        .filter(bot_configs::Column::UserId.eq(bot_id))
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("Bot has no strategy"))?;
+
+    let mut active_config = config.into_active_model();
+    active_config.enabled = Set(enabled);
+    active_config.update(db).await?;
+    Ok(())
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct BotPath {
+    pub bot_id: i32,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BotConfigInput {
+    /// One of `market_maker`, `momentum`, `mean_reversion`, `noise`
+    strategy: String,
+    #[schema(value_type = Option<Object>)]
+    params: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BotAllocationInput {
+    allocation: f64,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct BotOrderResponse {
+    pub id: i32,
+    pub asset_id: i32,
+    pub order_type: String,
+    pub price: Decimal,
+    pub amount: Decimal,
+    pub created_at: DateTime,
+}
+
+#[derive(Serialize)]
+pub struct BotStatsResponse {
+    pub bot_id: i32,
+    pub username: String,
+    pub strategy: String,
+    pub params: serde_json::Value,
+    pub enabled: bool,
+    pub allocation: Decimal,
+    pub net_worth: NetWorth,
+    /// Net worth minus the allocation
+    pub pnl: Decimal,
+    pub open_orders: Vec<BotOrderResponse>,
+    pub trade_count: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime>,
+}
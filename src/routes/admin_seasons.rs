@@ -0,0 +1,133 @@
+use crate::utils::admin::AdminToken;
+use crate::utils::app_error::AppError;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use entity::seasons;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, ModelTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[utoipa::path(
+    request_body = SeasonInput,
+    tag="Admin",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/admin/seasons")]
+pub async fn admin_season_create(
+    state: web::Data<AppState>,
+    input: web::Json<SeasonInput>,
+    _admin: AdminToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("Season name must not be empty".to_string()));
+    }
+    let starts_at = DateTime::from_timestamp(input.starts_at, 0)
+        .ok_or(AppError::Validation("Invalid starts_at".to_string()))?
+        .naive_utc();
+    let ends_at = DateTime::from_timestamp(input.ends_at, 0)
+        .ok_or(AppError::Validation("Invalid ends_at".to_string()))?
+        .naive_utc();
+    if ends_at <= starts_at || ends_at <= Utc::now().naive_utc() {
+        return Err(AppError::Validation(
+            "ends_at must be in the future and after starts_at".to_string(),
+        ));
+    }
+
+    let overlapping = seasons::Entity::find()
+        .filter(seasons::Column::FinishedAt.is_null())
+        .filter(seasons::Column::StartsAt.lt(ends_at))
+        .filter(seasons::Column::EndsAt.gt(starts_at))
+        .one(state.db.as_ref())
+        .await?;
+    if overlapping.is_some() {
+        return Err(AppError::AlreadyExists("Season overlaps an existing one"));
+    }
+
+    let season = seasons::ActiveModel {
+        name: Set(name),
+        starts_at: Set(starts_at),
+        ends_at: Set(ends_at),
+        ..Default::default()
+    }
+    .insert(state.db.as_ref())
+    .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<SeasonCreated> {
+        status: ResponseStatus::Ok,
+        data: SeasonCreated { id: season.id },
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(AdminSeasonPath),
+    tag="Admin",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/admin/seasons/{season_id}/end")]
+pub async fn admin_season_end(
+    state: web::Data<AppState>,
+    path: web::Path<AdminSeasonPath>,
+    _admin: AdminToken,
+) -> Result<HttpResponse, AppError> {
+    // Ends a running season now, a scheduled one that hasn't started yet is cancelled
+    let season = seasons::Entity::find_by_id(path.season_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No season"))?;
+    if season.finished_at.is_some() {
+        return Err(AppError::Validation("Season is already finished".to_string()));
+    }
+
+    let now = Utc::now().naive_utc();
+    if now < season.starts_at {
+        // Nothing was played yet, there are no standings to archive and no balances to reset
+        season.delete(state.db.as_ref()).await?;
+        return Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+            status: ResponseStatus::Ok,
+            data: (),
+            error: None,
+            code: None,
+        }));
+    }
+
+    // The seasons job archives and resets on its next run
+    let mut active_season = season.into_active_model();
+    active_season.ends_at = Set(now);
+    active_season.update(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SeasonInput {
+    pub name: String,
+    /// Unix timestamp
+    pub starts_at: i64,
+    /// Unix timestamp
+    pub ends_at: i64,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct AdminSeasonPath {
+    pub season_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct SeasonCreated {
+    pub id: i32,
+}
@@ -0,0 +1,205 @@
+use crate::structs::alert_structs::AlertKind;
+use crate::utils::alerts::{DEFAULT_CHANGE_WINDOW, MAX_ACTIVE_ALERTS};
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::AccessToken;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, post, web, HttpResponse};
+use entity::{alerts, assets, orders};
+use sea_orm::prelude::{DateTime, Decimal};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[utoipa::path(
+    request_body = AlertInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/alerts/create")]
+pub async fn alert_create(
+    state: web::Data<AppState>,
+    input: web::Json<AlertInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    let active = alerts::Entity::find()
+        .filter(alerts::Column::UserId.eq(token.claims.sub))
+        .filter(alerts::Column::Active.eq(true))
+        .count(state.db.as_ref())
+        .await?;
+    if active >= MAX_ACTIVE_ALERTS {
+        return Err(AppError::Validation(format!(
+            "No more than {MAX_ACTIVE_ALERTS} active alerts allowed"
+        )));
+    }
+
+    let mut alert = alerts::ActiveModel {
+        user_id: Set(token.claims.sub),
+        kind: Set(input.kind.name().to_string()),
+        email: Set(input.email),
+        ..Default::default()
+    };
+    match input.kind {
+        AlertKind::Above | AlertKind::Below | AlertKind::Change => {
+            let asset_id = input
+                .asset_id
+                .ok_or(AppError::Validation("asset_id is required".to_string()))?;
+            assets::Entity::find_by_id(asset_id)
+                .one(state.db.as_ref())
+                .await?
+                .ok_or(AppError::NotFound("No asset"))?;
+            let threshold = input
+                .threshold
+                .and_then(Decimal::from_f64_retain)
+                .filter(|threshold| threshold.is_sign_positive() && !threshold.is_zero())
+                .ok_or(AppError::Validation("Wrong threshold".to_string()))?;
+            alert.asset_id = Set(Some(asset_id));
+            alert.threshold = Set(Some(threshold));
+            if input.kind == AlertKind::Change {
+                let window = input.window_minutes.unwrap_or(DEFAULT_CHANGE_WINDOW);
+                // Minute history is kept in Redis for a day
+                if !(1..=1440).contains(&window) {
+                    return Err(AppError::Validation(
+                        "window_minutes must be between 1 and 1440".to_string(),
+                    ));
+                }
+                alert.window_minutes = Set(Some(window));
+            }
+        }
+        AlertKind::OrderFilled => {
+            let order_id = input
+                .order_id
+                .ok_or(AppError::Validation("order_id is required".to_string()))?;
+            let order = orders::Entity::find_by_id(order_id)
+                .filter(orders::Column::UserId.eq(token.claims.sub))
+                .one(state.db.as_ref())
+                .await?
+                .ok_or(AppError::NotFound("No order"))?;
+            if order.status != "pending" {
+                return Err(AppError::OrderNotExecutable("Order is not pending"));
+            }
+            alert.order_id = Set(Some(order.id));
+            alert.asset_id = Set(Some(order.asset_id));
+        }
+    }
+    let alert = alert.insert(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<AlertInfo> {
+        status: ResponseStatus::Ok,
+        data: alert.into(),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/alerts")]
+pub async fn alerts_list(
+    state: web::Data<AppState>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let data = alerts::Entity::find()
+        .filter(alerts::Column::UserId.eq(token.claims.sub))
+        .order_by_desc(alerts::Column::CreatedAt)
+        .all(state.db.as_ref())
+        .await?
+        .into_iter()
+        .map(AlertInfo::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<AlertInfo>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(AlertPath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/alerts/{alert_id}/delete")]
+pub async fn alert_delete(
+    state: web::Data<AppState>,
+    path: web::Path<AlertPath>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let alert = alerts::Entity::find_by_id(path.alert_id)
+        .filter(alerts::Column::UserId.eq(token.claims.sub))
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No alert"))?;
+    alert.delete(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AlertInput {
+    pub kind: AlertKind,
+    pub asset_id: Option<i32>,
+    pub order_id: Option<i32>,
+    /// Price for `above` / `below`, percent for `change`
+    pub threshold: Option<f64>,
+    /// Window for `change`, 60 minutes by default
+    pub window_minutes: Option<i32>,
+    /// Also send the alert by email
+    #[serde(default)]
+    pub email: bool,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct AlertPath {
+    pub alert_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct AlertInfo {
+    pub id: i32,
+    pub kind: String,
+    pub asset_id: Option<i32>,
+    pub order_id: Option<i32>,
+    pub threshold: Option<Decimal>,
+    pub window_minutes: Option<i32>,
+    pub email: bool,
+    pub active: bool,
+    pub triggered_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+impl From<alerts::Model> for AlertInfo {
+    fn from(alert: alerts::Model) -> Self {
+        Self {
+            id: alert.id,
+            kind: alert.kind,
+            asset_id: alert.asset_id,
+            order_id: alert.order_id,
+            threshold: alert.threshold,
+            window_minutes: alert.window_minutes,
+            email: alert.email,
+            active: alert.active,
+            triggered_at: alert.triggered_at,
+            created_at: alert.created_at,
+        }
+    }
+}
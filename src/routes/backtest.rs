@@ -0,0 +1,80 @@
+use crate::bots::backtest::{load_history, run_backtest, BacktestReport};
+use crate::bots::script::ScriptStrategy;
+use crate::bots::strategy::{build_strategy, Strategy};
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::AccessToken;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
+use chrono::{Duration, NaiveDateTime, Utc};
+use sea_orm::prelude::Decimal;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[utoipa::path(
+    request_body = BacktestInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/backtest")]
+pub async fn backtest(
+    state: web::Data<AppState>,
+    input: web::Json<BacktestInput>,
+    _token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    if input.asset_ids.is_empty() {
+        return Err(AppError::Validation("No assets to test".into()));
+    }
+    let initial_cash = Decimal::from_f64_retain(input.initial_cash.unwrap_or(10_000.0))
+        .map(|cash| cash.round_dp(3))
+        .filter(|cash| cash.is_sign_positive() && !cash.is_zero())
+        .ok_or(AppError::Validation("Wrong initial_cash".into()))?;
+    let to = input.to.unwrap_or_else(|| Utc::now().naive_utc());
+    let from = input.from.unwrap_or(to - Duration::days(7));
+    if from >= to {
+        return Err(AppError::Validation("`from` must be before `to`".into()));
+    }
+
+    let mut strategy: Box<dyn Strategy> = match (input.strategy.as_str(), input.source) {
+        ("script", Some(source)) => Box::new(ScriptStrategy::new(&source)?),
+        ("script", None) => return Err(AppError::Validation("Script source is missing".into())),
+        (name, _) => build_strategy(name, &input.params.unwrap_or(serde_json::json!({})))?,
+    };
+
+    let history = load_history(state.db.as_ref(), &state.cache, &input.asset_ids, from, to).await?;
+    if history.is_empty() {
+        return Err(AppError::NotFound("No price history in this range"));
+    }
+
+    // Scripts may burn their whole operation budget on every tick, keep that off the workers
+    let report = web::block(move || run_backtest(strategy.as_mut(), &history, initial_cash))
+        .await
+        .map_err(AppError::internal)?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<BacktestReport> {
+        status: ResponseStatus::Ok,
+        data: report,
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BacktestInput {
+    /// A built-in strategy name or `script` together with `source`
+    strategy: String,
+    #[schema(value_type = Option<Object>)]
+    params: Option<serde_json::Value>,
+    source: Option<String>,
+    asset_ids: Vec<i32>,
+    /// Defaults to a week before `to`
+    #[schema(value_type = Option<String>)]
+    from: Option<NaiveDateTime>,
+    /// Defaults to now
+    #[schema(value_type = Option<String>)]
+    to: Option<NaiveDateTime>,
+    initial_cash: Option<f64>,
+}
@@ -1,9 +1,10 @@
 use crate::utils::jwt::AccessToken;
+use crate::utils::app_error::AppError;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
 use entity::{messages, users};
-use sea_orm::prelude::DateTime;
+use sea_orm::prelude::{DateTime, Json};
 use sea_orm::{ColumnTrait, Condition, EntityTrait, FromQueryResult, QuerySelect};
 use sea_orm::{QueryFilter, QueryOrder};
 use serde::{Deserialize, Serialize};
@@ -22,60 +23,51 @@ pub async fn chat_history(
     token: AccessToken,
     params: web::Path<HistoryParams>,
     query: web::Query<HistoryQuery>,
-) -> impl Responder {
-    let user_data = try_or_http_err!(
-        users::Entity::find_by_id(token.claims.sub)
-            .one(state.db.as_ref())
-            .await
-    );
-    if let Some(user) = user_data {
-        let data = try_or_http_err!(
-            messages::Entity::find()
-                .filter(
-                    Condition::any()
-                        .add({
-                            let cond = Condition::all()
-                                .add(messages::Column::FromId.eq(user.id))
-                                .add(messages::Column::RecipientId.eq(params.user_id));
+) -> Result<HttpResponse, AppError> {
+    let user = users::Entity::find_by_id(token.claims.sub)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
 
-                            if let Some(before) = query.before_message_id {
-                                cond.add(messages::Column::Id.lt(before))
-                            } else {
-                                cond
-                            }
-                        })
-                        .add({
-                            let cond = Condition::all()
-                                .add(messages::Column::RecipientId.eq(user.id))
-                                .add(messages::Column::FromId.eq(params.user_id));
-                    
-                            if let Some(before) = query.before_message_id {
-                                cond.add(messages::Column::Id.lt(before))
-                            } else {
-                                cond
-                            }
-                        })
-                )
-                .column_as(messages::Column::Id, "message_id")
-                .order_by_asc(messages::Column::CreatedAt)
-                .limit(query.limit)
-                .into_model::<ChatMsg>()
-                .all(state.db.as_ref())
-                .await
-        );
+    let data = messages::Entity::find()
+        .filter(
+            Condition::any()
+                .add({
+                    let cond = Condition::all()
+                        .add(messages::Column::FromId.eq(user.id))
+                        .add(messages::Column::RecipientId.eq(params.user_id));
 
-        return HttpResponse::Ok().json(CommonResponse::<Vec<ChatMsg>> {
-            status: ResponseStatus::Ok,
-            data,
-            error: None,
-        });
-    }
+                    if let Some(before) = query.before_message_id {
+                        cond.add(messages::Column::Id.lt(before))
+                    } else {
+                        cond
+                    }
+                })
+                .add({
+                    let cond = Condition::all()
+                        .add(messages::Column::RecipientId.eq(user.id))
+                        .add(messages::Column::FromId.eq(params.user_id));
+            
+                    if let Some(before) = query.before_message_id {
+                        cond.add(messages::Column::Id.lt(before))
+                    } else {
+                        cond
+                    }
+                })
+        )
+        .column_as(messages::Column::Id, "message_id")
+        .order_by_asc(messages::Column::CreatedAt)
+        .limit(query.limit)
+        .into_model::<ChatMsg>()
+        .all(state.db.as_ref())
+        .await?;
 
-    HttpResponse::Ok().json(CommonResponse::<()> {
-        status: ResponseStatus::Error,
-        data: (),
-        error: Some("No user".into()),
-    })
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<ChatMsg>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
 }
 
 #[derive(FromQueryResult, Serialize)]
@@ -83,8 +75,13 @@ pub struct ChatMsg {
     message_id: i32,
     from_id: i32,
     recipient_id: i32,
+    /// Empty once the message is deleted
     text: String,
+    /// Trade, order or asset card
+    attachment: Option<Json>,
     created_at: DateTime,
+    edited_at: Option<DateTime>,
+    deleted_at: Option<DateTime>,
 }
 
 #[derive(Debug, Deserialize, ToSchema, IntoParams)]
@@ -0,0 +1,71 @@
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::AccessToken;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+#[utoipa::path(
+    params(MessagePath),
+    request_body = MessageEditInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/messages/{message_id}/edit")]
+pub async fn chat_message_edit(
+    state: web::Data<AppState>,
+    path: web::Path<MessagePath>,
+    input: web::Json<MessageEditInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    state
+        .edit_message(token.claims.sub, path.message_id, input.text)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(MessagePath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/messages/{message_id}/delete")]
+pub async fn chat_message_delete(
+    state: web::Data<AppState>,
+    path: web::Path<MessagePath>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    state
+        .delete_message(token.claims.sub, path.message_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct MessagePath {
+    /// Own private message
+    pub message_id: i32,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct MessageEditInput {
+    pub text: String,
+}
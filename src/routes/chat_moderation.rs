@@ -0,0 +1,240 @@
+use crate::structs::moderation_structs::{MessageType, ReportStatus};
+use crate::utils::app_error::AppError;
+use crate::utils::chat_rooms::check_room_readable;
+use crate::utils::jwt::AccessToken;
+use crate::utils::leagues::find_member;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, post, web, HttpResponse};
+use entity::{chat_room_messages, league_messages, message_reports, messages, user_blocks, users};
+use sea_orm::prelude::DateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+const MAX_REPORT_REASON: usize = 500;
+
+#[utoipa::path(
+    request_body = BlockInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/block")]
+pub async fn chat_block(
+    state: web::Data<AppState>,
+    input: web::Json<BlockInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    if input.user_id == token.claims.sub {
+        return Err(AppError::Validation("Can't block yourself".to_string()));
+    }
+    users::Entity::find_by_id(input.user_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
+
+    user_blocks::ActiveModel {
+        user_id: Set(token.claims.sub),
+        blocked_id: Set(input.user_id),
+        ..Default::default()
+    }
+    .insert(state.db.as_ref())
+    .await
+    .map_err(|err| match AppError::from(err) {
+        AppError::AlreadyExists(_) => AppError::AlreadyExists("User is already blocked"),
+        err => err,
+    })?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    request_body = BlockInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/unblock")]
+pub async fn chat_unblock(
+    state: web::Data<AppState>,
+    input: web::Json<BlockInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let deleted = user_blocks::Entity::delete_many()
+        .filter(user_blocks::Column::UserId.eq(token.claims.sub))
+        .filter(user_blocks::Column::BlockedId.eq(input.user_id))
+        .exec(state.db.as_ref())
+        .await?;
+    if deleted.rows_affected == 0 {
+        return Err(AppError::NotFound("User is not blocked"));
+    }
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/chat/blocks")]
+pub async fn chat_blocks_list(
+    state: web::Data<AppState>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let data = user_blocks::Entity::find()
+        .filter(user_blocks::Column::UserId.eq(token.claims.sub))
+        .join(JoinType::InnerJoin, user_blocks::Relation::Users2.def())
+        .select_only()
+        .column_as(user_blocks::Column::BlockedId, "user_id")
+        .column(users::Column::Username)
+        .column(user_blocks::Column::CreatedAt)
+        .order_by_desc(user_blocks::Column::CreatedAt)
+        .into_model::<BlockedUser>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<BlockedUser>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    request_body = ReportInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/report")]
+pub async fn chat_report(
+    state: web::Data<AppState>,
+    input: web::Json<ReportInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    let reason = input.reason.trim().to_string();
+    if reason.is_empty() || reason.chars().count() > MAX_REPORT_REASON {
+        return Err(AppError::Validation(format!(
+            "Reason must be 1 to {MAX_REPORT_REASON} characters"
+        )));
+    }
+    let (reported_id, text) = __visible_message(
+        state.db.as_ref(),
+        input.message_type,
+        input.message_id,
+        token.claims.sub,
+    )
+    .await?;
+    if reported_id == token.claims.sub {
+        return Err(AppError::Validation("Can't report your own message".to_string()));
+    }
+
+    let report = message_reports::ActiveModel {
+        reporter_id: Set(token.claims.sub),
+        reported_id: Set(reported_id),
+        message_type: Set(input.message_type.name().to_string()),
+        message_id: Set(input.message_id),
+        text: Set(text),
+        reason: Set(reason),
+        status: Set(ReportStatus::Open.name().to_string()),
+        ..Default::default()
+    }
+    .insert(state.db.as_ref())
+    .await
+    .map_err(|err| match AppError::from(err) {
+        AppError::AlreadyExists(_) => AppError::AlreadyExists("Message is already reported"),
+        err => err,
+    })?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<ReportCreated> {
+        status: ResponseStatus::Ok,
+        data: ReportCreated { id: report.id },
+        error: None,
+        code: None,
+    }))
+}
+
+/// Finds a message the user can see and returns its author and text.
+async fn __visible_message<C: ConnectionTrait>(
+    conn: &C,
+    message_type: MessageType,
+    message_id: i32,
+    user_id: i32,
+) -> Result<(i32, String), AppError> {
+    match message_type {
+        MessageType::Private => {
+            let message = messages::Entity::find_by_id(message_id)
+                .one(conn)
+                .await?
+                .filter(|message| message.from_id == user_id || message.recipient_id == user_id)
+                .ok_or(AppError::NotFound("No message"))?;
+            // A deleted message can still be reported, the moderator sees its original text
+            let text = match message.deleted_at {
+                Some(_) => message.deleted_text.unwrap_or_default(),
+                None => message.text,
+            };
+            Ok((message.from_id, text))
+        }
+        MessageType::League => {
+            let message = league_messages::Entity::find_by_id(message_id)
+                .one(conn)
+                .await?
+                .ok_or(AppError::NotFound("No message"))?;
+            find_member(conn, message.league_id, user_id).await?;
+            Ok((message.from_id, message.text))
+        }
+        MessageType::Room => {
+            let message = chat_room_messages::Entity::find_by_id(message_id)
+                .one(conn)
+                .await?
+                .ok_or(AppError::NotFound("No message"))?;
+            check_room_readable(conn, message.room_id, user_id).await?;
+            Ok((message.from_id, message.text))
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BlockInput {
+    pub user_id: i32,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ReportInput {
+    pub message_type: MessageType,
+    pub message_id: i32,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct ReportCreated {
+    pub id: i32,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct BlockedUser {
+    pub user_id: i32,
+    pub username: String,
+    pub created_at: DateTime,
+}
@@ -0,0 +1,40 @@
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::AccessToken;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[utoipa::path(
+    request_body = ChatReadInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/read")]
+pub async fn chat_read(
+    state: web::Data<AppState>,
+    input: web::Json<ChatReadInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    state
+        .mark_read(token.claims.sub, input.user_id, input.message_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ChatReadInput {
+    /// The other user of the conversation
+    user_id: i32,
+    /// Everything up to and including this message is read
+    message_id: i32,
+}
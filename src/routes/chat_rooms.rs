@@ -0,0 +1,579 @@
+use crate::structs::chat_room_structs::{RoomKind, RoomRole};
+use crate::utils::app_error::AppError;
+use crate::structs::ws_structs::Topic;
+use crate::utils::chat_rooms::{
+    asset_room, check_room_readable, find_room, find_room_admin, find_room_member,
+};
+use crate::utils::jwt::AccessToken;
+use crate::utils::pubsub::publish_unsubscribe;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, post, web, HttpResponse};
+use entity::{chat_room_members, chat_room_messages, chat_rooms, users};
+use redis::Client;
+use sea_orm::prelude::DateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, JoinType,
+    ModelTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_ROOM_NAME: usize = 64;
+const MAX_GROUP_MEMBERS: usize = 100;
+const MAX_PAGE: u64 = 100;
+
+#[utoipa::path(
+    request_body = RoomInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/rooms")]
+pub async fn room_create(
+    state: web::Data<AppState>,
+    input: web::Json<RoomInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    let name = input.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_ROOM_NAME {
+        return Err(AppError::Validation(format!(
+            "Room name must be 1 to {MAX_ROOM_NAME} characters"
+        )));
+    }
+    let mut member_ids = input.member_ids;
+    member_ids.retain(|user_id| *user_id != token.claims.sub);
+    member_ids.sort_unstable();
+    member_ids.dedup();
+    if member_ids.len() + 1 > MAX_GROUP_MEMBERS {
+        return Err(AppError::Validation(format!(
+            "A group has at most {MAX_GROUP_MEMBERS} members"
+        )));
+    }
+    let known = users::Entity::find()
+        .filter(users::Column::Id.is_in(member_ids.clone()))
+        .count(state.db.as_ref())
+        .await?;
+    if known != member_ids.len() as u64 {
+        return Err(AppError::Validation("Unknown member_ids".to_string()));
+    }
+
+    let txn = state.db.begin().await?;
+    let room = chat_rooms::ActiveModel {
+        kind: Set(RoomKind::Group.name().to_string()),
+        name: Set(name),
+        owner_id: Set(Some(token.claims.sub)),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    let members = std::iter::once((token.claims.sub, RoomRole::Admin))
+        .chain(member_ids.into_iter().map(|user_id| (user_id, RoomRole::Member)))
+        .map(|(user_id, role)| chat_room_members::ActiveModel {
+            room_id: Set(room.id),
+            user_id: Set(user_id),
+            role: Set(role.name().to_string()),
+            ..Default::default()
+        });
+    chat_room_members::Entity::insert_many(members)
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<RoomInfo> {
+        status: ResponseStatus::Ok,
+        data: RoomInfo::new(room, Some(RoomRole::Admin.name().to_string())),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/chat/rooms")]
+pub async fn rooms_list(
+    state: web::Data<AppState>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let data = chat_rooms::Entity::find()
+        .join(JoinType::InnerJoin, chat_rooms::Relation::ChatRoomMembers.def())
+        .filter(chat_room_members::Column::UserId.eq(token.claims.sub))
+        .select_only()
+        .column(chat_rooms::Column::Id)
+        .column(chat_rooms::Column::Kind)
+        .column(chat_rooms::Column::Name)
+        .column(chat_rooms::Column::AssetId)
+        .column(chat_rooms::Column::CreatedAt)
+        .column(chat_room_members::Column::Role)
+        .order_by_desc(chat_room_members::Column::JoinedAt)
+        .into_model::<RoomInfo>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<RoomInfo>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(AssetRoomPath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/chat/rooms/asset/{asset_id}")]
+pub async fn asset_room_info(
+    state: web::Data<AppState>,
+    path: web::Path<AssetRoomPath>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let room = asset_room(state.db.as_ref(), path.asset_id).await?;
+    let role = __role(state.db.as_ref(), room.id, token.claims.sub).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<RoomInfo> {
+        status: ResponseStatus::Ok,
+        data: RoomInfo::new(room, role),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(RoomPath, RoomMembersQuery),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/chat/rooms/{room_id}")]
+pub async fn room_info(
+    state: web::Data<AppState>,
+    path: web::Path<RoomPath>,
+    query: web::Query<RoomMembersQuery>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let room = find_room(state.db.as_ref(), path.room_id).await?;
+    let role = __role(state.db.as_ref(), room.id, token.claims.sub).await?;
+    if room.kind == RoomKind::Group.name() && role.is_none() {
+        return Err(AppError::Forbidden("Not a room member"));
+    }
+    let member_count = chat_room_members::Entity::find()
+        .filter(chat_room_members::Column::RoomId.eq(room.id))
+        .count(state.db.as_ref())
+        .await?;
+    let members = chat_room_members::Entity::find()
+        .filter(chat_room_members::Column::RoomId.eq(room.id))
+        .join(JoinType::InnerJoin, chat_room_members::Relation::Users.def())
+        .select_only()
+        .column(chat_room_members::Column::UserId)
+        .column(users::Column::Username)
+        .column(chat_room_members::Column::Role)
+        .column(chat_room_members::Column::JoinedAt)
+        .order_by_asc(chat_room_members::Column::JoinedAt)
+        .order_by_asc(chat_room_members::Column::Id)
+        .offset(query.offset)
+        .limit(query.limit.min(MAX_PAGE))
+        .into_model::<RoomMember>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<RoomDetails> {
+        status: ResponseStatus::Ok,
+        data: RoomDetails {
+            room: RoomInfo::new(room, role),
+            member_count,
+            members,
+        },
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(RoomPath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/rooms/{room_id}/join")]
+pub async fn room_join(
+    state: web::Data<AppState>,
+    path: web::Path<RoomPath>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let room = find_room(state.db.as_ref(), path.room_id).await?;
+    if room.kind != RoomKind::Asset.name() {
+        return Err(AppError::Forbidden("Group members are added by its admins"));
+    }
+    if find_room_member(state.db.as_ref(), room.id, token.claims.sub).await.is_ok() {
+        return Err(AppError::AlreadyExists("Already a room member"));
+    }
+    __add_member(state.db.as_ref(), room.id, token.claims.sub, RoomRole::Member).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<RoomInfo> {
+        status: ResponseStatus::Ok,
+        data: RoomInfo::new(room, Some(RoomRole::Member.name().to_string())),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(RoomPath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/rooms/{room_id}/leave")]
+pub async fn room_leave(
+    state: web::Data<AppState>,
+    path: web::Path<RoomPath>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let room = find_room(state.db.as_ref(), path.room_id).await?;
+    let member = find_room_member(state.db.as_ref(), room.id, token.claims.sub).await?;
+    __remove_member(state.db.as_ref(), state.cache.as_ref(), &room, member).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(RoomPath),
+    request_body = RoomMemberInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/rooms/{room_id}/members")]
+pub async fn room_member_add(
+    state: web::Data<AppState>,
+    path: web::Path<RoomPath>,
+    input: web::Json<RoomMemberInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let room = __find_group(state.db.as_ref(), path.room_id).await?;
+    find_room_admin(state.db.as_ref(), room.id, token.claims.sub).await?;
+    users::Entity::find_by_id(input.user_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
+    if find_room_member(state.db.as_ref(), room.id, input.user_id).await.is_ok() {
+        return Err(AppError::AlreadyExists("Already a room member"));
+    }
+    let members = chat_room_members::Entity::find()
+        .filter(chat_room_members::Column::RoomId.eq(room.id))
+        .count(state.db.as_ref())
+        .await?;
+    if members as usize >= MAX_GROUP_MEMBERS {
+        return Err(AppError::Validation(format!(
+            "A group has at most {MAX_GROUP_MEMBERS} members"
+        )));
+    }
+    __add_member(state.db.as_ref(), room.id, input.user_id, RoomRole::Member).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(RoomPath),
+    request_body = RoomMemberInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/rooms/{room_id}/members/remove")]
+pub async fn room_member_remove(
+    state: web::Data<AppState>,
+    path: web::Path<RoomPath>,
+    input: web::Json<RoomMemberInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let room = __find_group(state.db.as_ref(), path.room_id).await?;
+    find_room_admin(state.db.as_ref(), room.id, token.claims.sub).await?;
+    if input.user_id == token.claims.sub {
+        return Err(AppError::Validation("Use leave to quit the room".to_string()));
+    }
+    let member = chat_room_members::Entity::find()
+        .filter(chat_room_members::Column::RoomId.eq(room.id))
+        .filter(chat_room_members::Column::UserId.eq(input.user_id))
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No room member"))?;
+    __remove_member(state.db.as_ref(), state.cache.as_ref(), &room, member).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(RoomPath),
+    request_body = RoomRoleInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/chat/rooms/{room_id}/role")]
+pub async fn room_member_role(
+    state: web::Data<AppState>,
+    path: web::Path<RoomPath>,
+    input: web::Json<RoomRoleInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let room = __find_group(state.db.as_ref(), path.room_id).await?;
+    find_room_admin(state.db.as_ref(), room.id, token.claims.sub).await?;
+    if input.user_id == token.claims.sub {
+        return Err(AppError::Validation("Can't change your own role".to_string()));
+    }
+    let member = chat_room_members::Entity::find()
+        .filter(chat_room_members::Column::RoomId.eq(room.id))
+        .filter(chat_room_members::Column::UserId.eq(input.user_id))
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No room member"))?;
+    let mut member: chat_room_members::ActiveModel = member.into();
+    member.role = Set(input.role.name().to_string());
+    member.update(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(RoomPath, RoomHistoryQuery),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/chat/rooms/{room_id}/history")]
+pub async fn room_history(
+    state: web::Data<AppState>,
+    path: web::Path<RoomPath>,
+    query: web::Query<RoomHistoryQuery>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    check_room_readable(state.db.as_ref(), path.room_id, token.claims.sub).await?;
+
+    let mut select = chat_room_messages::Entity::find()
+        .filter(chat_room_messages::Column::RoomId.eq(path.room_id));
+    if let Some(before) = query.before_message_id {
+        select = select.filter(chat_room_messages::Column::Id.lt(before));
+    }
+    let mut data = select
+        .select_only()
+        .column_as(chat_room_messages::Column::Id, "message_id")
+        .column(chat_room_messages::Column::FromId)
+        .column(chat_room_messages::Column::Text)
+        .column(chat_room_messages::Column::CreatedAt)
+        .order_by_desc(chat_room_messages::Column::Id)
+        .limit(query.limit.min(MAX_PAGE))
+        .into_model::<RoomChatMsg>()
+        .all(state.db.as_ref())
+        .await?;
+    data.reverse();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<RoomChatMsg>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+async fn __role<C: ConnectionTrait>(conn: &C, room_id: i32, user_id: i32) -> Result<Option<String>, AppError> {
+    match find_room_member(conn, room_id, user_id).await {
+        Ok(member) => Ok(Some(member.role)),
+        Err(AppError::Forbidden(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+async fn __find_group<C: ConnectionTrait>(conn: &C, room_id: i32) -> Result<chat_rooms::Model, AppError> {
+    let room = find_room(conn, room_id).await?;
+    if room.kind != RoomKind::Group.name() {
+        return Err(AppError::Forbidden("Asset rooms have no admins"));
+    }
+    Ok(room)
+}
+
+async fn __add_member<C: ConnectionTrait>(conn: &C, room_id: i32, user_id: i32, role: RoomRole) -> Result<(), AppError> {
+    chat_room_members::ActiveModel {
+        room_id: Set(room_id),
+        user_id: Set(user_id),
+        role: Set(role.name().to_string()),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
+
+/// Removes the member. A group that loses its last admin hands the role to the oldest
+/// member, an empty group is deleted.
+async fn __remove_member<C: TransactionTrait>(
+    db: &C,
+    cache: &Client,
+    room: &chat_rooms::Model,
+    member: chat_room_members::Model,
+) -> Result<(), AppError> {
+    let user_id = member.user_id;
+    let txn = db.begin().await?;
+    member.delete(&txn).await?;
+    if room.kind == RoomKind::Group.name() {
+        let remaining = chat_room_members::Entity::find()
+            .filter(chat_room_members::Column::RoomId.eq(room.id))
+            .order_by_asc(chat_room_members::Column::JoinedAt)
+            .order_by_asc(chat_room_members::Column::Id)
+            .all(&txn)
+            .await?;
+        match remaining.first() {
+            None => {
+                chat_rooms::Entity::delete_by_id(room.id).exec(&txn).await?;
+            }
+            Some(oldest) if !remaining.iter().any(|m| m.role == RoomRole::Admin.name()) => {
+                let mut oldest: chat_room_members::ActiveModel = oldest.clone().into();
+                oldest.role = Set(RoomRole::Admin.name().to_string());
+                oldest.update(&txn).await?;
+            }
+            Some(_) => {}
+        }
+    }
+    txn.commit().await?;
+    // Asset rooms stay readable after leaving, group rooms don't
+    if room.kind == RoomKind::Group.name() {
+        publish_unsubscribe(cache, &Topic::Room(room.id), user_id).await;
+    }
+    Ok(())
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RoomInput {
+    pub name: String,
+    /// Users added to the group besides the creator, who becomes its admin
+    #[serde(default)]
+    pub member_ids: Vec<i32>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RoomMemberInput {
+    pub user_id: i32,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RoomRoleInput {
+    pub user_id: i32,
+    pub role: RoomRole,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct RoomPath {
+    pub room_id: i32,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct AssetRoomPath {
+    pub asset_id: i32,
+}
+
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct RoomMembersQuery {
+    /// At most 100, larger values are clamped
+    #[serde(default = "__default_members_limit")]
+    pub limit: u64,
+    #[serde(default)]
+    pub offset: u64,
+}
+
+fn __default_members_limit() -> u64 {
+    MAX_PAGE
+}
+
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct RoomHistoryQuery {
+    /// At most 100, larger values are clamped
+    pub limit: u64,
+    pub before_message_id: Option<i32>,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct RoomInfo {
+    pub id: i32,
+    /// `group` or `asset`
+    pub kind: String,
+    pub name: String,
+    pub asset_id: Option<i32>,
+    pub created_at: DateTime,
+    /// Role of the requesting user, `null` when not a member
+    pub role: Option<String>,
+}
+
+impl RoomInfo {
+    fn new(room: chat_rooms::Model, role: Option<String>) -> Self {
+        Self {
+            id: room.id,
+            kind: room.kind,
+            name: room.name,
+            asset_id: room.asset_id,
+            created_at: room.created_at,
+            role,
+        }
+    }
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct RoomMember {
+    pub user_id: i32,
+    pub username: String,
+    pub role: String,
+    pub joined_at: DateTime,
+}
+
+#[derive(Serialize)]
+pub struct RoomDetails {
+    pub room: RoomInfo,
+    /// All members, `members` holds the requested page ordered by join time
+    pub member_count: u64,
+    pub members: Vec<RoomMember>,
+}
+
+#[derive(FromQueryResult, Serialize)]
+pub struct RoomChatMsg {
+    message_id: i32,
+    from_id: i32,
+    text: String,
+    created_at: DateTime,
+}
@@ -1,9 +1,8 @@
-use crate::try_or_http_err;
-use crate::unwrap_or_http_err_with_opt_msg;
+use crate::utils::app_error::AppError;
 use crate::utils::jwt::generate_access_token;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{extract_db_response_or_http_err_with_opt_msg, AppState, RECOVERSTORAGE};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::{AppState, RECOVERSTORAGE};
+use actix_web::{post, web, HttpResponse};
 use entity::users;
 use sea_orm::ColumnTrait;
 use sea_orm::EntityTrait;
@@ -19,46 +18,36 @@ use utoipa::ToSchema;
 pub async fn check_recover_code(
     state: web::Data<AppState>,
     input: web::Json<RecoverCodeInput>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let input = input.into_inner();
-    let user = extract_db_response_or_http_err_with_opt_msg!(
-        users::Entity::find()
-            .filter(users::Column::Email.eq(&input.email))
-            .one(state.db.as_ref())
-            .await,
-        "User not found"
-    );
+    let user = users::Entity::find()
+        .filter(users::Column::Email.eq(&input.email))
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("User not found"))?;
 
     let mut codes_storage = RECOVERSTORAGE.lock().await;
-    match codes_storage.get_mut(&user.id) {
-        Some(codes) => {
-            if codes.all().await.contains(&input.code) {
-                let access_token = try_or_http_err!(generate_access_token(
-                    user.id,
-                    user.username.as_str(),
-                    user.email.as_str(),
-                    state.jwt_secret.as_str()
-                ));
+    let codes = codes_storage
+        .get_mut(&user.id)
+        .ok_or(AppError::NotFound("No codes for user"))?;
 
-                return HttpResponse::Ok().json(CommonResponse::<RecoverResponse> {
-                    status: ResponseStatus::Ok,
-                    data: RecoverResponse { access_token },
-                    error: None,
-                });
-            }
-
-            HttpResponse::Forbidden().json(CommonResponse::<()> {
-                status: ResponseStatus::Error,
-                data: (),
-                error: Some("Wrong code".into()),
-            })
-        }
-        None => HttpResponse::BadRequest().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("No codes for user".into()),
-        }),
+    if !codes.all().await.contains(&input.code) {
+        return Err(AppError::WrongRecoverCode);
     }
+
+    let access_token = generate_access_token(
+        user.id,
+        user.username.as_str(),
+        user.email.as_str(),
+        state.jwt_secret.as_str()
+    )?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<RecoverResponse> {
+        status: ResponseStatus::Ok,
+        data: RecoverResponse { access_token },
+        error: None,
+        code: None,
+    }))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
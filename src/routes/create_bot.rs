@@ -1,33 +1,42 @@
+use crate::bots::strategy::build_strategy;
+use crate::utils::admin::AdminToken;
+use crate::utils::app_error::AppError;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
 use argon2::password_hash::SaltString;
 use argon2::{Argon2, PasswordHasher};
-use entity::prelude::Users;
-use entity::users;
+use entity::prelude::BotConfigs;
+use entity::{bot_configs, users};
 use rand_core::OsRng;
-use sea_orm::{EntityTrait, Set};
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 #[utoipa::path(
     request_body = BotInput,
-    tag="Market"
+    tag="Admin",
+    security(
+        ("bearer_token" = [])
+    )
 )]
 #[post("/api/v1/bots/create")]
 pub async fn create_bot(
     state: web::Data<AppState>,
     input: web::Json<BotInput>,
-) -> impl Responder {
+    _admin: AdminToken,
+) -> Result<HttpResponse, AppError> {
     let input = input.into_inner();
+    let params = input.params.unwrap_or(serde_json::json!({}));
+    if let Some(strategy) = &input.strategy {
+        build_strategy(strategy, &params)?;
+    }
 
     let salt = SaltString::generate(&mut OsRng);
     let password_hash = Argon2::default()
-        .hash_password(input.password.as_bytes(), &salt)
-        .unwrap()
+        .hash_password(input.password.as_bytes(), &salt)?
         .to_string();
 
-
     let bot = users::ActiveModel {
         username: Set(input.username),
         email: Set(input.email),
@@ -35,22 +44,38 @@ pub async fn create_bot(
         is_bot: Set(true),
         ..Default::default()
     };
-    let bot_id = try_or_http_err!(Users::insert(bot).exec(state.db.as_ref()).await);
+    let bot = bot.insert(state.db.as_ref()).await?;
+
+    if let Some(strategy) = input.strategy {
+        let config = bot_configs::ActiveModel {
+            user_id: Set(bot.id),
+            strategy: Set(strategy),
+            params: Set(params),
+            allocation: Set(bot.balance),
+            ..Default::default()
+        };
+        BotConfigs::insert(config).exec(state.db.as_ref()).await?;
+    }
 
-    HttpResponse::Ok().json(CommonResponse::<BotResponse> {
+    Ok(HttpResponse::Ok().json(CommonResponse::<BotResponse> {
         status: ResponseStatus::Ok,
         data: BotResponse {
-            bot_id: bot_id.last_insert_id,
+            bot_id: bot.id,
         },
         error: None,
-    })
+        code: None,
+    }))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct BotInput {
     username: String,
     email: String,
-    password: String
+    password: String,
+    /// One of `market_maker`, `momentum`, `mean_reversion`, `noise`; without it the bot stays idle
+    strategy: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    params: Option<serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -1,6 +1,9 @@
+use crate::structs::notification_structs::NotificationKind;
+use crate::utils::app_error::AppError;
+use crate::utils::notifications::notify_players;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
 use entity::events;
 use entity::prelude::Events;
 use sea_orm::{EntityTrait, Set};
@@ -15,23 +18,31 @@ use utoipa::ToSchema;
 pub async fn create_event(
     state: web::Data<AppState>,
     input: web::Json<EventInput>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let input = input.into_inner();
 
     let event = events::ActiveModel {
-        title: Set(input.title),
-        description: Set(input.description),
+        title: Set(input.title.clone()),
+        description: Set(input.description.clone()),
         ..Default::default()
     };
-    let event_id = try_or_http_err!(Events::insert(event).exec(state.db.as_ref()).await);
+    let event_id = Events::insert(event).exec(state.db.as_ref()).await?;
 
-    HttpResponse::Ok().json(CommonResponse::<EventResponse> {
+    let notification = EventNotification {
+        event_id: event_id.last_insert_id,
+        title: input.title,
+        description: input.description,
+    };
+    notify_players(state.db.as_ref(), state.cache.as_ref(), NotificationKind::Event, &notification).await;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<EventResponse> {
         status: ResponseStatus::Ok,
         data: EventResponse {
             event_id: event_id.last_insert_id,
         },
         error: None,
-    })
+        code: None,
+    }))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -44,3 +55,10 @@ pub struct EventInput {
 pub struct EventResponse {
     event_id: i32,
 }
+
+#[derive(Serialize)]
+struct EventNotification {
+    event_id: i32,
+    title: String,
+    description: String,
+}
@@ -0,0 +1,106 @@
+use crate::utils::app_error::AppError;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use entity::net_worth_snapshots;
+use sea_orm::prelude::{DateTime as DbDateTime, Decimal};
+use sea_orm::{ColumnTrait, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_RANGE_DAYS: i64 = 366;
+const MAX_POINTS: usize = 500;
+
+/// Longer ranges are thinned to at most `MAX_POINTS` evenly spaced snapshots, the latest
+/// one is always kept.
+#[utoipa::path(params(EquityPath, EquityQuery), tag = "User")]
+#[get("/api/v1/equity/history/{user_id}")]
+pub async fn equity_history(
+    state: web::Data<AppState>,
+    path: web::Path<EquityPath>,
+    query: web::Query<EquityQuery>,
+) -> Result<HttpResponse, AppError> {
+    let to = match query.to {
+        Some(to) => __timestamp(to)?,
+        None => Utc::now().naive_utc(),
+    };
+    let from = match query.from {
+        Some(from) => __timestamp(from)?,
+        None => to - Duration::days(30),
+    };
+    if from > to {
+        return Err(AppError::Validation("from must not be after to".to_string()));
+    }
+    if to - from > Duration::days(MAX_RANGE_DAYS) {
+        return Err(AppError::Validation(format!(
+            "Range must not be longer than {MAX_RANGE_DAYS} days"
+        )));
+    }
+
+    let data = net_worth_snapshots::Entity::find()
+        .select_only()
+        .column(net_worth_snapshots::Column::Cash)
+        .column(net_worth_snapshots::Column::InOrders)
+        .column(net_worth_snapshots::Column::Assets)
+        .column(net_worth_snapshots::Column::Total)
+        .column(net_worth_snapshots::Column::Place)
+        .column(net_worth_snapshots::Column::CreatedAt)
+        .filter(net_worth_snapshots::Column::UserId.eq(path.user_id))
+        .filter(net_worth_snapshots::Column::CreatedAt.between(from, to))
+        .order_by_asc(net_worth_snapshots::Column::CreatedAt)
+        .into_model::<EquityPoint>()
+        .all(state.db.as_ref())
+        .await?;
+    let data = __downsample(data);
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<EquityPoint>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+fn __downsample(points: Vec<EquityPoint>) -> Vec<EquityPoint> {
+    if points.len() <= MAX_POINTS {
+        return points;
+    }
+    let step = points.len().div_ceil(MAX_POINTS);
+    let last = points.len() - 1;
+    points
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| index % step == 0 || *index == last)
+        .map(|(_, point)| point)
+        .collect()
+}
+
+fn __timestamp(timestamp: i64) -> Result<DbDateTime, AppError> {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.naive_utc())
+        .ok_or(AppError::Validation("Invalid timestamp".to_string()))
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct EquityPath {
+    pub user_id: i32,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct EquityQuery {
+    /// Unix timestamp, defaults to 30 days before `to`, at most 366 days before it
+    pub from: Option<i64>,
+    /// Unix timestamp, defaults to now
+    pub to: Option<i64>,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct EquityPoint {
+    pub cash: Decimal,
+    pub in_orders: Decimal,
+    pub assets: Decimal,
+    pub total: Decimal,
+    pub place: Option<i32>,
+    pub created_at: DbDateTime,
+}
@@ -1,11 +1,13 @@
 use crate::utils::jwt::AccessToken;
+use crate::utils::app_error::AppError;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{get, web, HttpResponse, Responder};
-use entity::{messages, users};
-use sea_orm::{ColumnTrait, Condition, FromQueryResult, QueryFilter};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
+use entity::{chat_reads, messages, users};
+use sea_orm::{ColumnTrait, Condition, DatabaseBackend, FromQueryResult, QueryFilter, Statement};
 use sea_orm::{EntityTrait, QuerySelect};
 use serde::Serialize;
+use std::collections::HashMap;
 
 #[utoipa::path(
     tag = "User",
@@ -14,24 +16,22 @@ use serde::Serialize;
     )
 )]
 #[get("/api/v1/chats/list")]
-pub async fn get_chats(state: web::Data<AppState>, token: AccessToken) -> impl Responder {
+pub async fn get_chats(state: web::Data<AppState>, token: AccessToken) -> Result<HttpResponse, AppError> {
     let user_id = token.claims.sub;
 
-    let users_list = try_or_http_err!(
-        messages::Entity::find()
-            .distinct()
-            .select_only()
-            .column(messages::Column::FromId)
-            .column(messages::Column::RecipientId)
-            .filter(
-                Condition::any()
-                    .add(messages::Column::FromId.eq(user_id))
-                    .add(messages::Column::RecipientId.eq(user_id))
-            )
-            .into_tuple::<(i32, i32)>()
-            .all(state.db.as_ref())
-            .await
-    );
+    let users_list = messages::Entity::find()
+        .distinct()
+        .select_only()
+        .column(messages::Column::FromId)
+        .column(messages::Column::RecipientId)
+        .filter(
+            Condition::any()
+                .add(messages::Column::FromId.eq(user_id))
+                .add(messages::Column::RecipientId.eq(user_id))
+        )
+        .into_tuple::<(i32, i32)>()
+        .all(state.db.as_ref())
+        .await?;
 
     let unique_ids: Vec<i32> = users_list
         .into_iter()
@@ -41,25 +41,70 @@ pub async fn get_chats(state: web::Data<AppState>, token: AccessToken) -> impl R
         .into_iter()
         .collect();
 
-    let data = try_or_http_err!(
-        users::Entity::find()
-            .filter(users::Column::Id.is_in(unique_ids))
-            .select_only()
-            .columns([users::Column::Id, users::Column::Username])
-            .into_model::<ChatsResponse>()
-            .all(state.db.as_ref())
-            .await
-    );
+    let unread: HashMap<i32, i64> = UnreadCount::find_by_statement(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "SELECT m.from_id AS peer_id, COUNT(*) AS unread FROM messages m \
+         LEFT JOIN chat_reads r ON r.user_id = m.recipient_id AND r.peer_id = m.from_id \
+         WHERE m.recipient_id = $1 AND m.id > COALESCE(r.last_read_message_id, 0) \
+         GROUP BY m.from_id",
+        [user_id.into()],
+    ))
+    .all(state.db.as_ref())
+    .await?
+    .into_iter()
+    .map(|count| (count.peer_id, count.unread))
+    .collect();
 
-    HttpResponse::Ok().json(CommonResponse::<Vec<ChatsResponse>> {
+    let peer_reads: HashMap<i32, i32> = chat_reads::Entity::find()
+        .filter(chat_reads::Column::PeerId.eq(user_id))
+        .all(state.db.as_ref())
+        .await?
+        .into_iter()
+        .map(|read| (read.user_id, read.last_read_message_id))
+        .collect();
+
+    let data = users::Entity::find()
+        .filter(users::Column::Id.is_in(unique_ids))
+        .select_only()
+        .columns([users::Column::Id, users::Column::Username])
+        .into_model::<ChatUser>()
+        .all(state.db.as_ref())
+        .await?
+        .into_iter()
+        .map(|user| ChatsResponse {
+            unread: unread.get(&user.id).copied().unwrap_or_default(),
+            peer_read_message_id: peer_reads.get(&user.id).copied(),
+            id: user.id,
+            username: user.username,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<ChatsResponse>> {
         status: ResponseStatus::Ok,
         data,
         error: None,
-    })
+        code: None,
+    }))
+}
+
+#[derive(FromQueryResult)]
+struct ChatUser {
+    id: i32,
+    username: String,
+}
+
+#[derive(FromQueryResult)]
+struct UnreadCount {
+    peer_id: i32,
+    unread: i64,
 }
 
-#[derive(Serialize, FromQueryResult)]
+#[derive(Serialize)]
 pub struct ChatsResponse {
     pub id: i32,
     pub username: String,
+    /// Messages from this user after the read marker
+    pub unread: i64,
+    /// How far this user has read the conversation
+    pub peer_read_message_id: Option<i32>,
 }
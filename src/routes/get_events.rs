@@ -1,6 +1,7 @@
+use crate::utils::app_error::AppError;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
 use entity::events;
 use sea_orm::prelude::DateTime;
 use sea_orm::{EntityTrait, FromQueryResult, QueryOrder, QuerySelect};
@@ -12,22 +13,21 @@ use utoipa::{IntoParams, ToSchema};
 pub async fn get_events(
     state: web::Data<AppState>,
     query: web::Query<EventsQuery>,
-) -> impl Responder {
-    let events_data = try_or_http_err!(
-        events::Entity::find()
-            .order_by_desc(events::Column::CreatedAt)
-            .limit(query.limit)
-            .offset(query.offset)
-            .into_model::<EventsResponse>()
-            .all(state.db.as_ref())
-            .await
-    );
+) -> Result<HttpResponse, AppError> {
+    let events_data = events::Entity::find()
+        .order_by_desc(events::Column::CreatedAt)
+        .limit(query.limit)
+        .offset(query.offset)
+        .into_model::<EventsResponse>()
+        .all(state.db.as_ref())
+        .await?;
 
-    HttpResponse::Ok().json(CommonResponse::<Vec<EventsResponse>> {
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<EventsResponse>> {
         status: ResponseStatus::Ok,
         data: events_data,
         error: None,
-    })
+        code: None,
+    }))
 }
 
 #[derive(Deserialize, ToSchema, IntoParams)]
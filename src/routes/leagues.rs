@@ -0,0 +1,455 @@
+use crate::routes::market_buy::__non_negative;
+use crate::structs::order_structs::OrderType;
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::AccessToken;
+use crate::utils::leagues::{
+    execute_league_market_order, find_league, find_member, generate_invite_code,
+    league_asset_ids, league_standings, LeagueStanding,
+};
+use crate::utils::market_execution::MarketFill;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, post, web, HttpResponse};
+use chrono::{DateTime, Utc};
+use entity::{assets, league_balances, league_members, league_messages, leagues};
+use sea_orm::prelude::{DateTime as DbDateTime, Decimal};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, FromQueryResult, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_LEAGUE_NAME: usize = 64;
+const MAX_STARTING_CAPITAL: i64 = 1_000_000;
+const MAX_CHAT_PAGE: u64 = 100;
+
+#[utoipa::path(
+    request_body = LeagueInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/leagues")]
+pub async fn league_create(
+    state: web::Data<AppState>,
+    input: web::Json<LeagueInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    let name = input.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_LEAGUE_NAME {
+        return Err(AppError::Validation(format!(
+            "League name must be 1 to {MAX_LEAGUE_NAME} characters"
+        )));
+    }
+    let starts_at = DateTime::from_timestamp(input.starts_at, 0)
+        .ok_or(AppError::Validation("Invalid starts_at".to_string()))?
+        .naive_utc();
+    let ends_at = DateTime::from_timestamp(input.ends_at, 0)
+        .ok_or(AppError::Validation("Invalid ends_at".to_string()))?
+        .naive_utc();
+    if ends_at <= starts_at || ends_at <= Utc::now().naive_utc() {
+        return Err(AppError::Validation(
+            "ends_at must be in the future and after starts_at".to_string(),
+        ));
+    }
+    let starting_capital = __non_negative(Some(input.starting_capital), "Wrong starting_capital")?
+        .unwrap_or_default()
+        .round_dp(3);
+    if starting_capital.is_zero() || starting_capital > Decimal::from(MAX_STARTING_CAPITAL) {
+        return Err(AppError::Validation(format!(
+            "starting_capital must be above 0 and at most {MAX_STARTING_CAPITAL}"
+        )));
+    }
+    let mut asset_ids = input.asset_ids;
+    asset_ids.sort_unstable();
+    asset_ids.dedup();
+    let known = assets::Entity::find()
+        .filter(assets::Column::Id.is_in(asset_ids.clone()))
+        .count(state.db.as_ref())
+        .await?;
+    if asset_ids.is_empty() || known != asset_ids.len() as u64 {
+        return Err(AppError::Validation("Unknown or empty asset_ids".to_string()));
+    }
+
+    let mut invite_code = generate_invite_code();
+    while leagues::Entity::find()
+        .filter(leagues::Column::InviteCode.eq(&invite_code))
+        .one(state.db.as_ref())
+        .await?
+        .is_some()
+    {
+        invite_code = generate_invite_code();
+    }
+
+    let txn = state.db.begin().await?;
+    let league = leagues::ActiveModel {
+        name: Set(name),
+        owner_id: Set(token.claims.sub),
+        invite_code: Set(invite_code),
+        starts_at: Set(starts_at),
+        ends_at: Set(ends_at),
+        starting_capital: Set(starting_capital),
+        asset_ids: Set(serde_json::json!(asset_ids)),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    league_members::ActiveModel {
+        league_id: Set(league.id),
+        user_id: Set(token.claims.sub),
+        balance: Set(starting_capital),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<LeagueCreated> {
+        status: ResponseStatus::Ok,
+        data: LeagueCreated {
+            id: league.id,
+            invite_code: league.invite_code,
+        },
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    request_body = LeagueJoinInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/leagues/join")]
+pub async fn league_join(
+    state: web::Data<AppState>,
+    input: web::Json<LeagueJoinInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let league = leagues::Entity::find()
+        .filter(leagues::Column::InviteCode.eq(input.invite_code.trim().to_uppercase()))
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No league"))?;
+    if league.ends_at <= Utc::now().naive_utc() {
+        return Err(AppError::Validation("League is over".to_string()));
+    }
+    if find_member(state.db.as_ref(), league.id, token.claims.sub).await.is_ok() {
+        return Err(AppError::AlreadyExists("Already a league member"));
+    }
+
+    league_members::ActiveModel {
+        league_id: Set(league.id),
+        user_id: Set(token.claims.sub),
+        balance: Set(league.starting_capital),
+        ..Default::default()
+    }
+    .insert(state.db.as_ref())
+    .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<LeagueJoined> {
+        status: ResponseStatus::Ok,
+        data: LeagueJoined {
+            league_id: league.id,
+        },
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/leagues")]
+pub async fn leagues_list(
+    state: web::Data<AppState>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let data = leagues::Entity::find()
+        .join(JoinType::InnerJoin, leagues::Relation::LeagueMembers.def())
+        .filter(league_members::Column::UserId.eq(token.claims.sub))
+        .order_by_desc(leagues::Column::StartsAt)
+        .all(state.db.as_ref())
+        .await?
+        .into_iter()
+        .map(LeagueInfo::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<LeagueInfo>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(LeaguePath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/leagues/{league_id}")]
+pub async fn league_info(
+    state: web::Data<AppState>,
+    path: web::Path<LeaguePath>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let league = find_league(state.db.as_ref(), path.league_id).await?;
+    let member = find_member(state.db.as_ref(), league.id, token.claims.sub).await?;
+    let holdings = league_balances::Entity::find()
+        .filter(league_balances::Column::MemberId.eq(member.id))
+        .filter(league_balances::Column::Amount.gt(Decimal::ZERO))
+        .join(JoinType::InnerJoin, league_balances::Relation::Assets.def())
+        .select_only()
+        .column(league_balances::Column::AssetId)
+        .column(assets::Column::Symbol)
+        .column(league_balances::Column::Amount)
+        .into_model::<LeagueHolding>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<LeagueDetails> {
+        status: ResponseStatus::Ok,
+        data: LeagueDetails {
+            league: league.into(),
+            balance: member.balance,
+            holdings,
+        },
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    request_body = LeagueMarketRequest,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/leagues/market/buy")]
+pub async fn league_market_buy(
+    state: web::Data<AppState>,
+    input: web::Json<LeagueMarketRequest>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    __league_market(state, input.into_inner(), token, OrderType::Buy).await
+}
+
+#[utoipa::path(
+    request_body = LeagueMarketRequest,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/leagues/market/sell")]
+pub async fn league_market_sell(
+    state: web::Data<AppState>,
+    input: web::Json<LeagueMarketRequest>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    __league_market(state, input.into_inner(), token, OrderType::Sell).await
+}
+
+async fn __league_market(
+    state: web::Data<AppState>,
+    input: LeagueMarketRequest,
+    token: AccessToken,
+    side: OrderType,
+) -> Result<HttpResponse, AppError> {
+    let amount = Decimal::from_f64_retain(input.amount)
+        .ok_or(AppError::Validation("Wrong amount".into()))?;
+    let fill = execute_league_market_order(
+        state.db.as_ref(),
+        &state.cache,
+        side,
+        input.league_id,
+        token.claims.sub,
+        input.asset_id,
+        amount,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<MarketFill> {
+        status: ResponseStatus::Ok,
+        data: fill,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(LeaguePath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/leagues/{league_id}/leaderboard")]
+pub async fn league_leaderboard(
+    state: web::Data<AppState>,
+    path: web::Path<LeaguePath>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let league = find_league(state.db.as_ref(), path.league_id).await?;
+    find_member(state.db.as_ref(), league.id, token.claims.sub).await?;
+    let data = league_standings(state.db.as_ref(), &state.cache, &league).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<LeagueStanding>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(LeaguePath, LeagueChatQuery),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/leagues/{league_id}/chat")]
+pub async fn league_chat_history(
+    state: web::Data<AppState>,
+    path: web::Path<LeaguePath>,
+    query: web::Query<LeagueChatQuery>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    find_member(state.db.as_ref(), path.league_id, token.claims.sub).await?;
+
+    let mut select = league_messages::Entity::find()
+        .filter(league_messages::Column::LeagueId.eq(path.league_id));
+    if let Some(before) = query.before_message_id {
+        select = select.filter(league_messages::Column::Id.lt(before));
+    }
+    let mut data = select
+        .select_only()
+        .column_as(league_messages::Column::Id, "message_id")
+        .column(league_messages::Column::FromId)
+        .column(league_messages::Column::Text)
+        .column(league_messages::Column::CreatedAt)
+        .order_by_desc(league_messages::Column::Id)
+        .limit(query.limit.min(MAX_CHAT_PAGE))
+        .into_model::<LeagueChatMsg>()
+        .all(state.db.as_ref())
+        .await?;
+    data.reverse();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<LeagueChatMsg>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LeagueInput {
+    pub name: String,
+    /// Unix timestamp
+    pub starts_at: i64,
+    /// Unix timestamp
+    pub ends_at: i64,
+    /// Virtual cash every member starts with
+    pub starting_capital: f64,
+    /// Assets members may trade
+    pub asset_ids: Vec<i32>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LeagueJoinInput {
+    pub invite_code: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LeagueMarketRequest {
+    pub league_id: i32,
+    pub asset_id: i32,
+    pub amount: f64,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct LeaguePath {
+    pub league_id: i32,
+}
+
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct LeagueChatQuery {
+    /// At most 100, larger values are clamped
+    pub limit: u64,
+    pub before_message_id: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct LeagueCreated {
+    pub id: i32,
+    pub invite_code: String,
+}
+
+#[derive(Serialize)]
+pub struct LeagueJoined {
+    pub league_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct LeagueInfo {
+    pub id: i32,
+    pub name: String,
+    pub owner_id: i32,
+    pub invite_code: String,
+    pub starts_at: DbDateTime,
+    pub ends_at: DbDateTime,
+    pub starting_capital: Decimal,
+    pub asset_ids: Vec<i32>,
+}
+
+impl From<leagues::Model> for LeagueInfo {
+    fn from(league: leagues::Model) -> Self {
+        Self {
+            asset_ids: league_asset_ids(&league),
+            id: league.id,
+            name: league.name,
+            owner_id: league.owner_id,
+            invite_code: league.invite_code,
+            starts_at: league.starts_at,
+            ends_at: league.ends_at,
+            starting_capital: league.starting_capital,
+        }
+    }
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct LeagueHolding {
+    pub asset_id: i32,
+    pub symbol: String,
+    pub amount: Decimal,
+}
+
+#[derive(Serialize)]
+pub struct LeagueDetails {
+    pub league: LeagueInfo,
+    /// Virtual cash of the requesting member
+    pub balance: Decimal,
+    pub holdings: Vec<LeagueHolding>,
+}
+
+#[derive(FromQueryResult, Serialize)]
+pub struct LeagueChatMsg {
+    message_id: i32,
+    from_id: i32,
+    text: String,
+    created_at: DbDateTime,
+}
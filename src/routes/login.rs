@@ -1,7 +1,8 @@
+use crate::utils::app_error::AppError;
 use crate::utils::jwt::{generate_access_token, generate_refresh_token};
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
 use argon2::password_hash::PasswordHash;
 use argon2::{Argon2, PasswordVerifier};
 use entity::users;
@@ -14,58 +15,52 @@ use utoipa::ToSchema;
     tag="Authorization"
 )]
 #[post("/api/v1/auth/login")]
-pub async fn login(state: web::Data<AppState>, input: web::Json<LoginInput>) -> impl Responder {
+pub async fn login(state: web::Data<AppState>, input: web::Json<LoginInput>) -> Result<HttpResponse, AppError> {
     let input = input.into_inner();
 
-    let user = try_or_http_err!(
-        users::Entity::find()
-            .filter(
-                Condition::any()
-                    .add(users::Column::Email.eq(&input.username))
-                    .add(users::Column::Username.eq(&input.username))
-            )
-            .one(state.db.as_ref())
-            .await
-    );
+    let user = users::Entity::find()
+        .filter(
+            Condition::any()
+                .add(users::Column::Email.eq(&input.username))
+                .add(users::Column::Username.eq(&input.username))
+        )
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
 
-    if let Some(user) = user {
-        let stored_hash = user.hashed_password;
-        let parsed_hash = PasswordHash::new(&stored_hash).expect("Failed to parse hash");
-        if Argon2::default()
-            .verify_password(input.password.as_bytes(), &parsed_hash)
-            .is_ok()
-        {
-            let access_token = try_or_http_err!(generate_access_token(
-                user.id,
-                user.username.as_str(),
-                user.email.as_str(),
-                state.jwt_secret.as_str()
-            ));
-            let refresh_token = try_or_http_err!(generate_refresh_token(
-                user.id,
-                user.username.as_str(),
-                user.email.as_str(),
-                state.jwt_secret.as_str()
-            ));
-
-            return HttpResponse::Ok().json(CommonResponse::<Option<LoginResponse>> {
-                status: ResponseStatus::Ok,
-                data: Some(LoginResponse {
-                    access_token,
-                    refresh_token,
-                    user_id: user.id,
-                    email: user.email,
-                    username: user.username,
-                }),
-                error: None,
-            });
-        }
+    let parsed_hash = PasswordHash::new(&user.hashed_password)?;
+    if Argon2::default()
+        .verify_password(input.password.as_bytes(), &parsed_hash)
+        .is_err()
+    {
+        return Err(AppError::InvalidCredentials);
     }
-    HttpResponse::BadRequest().json(CommonResponse::<Option<LoginResponse>> {
-        status: ResponseStatus::Error,
-        data: None,
-        error: Some("No user".into()),
-    })
+
+    let access_token = generate_access_token(
+        user.id,
+        user.username.as_str(),
+        user.email.as_str(),
+        state.jwt_secret.as_str()
+    )?;
+    let refresh_token = generate_refresh_token(
+        user.id,
+        user.username.as_str(),
+        user.email.as_str(),
+        state.jwt_secret.as_str()
+    )?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<LoginResponse> {
+        status: ResponseStatus::Ok,
+        data: LoginResponse {
+            access_token,
+            refresh_token,
+            user_id: user.id,
+            email: user.email,
+            username: user.username,
+        },
+        error: None,
+        code: None,
+    }))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
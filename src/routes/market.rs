@@ -1,31 +1,28 @@
+use crate::traits::heartbeat::Heartbeat;
+use crate::utils::app_error::AppError;
+use crate::utils::price_broadcaster::{
+    market_snapshot, Broadcast, GetSnapshot, MarketSnapshot, Subscribe, Unsubscribe,
+};
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::AppState;
+use crate::{AppState, PRICE_BROADCASTER};
 use actix::prelude::*;
-use actix_web::{get, web, Error as ActixError, HttpRequest, HttpResponse};
+use actix_web::{get, web, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
-use chrono::Utc;
-use entity::assets;
-use redis::{AsyncCommands, Client};
-use sea_orm::prelude::Decimal;
-use sea_orm::{DbConn, EntityTrait};
-use serde::Serialize;
-use std::collections::HashMap;
-use std::error::Error;
-use std::str::FromStr;
+use serde::Deserialize;
+use std::collections::HashSet;
 use std::sync::Arc;
-use std::time::Duration;
-
-#[derive(Serialize)]
-struct MarketData {
-    symbol: String,
-    name: String,
-    price: Decimal,
-    trend: String,
-    change_percent: Decimal,
-}
+use std::time::Instant;
+use utoipa::{IntoParams, ToSchema};
 
 pub(crate) struct MarketWs {
-    state: Arc<AppState>,
+    symbols: Option<HashSet<String>>,
+    last_seen: Instant,
+}
+
+impl Heartbeat for MarketWs {
+    fn last_seen(&mut self) -> &mut Instant {
+        &mut self.last_seen
+    }
 }
 
 #[derive(Message)]
@@ -34,41 +31,28 @@ pub struct WebSocketMessage {
     pub message: String,
 }
 
+impl MarketWs {
+    fn __subscribe(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        PRICE_BROADCASTER.do_send(Subscribe {
+            recipient: ctx.address().recipient(),
+            symbols: self.symbols.clone(),
+        });
+    }
+}
+
 impl Actor for MarketWs {
     type Context = ws::WebsocketContext<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
-        let db = self.state.db.clone();
-        let cache = self.state.cache.clone();
-        let addr = ctx.address();
-
-        let _db = db.clone();
-        let _cache = cache.clone();
-        let _addr = ctx.address();
-
-        tokio::spawn(async move {
-            let data = __get_price_changes(_db, _cache).await.unwrap_or_default();
-            let message_json = serde_json::to_string(&data).unwrap_or_default();
-
-            _addr.do_send(WebSocketMessage {
-                message: message_json,
-            });
-        });
-
-        ctx.run_interval(Duration::from_secs(5), move |_, _ctx| {
-            let db = db.clone();
-            let cache = cache.clone();
-            let addr = addr.clone();
-
-            tokio::spawn(async move {
-                let data = __get_price_changes(db, cache).await.unwrap_or_default();
-                let message_json = serde_json::to_string(&data).unwrap_or_default();
+        self.start_heartbeat(ctx);
+        self.__subscribe(ctx);
+    }
 
-                addr.do_send(WebSocketMessage {
-                    message: message_json,
-                });
-            });
+    fn stopping(&mut self, ctx: &mut Self::Context) -> Running {
+        PRICE_BROADCASTER.do_send(Unsubscribe {
+            recipient: ctx.address().recipient(),
         });
+        Running::Stop
     }
 }
 
@@ -82,113 +66,88 @@ impl Handler<WebSocketMessage> for MarketWs {
 
 impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for MarketWs {
     fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
-        if let Ok(ws::Message::Ping(msg)) = msg {
-            ctx.pong(&msg);
+        let Some(text) = self.control_frame(msg, ctx) else {
+            return;
+        };
+        if let Ok(subscription) = serde_json::from_str::<MarketSubscription>(&text) {
+            self.symbols = __symbols(subscription.symbols);
+            self.__subscribe(ctx);
         }
     }
 }
 
-#[utoipa::path(tag = "Market")]
+/// Sent over the socket to change the watched symbols, `null` or no field watches every asset.
+#[derive(Deserialize, ToSchema)]
+pub struct MarketSubscription {
+    #[serde(default)]
+    pub symbols: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct MarketQuery {
+    /// Comma separated symbols, e.g. `AAPL,ETH`; every asset when omitted
+    pub symbols: Option<String>,
+}
+
+#[utoipa::path(
+    params(MarketQuery),
+    request_body = MarketSubscription,
+    tag = "Market"
+)]
 #[get("/api/v1/market/data")]
 pub(crate) async fn market(
     req: HttpRequest,
     stream: web::Payload,
     state: web::Data<AppState>,
-) -> Result<HttpResponse, ActixError> {
+    query: web::Query<MarketQuery>,
+) -> Result<HttpResponse, AppError> {
+    let symbols = __symbols(
+        query
+            .into_inner()
+            .symbols
+            .map(|symbols| symbols.split(',').map(str::to_string).collect()),
+    );
     if ws::handshake(&req).is_ok() {
-        return Ok(ws::start(
+        return ws::start(
             MarketWs {
-                state: state.into_inner(),
+                symbols,
+                last_seen: Instant::now(),
             },
             &req,
             stream,
-        )?);
+        )
+        .map_err(AppError::internal);
     }
-    let data = __get_price_changes(state.as_ref().db.clone(), state.as_ref().cache.clone()).await;
-
-    match data {
-        Ok(prices) => Ok(
-            HttpResponse::Ok().json(CommonResponse::<HashMap<String, MarketData>> {
-                status: ResponseStatus::Ok,
-                data: prices,
-                error: None,
-            }),
-        ),
-        Err(e) => Ok(HttpResponse::InternalServerError().json(CommonResponse::<
-            HashMap<String, MarketData>,
-        > {
-            status: ResponseStatus::Error,
-            data: HashMap::new(),
-            error: Some(e.to_string()),
-        })),
-    }
-}
-
-async fn __get_price_changes(
-    db: Arc<DbConn>,
-    cache: Arc<Client>,
-) -> Result<HashMap<String, MarketData>, Box<dyn Error + Sync + Send>> {
-    let mut redis_conn = cache.get_multiplexed_async_connection().await?;
-    let keys: Vec<String> = redis_conn.keys("asset_price_history:*").await?;
-
-    let now = Utc::now().timestamp();
-    let day_ago = now - 3600;
-    let mut result = HashMap::new();
-
-    for key in keys {
-        let asset_id = key.split(':').nth(1).unwrap_or("unknown").to_string();
-        let asset = match assets::Entity::find_by_id(asset_id.parse::<i32>()?)
-            .one(db.as_ref())
-            .await?
-        {
-            Some(data) => data,
-            None => Err("can't find asset")?,
-        };
 
-        let prices: Vec<(String, f64)> = redis_conn
-            .zrevrangebyscore_withscores(&key, now, day_ago)
-            .await?;
-
-        let (first_price_data, _) = prices
-            .last()
-            .unwrap()
-            .0
-            .split_once(":")
-            .unwrap_or_default();
-        let (last_price_data, _) = prices
-            .first()
-            .unwrap()
-            .0
-            .split_once(":")
-            .unwrap_or_default();
-        
-        let first_price = Decimal::from_str(&first_price_data).unwrap_or(Decimal::ZERO);
-        let last_price = Decimal::from_str(&last_price_data).unwrap_or(Decimal::ZERO);
-
-        let change = if first_price != Decimal::ZERO {
-            (((last_price - first_price) / first_price) * Decimal::from(100)).abs()
-        } else {
-            Decimal::ZERO
-        };
+    let mut prices = match PRICE_BROADCASTER.send(GetSnapshot).await.ok().flatten() {
+        Some(snapshot) => snapshot,
+        None => {
+            let state = state.into_inner();
+            let snapshot = market_snapshot(Arc::clone(&state.db), Arc::clone(&state.cache)).await?;
+            PRICE_BROADCASTER.do_send(Broadcast {
+                snapshot: snapshot.clone(),
+            });
+            snapshot
+        }
+    };
+    if let Some(symbols) = symbols {
+        prices.retain(|_, data| symbols.contains(&data.symbol));
+    }
 
-        let trend = if last_price > first_price {
-            "up"
-        } else if last_price < first_price {
-            "down"
-        } else {
-            "unchanged"
-        };
+    Ok(HttpResponse::Ok().json(CommonResponse::<MarketSnapshot> {
+        status: ResponseStatus::Ok,
+        data: prices,
+        error: None,
+        code: None,
+    }))
+}
 
-        result.insert(
-            asset_id,
-            MarketData {
-                symbol: asset.symbol,
-                name: asset.name,
-                price: last_price.round_dp(3),
-                trend: trend.to_string(),
-                change_percent: change.round_dp(2),
-            },
-        );
-    }
-    Ok(result)
+fn __symbols(symbols: Option<Vec<String>>) -> Option<HashSet<String>> {
+    symbols.map(|symbols| {
+        symbols
+            .iter()
+            .map(|symbol| symbol.trim().to_uppercase())
+            .filter(|symbol| !symbol.is_empty())
+            .collect()
+    })
 }
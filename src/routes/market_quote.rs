@@ -0,0 +1,73 @@
+use crate::structs::order_structs::OrderType;
+use crate::utils::app_error::AppError;
+use crate::utils::get_price::get_price_by_asset_id;
+use crate::utils::jwt::AccessToken;
+use crate::utils::quote::generate_quote;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
+use sea_orm::prelude::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[utoipa::path(
+    params(QuoteQuery),
+    tag="Market",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/market/quote")]
+pub async fn market_quote(
+    state: web::Data<AppState>,
+    query: web::Query<QuoteQuery>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let amount = Decimal::from_f64_retain(query.amount)
+        .filter(|amount| amount.is_sign_positive() && !amount.is_zero())
+        .ok_or(AppError::Validation("Wrong amount".into()))?;
+    let price = get_price_by_asset_id(&state.cache, query.asset_id).await?;
+
+    let (quote, claims) = generate_quote(
+        token.claims.sub,
+        query.asset_id,
+        &query.side,
+        amount,
+        price,
+        &state.jwt_secret,
+    )?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<QuoteResponse> {
+        status: ResponseStatus::Ok,
+        data: QuoteResponse {
+            quote,
+            asset_id: claims.asset_id,
+            side: claims.side,
+            amount: claims.amount,
+            price: claims.price,
+            total: (claims.price * claims.amount).round_dp(3),
+            expires_at: claims.exp,
+        },
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct QuoteQuery {
+    pub asset_id: i32,
+    pub side: OrderType,
+    pub amount: f64,
+}
+
+#[derive(Serialize)]
+pub struct QuoteResponse {
+    pub quote: String,
+    pub asset_id: i32,
+    pub side: String,
+    pub amount: Decimal,
+    pub price: Decimal,
+    pub total: Decimal,
+    /// Unix timestamp after which `market_buy`/`market_sell` reject the quote
+    pub expires_at: usize,
+}
@@ -1,19 +1,21 @@
-use crate::utils::get_price::get_price_by_asset_id;
+use crate::routes::market_buy::__non_negative;
+use crate::structs::order_structs::{MarketMode, OrderType};
+use crate::utils::app_error::AppError;
 use crate::utils::jwt::AccessToken;
+use crate::utils::market_execution::{execute_market_order, MarketFill, MarketOrder};
+use crate::utils::quote::{price_limit, verify_quote};
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::utils::take_commission::{take_commission};
-use crate::{try_or_http_err, AppState, COMMISSION_MARKET_SELL};
-use actix_web::{post, web, HttpResponse, Responder};
-use entity::{trades, user_balances, users};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
 use sea_orm::prelude::Decimal;
-use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, Set
-};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use utoipa::ToSchema;
 
 #[utoipa::path(
     request_body = SellMarketRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Repeated requests with the same key return the first result")
+    ),
     tag="User",
     security(
         ("bearer_token" = [])
@@ -24,95 +26,60 @@ pub async fn market_sell(
     state: web::Data<AppState>,
     input: web::Json<SellMarketRequest>,
     token: AccessToken,
-) -> impl Responder {
-    let user_id = token.claims.sub;
-    let current_price = try_or_http_err!(get_price_by_asset_id(&state.cache, input.asset_id).await);
-    let amount_to_sell = Decimal::from_f64_retain(input.amount).unwrap_or_default();
-    let total_cost = current_price * amount_to_sell;
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    let amount = Decimal::from_f64_retain(input.amount)
+        .ok_or(AppError::Validation("Wrong amount".into()))?;
+    let max_slippage = __non_negative(input.max_slippage, "Wrong max_slippage")?;
+    let limit = __non_negative(input.min_price, "Wrong min_price")?;
+    let quoted = input
+        .quote
+        .as_deref()
+        .map(|quote| {
+            verify_quote(
+                quote,
+                &state.jwt_secret,
+                token.claims.sub,
+                input.asset_id,
+                &OrderType::Sell,
+                amount,
+            )
+        })
+        .transpose()?;
 
-    let user_asset: user_balances::Model = if let Some(_data) = try_or_http_err!(
-        user_balances::Entity::find()
-            .filter(user_balances::Column::UserId.eq(user_id))
-            .filter(user_balances::Column::AssetId.eq(input.asset_id))
-            .one(state.db.as_ref())
-            .await
-    ) {
-        _data
-    } else {
-        return HttpResponse::BadRequest().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("Can't sell asset".into()),
-        });
-    };
-
-    let user: users::Model = match try_or_http_err!(
-        users::Entity::find_by_id(user_id)
-            .one(state.db.as_ref())
-            .await
-    ) {
-        Some(us) => us,
-        None => {
-            return HttpResponse::Ok().json(CommonResponse::<()> {
-                status: ResponseStatus::Error,
-                data: (),
-                error: Some("Can't find user".into()),
-            })
-        }
-    };
-
-    if user_asset.amount < try_or_http_err!(Decimal::try_from(input.amount)) {
-        return HttpResponse::Ok().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("You don't have enough asset".into()),
-        });
-    }
-    let _balance = user.balance;
-    let _user_amount = user_asset.amount;
-
-    let mut active_user: users::ActiveModel = user.into_active_model();
-    let amount_commission = take_commission(total_cost, COMMISSION_MARKET_SELL.clone());
-    let new_balance = (_balance + amount_commission.amount).round_dp(3);
-    active_user.balance = Set(new_balance);
-    try_or_http_err!(active_user.update(state.db.as_ref()).await);
-
-    let mut active_user_asset = user_asset.into_active_model();
-    let amount_data = (_user_amount - amount_to_sell).round_dp(3);
-    active_user_asset.amount = Set(amount_data);
-
-    try_or_http_err!(active_user_asset.update(state.db.as_ref()).await);
-    
-    let trade = trades::ActiveModel {
-        user_id: Set(user_id),
-        asset_id: Set(input.asset_id),
-        trade_type: Set("sell".into()),
-        price: Set(current_price),
-        amount: Set(amount_data),
-        ..Default::default()
-    };
-    try_or_http_err!(trade.insert(state.db.as_ref()).await);
+    let fill: MarketFill = execute_market_order(
+        state.db.as_ref(),
+        &state.cache,
+        OrderType::Sell,
+        MarketOrder {
+            user_id: token.claims.sub,
+            asset_id: input.asset_id,
+            amount,
+            mode: input.mode.unwrap_or_default(),
+            max_slippage,
+            limit_price: price_limit(&OrderType::Sell, quoted, limit),
+        },
+    )
+    .await?;
 
-    HttpResponse::Ok().json(CommonResponse::<BuyMarketResponse> {
+    Ok(HttpResponse::Ok().json(CommonResponse::<MarketFill> {
         status: ResponseStatus::Ok,
-        data: BuyMarketResponse {
-            amount: amount_commission.amount,
-            commission: amount_commission.commission,
-            balance: new_balance
-        },
+        data: fill,
         error: None,
-    })
+        code: None,
+    }))
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct SellMarketRequest {
     pub asset_id: i32,
     pub amount: f64,
-}
-
-#[derive(Serialize)]
-pub struct BuyMarketResponse {
-    amount: Decimal,
-    commission: Decimal,
-    balance: Decimal
+    /// `house` (default) or `book`
+    pub mode: Option<MarketMode>,
+    /// Percent below the current price the book may be walked to
+    pub max_slippage: Option<f64>,
+    /// Token from `/api/v1/market/quote`, the trade fails if the price got worse
+    pub quote: Option<String>,
+    /// The trade fails if the average price is below this
+    pub min_price: Option<f64>,
 }
@@ -7,9 +7,15 @@ pub mod user_assets;
 pub mod trades_history;
 pub mod private_chat;
 pub mod chat_history;
+pub mod chat_read;
+pub mod chat_message;
+pub mod chat_rooms;
+pub mod chat_moderation;
+pub mod moderation;
 pub mod user_orders;
 pub mod market_buy;
 pub mod market_sell;
+pub mod market_quote;
 pub mod top_users;
 pub mod order_sell;
 pub mod order_buy;
@@ -21,6 +27,17 @@ pub mod create_event;
 pub mod get_events;
 pub mod get_bots;
 pub mod create_bot;
+pub mod admin_bots;
+pub mod user_scripts;
+pub mod backtest;
+pub mod portfolio;
+pub mod equity_history;
+pub mod seasons;
+pub mod admin_seasons;
+pub mod leagues;
+pub mod notifications;
+pub mod ws;
+pub mod alerts;
 pub mod get_user_place;
 pub mod get_chats;
 pub mod recover_account;
@@ -0,0 +1,306 @@
+use crate::structs::moderation_structs::{ReportStatus, SanctionKind};
+use crate::utils::admin::ModeratorToken;
+use crate::utils::app_error::AppError;
+use crate::utils::moderation::active_sanctions;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, post, web, HttpResponse};
+use chrono::{Duration, Utc};
+use entity::{chat_sanctions, message_reports, users};
+use sea_orm::prelude::DateTime;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_SANCTION_REASON: usize = 500;
+const MAX_REPORTS_PAGE: u64 = 100;
+
+#[utoipa::path(
+    params(ReportsQuery),
+    tag="Moderation",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/moderation/reports")]
+pub async fn moderation_reports(
+    state: web::Data<AppState>,
+    query: web::Query<ReportsQuery>,
+    _moderator: ModeratorToken,
+) -> Result<HttpResponse, AppError> {
+    let status = query.status.unwrap_or(ReportStatus::Open);
+    let mut select = message_reports::Entity::find()
+        .filter(message_reports::Column::Status.eq(status.name()));
+    if let Some(after) = query.after_id {
+        select = select.filter(message_reports::Column::Id.gt(after));
+    }
+    let data = select
+        .order_by_asc(message_reports::Column::Id)
+        .limit(query.limit.min(MAX_REPORTS_PAGE))
+        .all(state.db.as_ref())
+        .await?
+        .into_iter()
+        .map(ReportInfo::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<ReportInfo>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(ReportPath),
+    request_body = ReportResolveInput,
+    tag="Moderation",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/moderation/reports/{report_id}/resolve")]
+pub async fn moderation_report_resolve(
+    state: web::Data<AppState>,
+    path: web::Path<ReportPath>,
+    input: web::Json<ReportResolveInput>,
+    moderator: ModeratorToken,
+) -> Result<HttpResponse, AppError> {
+    if input.status == ReportStatus::Open {
+        return Err(AppError::Validation("status must be resolved or dismissed".to_string()));
+    }
+    let report = message_reports::Entity::find_by_id(path.report_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No report"))?;
+    if report.status != ReportStatus::Open.name() {
+        return Err(AppError::Validation("Report is already closed".to_string()));
+    }
+
+    let mut report: message_reports::ActiveModel = report.into();
+    report.status = Set(input.status.name().to_string());
+    report.moderator_id = Set(Some(moderator.claims.sub));
+    report.resolved_at = Set(Some(Utc::now().naive_utc()));
+    let report = report.update(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<ReportInfo> {
+        status: ResponseStatus::Ok,
+        data: report.into(),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    request_body = SanctionInput,
+    tag="Moderation",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/moderation/sanctions")]
+pub async fn moderation_sanction(
+    state: web::Data<AppState>,
+    input: web::Json<SanctionInput>,
+    moderator: ModeratorToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    let reason = input.reason.trim().to_string();
+    if reason.is_empty() || reason.chars().count() > MAX_SANCTION_REASON {
+        return Err(AppError::Validation(format!(
+            "Reason must be 1 to {MAX_SANCTION_REASON} characters"
+        )));
+    }
+    let expires_at = match input.duration_secs {
+        Some(secs) if secs <= 0 => {
+            return Err(AppError::Validation("duration_secs must be positive".to_string()))
+        }
+        Some(secs) => Some(
+            Utc::now().naive_utc()
+                + Duration::try_seconds(secs).ok_or(AppError::Validation("Wrong duration_secs".to_string()))?,
+        ),
+        None => None,
+    };
+    let user = users::Entity::find_by_id(input.user_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
+    if user.id == moderator.claims.sub || user.role == "moderator" || user.role == "admin" {
+        return Err(AppError::Forbidden("Moderators can't be sanctioned"));
+    }
+
+    let sanction = chat_sanctions::ActiveModel {
+        user_id: Set(user.id),
+        kind: Set(input.kind.name().to_string()),
+        reason: Set(reason),
+        moderator_id: Set(Some(moderator.claims.sub)),
+        expires_at: Set(expires_at),
+        ..Default::default()
+    }
+    .insert(state.db.as_ref())
+    .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<SanctionInfo> {
+        status: ResponseStatus::Ok,
+        data: sanction.into(),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(SanctionsQuery),
+    tag="Moderation",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/moderation/sanctions")]
+pub async fn moderation_sanctions(
+    state: web::Data<AppState>,
+    query: web::Query<SanctionsQuery>,
+    _moderator: ModeratorToken,
+) -> Result<HttpResponse, AppError> {
+    let sanctions = active_sanctions(state.db.as_ref(), query.user_id).await?;
+    let data = sanctions.into_iter().map(SanctionInfo::from).collect();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<SanctionInfo>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(SanctionPath),
+    tag="Moderation",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/moderation/sanctions/{sanction_id}/lift")]
+pub async fn moderation_sanction_lift(
+    state: web::Data<AppState>,
+    path: web::Path<SanctionPath>,
+    _moderator: ModeratorToken,
+) -> Result<HttpResponse, AppError> {
+    let now = Utc::now().naive_utc();
+    let sanction = chat_sanctions::Entity::find_by_id(path.sanction_id)
+        .one(state.db.as_ref())
+        .await?
+        .filter(|sanction| sanction.expires_at.is_none_or(|expires_at| expires_at > now))
+        .ok_or(AppError::NotFound("No active sanction"))?;
+
+    // Expired instead of deleted, so the user's record stays visible
+    let mut sanction: chat_sanctions::ActiveModel = sanction.into();
+    sanction.expires_at = Set(Some(now));
+    let sanction = sanction.update(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<SanctionInfo> {
+        status: ResponseStatus::Ok,
+        data: sanction.into(),
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct ReportsQuery {
+    /// `open` by default
+    pub status: Option<ReportStatus>,
+    /// At most 100, larger values are clamped
+    pub limit: u64,
+    /// Oldest reports come first, pass the last seen id to get the next page
+    pub after_id: Option<i32>,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct ReportPath {
+    pub report_id: i32,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ReportResolveInput {
+    /// `resolved` or `dismissed`
+    pub status: ReportStatus,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SanctionInput {
+    pub user_id: i32,
+    pub kind: SanctionKind,
+    /// Permanent when omitted
+    pub duration_secs: Option<i64>,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema, IntoParams)]
+pub struct SanctionsQuery {
+    /// Active sanctions of one user, of everyone when omitted
+    pub user_id: Option<i32>,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct SanctionPath {
+    pub sanction_id: i32,
+}
+
+#[derive(Serialize)]
+pub struct ReportInfo {
+    pub id: i32,
+    pub reporter_id: i32,
+    pub reported_id: i32,
+    pub message_type: String,
+    pub message_id: i32,
+    /// Message text at the time of the report
+    pub text: String,
+    pub reason: String,
+    pub status: String,
+    pub moderator_id: Option<i32>,
+    pub created_at: DateTime,
+    pub resolved_at: Option<DateTime>,
+}
+
+impl From<message_reports::Model> for ReportInfo {
+    fn from(report: message_reports::Model) -> Self {
+        Self {
+            id: report.id,
+            reporter_id: report.reporter_id,
+            reported_id: report.reported_id,
+            message_type: report.message_type,
+            message_id: report.message_id,
+            text: report.text,
+            reason: report.reason,
+            status: report.status,
+            moderator_id: report.moderator_id,
+            created_at: report.created_at,
+            resolved_at: report.resolved_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SanctionInfo {
+    pub id: i32,
+    pub user_id: i32,
+    pub kind: String,
+    pub reason: String,
+    pub moderator_id: Option<i32>,
+    pub expires_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+impl From<chat_sanctions::Model> for SanctionInfo {
+    fn from(sanction: chat_sanctions::Model) -> Self {
+        Self {
+            id: sanction.id,
+            user_id: sanction.user_id,
+            kind: sanction.kind,
+            reason: sanction.reason,
+            moderator_id: sanction.moderator_id,
+            expires_at: sanction.expires_at,
+            created_at: sanction.created_at,
+        }
+    }
+}
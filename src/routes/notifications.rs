@@ -0,0 +1,226 @@
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::AccessToken;
+use crate::utils::notifications::Notification;
+use crate::structs::ws_structs::Topic;
+use crate::traits::heartbeat::Heartbeat;
+use crate::utils::pubsub::{publish, publish_event, Envelope, NOTIFICATION_CHANNEL};
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::{AppState, NOTIFICATION_SESSIONS};
+use actix::{Actor, AsyncContext, Handler, Message as ActixMessage, StreamHandler};
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use entity::{notifications, users};
+use redis::Client;
+use sea_orm::prelude::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Instant;
+use utoipa::{IntoParams, ToSchema};
+
+/// Unread notifications replayed to a freshly connected socket.
+const REPLAY_LIMIT: u64 = 100;
+const MAX_PAGE: u64 = 100;
+
+/// Sends the notification to the user's socket on whichever instance they are connected to.
+pub(crate) async fn push(cache: &Client, user_id: i32, notification: &Notification) {
+    publish_event(cache, &Topic::Notifications(user_id), notification).await;
+    let envelope = Envelope {
+        user_ids: vec![user_id],
+        payload: serde_json::to_string(notification).unwrap_or_default(),
+    };
+    publish(cache, NOTIFICATION_CHANNEL, envelope).await;
+}
+
+/// Writes a relayed frame to the sockets connected to this instance.
+pub(crate) async fn deliver_notification(user_ids: &[i32], message: String) {
+    let sessions = NOTIFICATION_SESSIONS.read().await;
+    for user_id in user_ids {
+        if let Some(addr) = sessions.get(user_id) {
+            addr.do_send(NotificationMessage {
+                message: message.clone(),
+            });
+        }
+    }
+}
+
+#[utoipa::path(
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/notifications/ws")]
+pub async fn notifications_ws(
+    req: HttpRequest,
+    stream: web::Payload,
+    state: web::Data<AppState>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let user = users::Entity::find_by_id(token.claims.sub)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::Unauthorized("No user"))?;
+
+    let session = NotificationSession {
+        id: user.id,
+        state: state.into_inner(),
+        last_seen: Instant::now(),
+    };
+    ws::start(session, &req, stream).map_err(AppError::internal)
+}
+
+#[utoipa::path(
+    params(NotificationsQuery),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/notifications")]
+pub async fn notifications_list(
+    state: web::Data<AppState>,
+    query: web::Query<NotificationsQuery>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let mut select = notifications::Entity::find()
+        .filter(notifications::Column::UserId.eq(token.claims.sub));
+    if query.unread {
+        select = select.filter(notifications::Column::Read.eq(false));
+    }
+    if let Some(before) = query.before_id {
+        select = select.filter(notifications::Column::Id.lt(before));
+    }
+    let data = select
+        .order_by_desc(notifications::Column::Id)
+        .limit(query.limit.min(MAX_PAGE))
+        .all(state.db.as_ref())
+        .await?
+        .into_iter()
+        .map(Notification::from)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<Notification>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    request_body = NotificationsReadInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/notifications/read")]
+pub async fn notifications_read(
+    state: web::Data<AppState>,
+    input: web::Json<NotificationsReadInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let mut update = notifications::Entity::update_many()
+        .col_expr(notifications::Column::Read, Expr::value(true))
+        .filter(notifications::Column::UserId.eq(token.claims.sub))
+        .filter(notifications::Column::Read.eq(false));
+    if let Some(ids) = input.into_inner().ids {
+        update = update.filter(notifications::Column::Id.is_in(ids));
+    }
+    update.exec(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct NotificationsQuery {
+    /// At most 100, larger values are clamped
+    pub limit: u64,
+    /// Only unread notifications
+    #[serde(default)]
+    pub unread: bool,
+    pub before_id: Option<i32>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct NotificationsReadInput {
+    /// Notifications to mark as read, all of them when omitted
+    pub ids: Option<Vec<i32>>,
+}
+
+#[derive(ActixMessage)]
+#[rtype(result = "()")]
+struct NotificationMessage {
+    message: String,
+}
+
+pub(crate) struct NotificationSession {
+    id: i32,
+    state: Arc<AppState>,
+    last_seen: Instant,
+}
+
+impl Heartbeat for NotificationSession {
+    fn last_seen(&mut self) -> &mut Instant {
+        &mut self.last_seen
+    }
+}
+
+impl Actor for NotificationSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.start_heartbeat(ctx);
+        let addr = ctx.address();
+        let session_id = self.id;
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            {
+                let mut sessions = NOTIFICATION_SESSIONS.write().await;
+                sessions.insert(session_id, addr.clone());
+            }
+            let unread = notifications::Entity::find()
+                .filter(notifications::Column::UserId.eq(session_id))
+                .filter(notifications::Column::Read.eq(false))
+                .order_by_desc(notifications::Column::Id)
+                .limit(REPLAY_LIMIT)
+                .all(state.db.as_ref())
+                .await
+                .unwrap_or_default();
+            for notification in unread.into_iter().rev() {
+                addr.do_send(NotificationMessage {
+                    message: serde_json::to_string(&Notification::from(notification)).unwrap_or_default(),
+                });
+            }
+        });
+    }
+
+    fn stopping(&mut self, _ctx: &mut Self::Context) -> actix::Running {
+        let session_id = self.id;
+        tokio::spawn(async move {
+            let mut sessions = NOTIFICATION_SESSIONS.write().await;
+            sessions.remove(&session_id);
+        });
+        actix::Running::Stop
+    }
+}
+
+impl Handler<NotificationMessage> for NotificationSession {
+    type Result = ();
+    fn handle(&mut self, msg: NotificationMessage, ctx: &mut Self::Context) {
+        ctx.text(msg.message);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for NotificationSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        // The socket only pushes, text from the client is ignored
+        let _ = self.control_frame(msg, ctx);
+    }
+}
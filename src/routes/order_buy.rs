@@ -1,15 +1,18 @@
+use crate::structs::notification_structs::NotificationKind;
+use crate::structs::ws_structs::{BookEvent, Topic, TradeEvent};
+use crate::utils::app_error::AppError;
 use crate::utils::jwt::AccessToken;
+use crate::utils::market_execution::{__change_asset_amount, __change_balance};
+use crate::utils::notifications::{notify, OrderNotification};
+use crate::utils::pubsub::publish_event;
 use crate::utils::response::{CommonResponse, ResponseStatus};
 use crate::utils::take_commission::take_commission;
-use crate::{
-    extract_db_response_or_http_err_with_opt_msg, try_or_http_err, AppState,
-};
-use crate::{unwrap_or_http_err_with_opt_msg, COMMISSION_ORDER_BUY};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::{AppState, COMMISSION_ORDER_BUY};
+use actix_web::{post, web, HttpResponse};
 use chrono::Utc;
-use entity::{orders, trades, user_balances, users};
+use entity::{orders, trades, user_balances};
 use sea_orm::prelude::Decimal;
-use sea_orm::QueryFilter;
+use sea_orm::{QueryFilter, QuerySelect, TransactionTrait};
 use sea_orm::{ActiveModelTrait, ColumnTrait, IntoActiveModel};
 use sea_orm::{Condition, EntityTrait, Set};
 use serde::{Deserialize, Serialize};
@@ -17,6 +20,9 @@ use utoipa::ToSchema;
 
 #[utoipa::path(
     request_body = OrderBuyInput,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Repeated requests with the same key return the first result")
+    ),
     tag="User",
     security(
         ("bearer_token" = [])
@@ -27,141 +33,93 @@ pub async fn order_buy(
     state: web::Data<AppState>,
     input: web::Json<OrderBuyInput>,
     token: AccessToken,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let input = input.into_inner();
-    let order = extract_db_response_or_http_err_with_opt_msg!(
-        orders::Entity::find_by_id(input.order_id)
-            .one(state.db.as_ref())
-            .await,
-        "No order with this ID"
-    );
+    let txn = state.db.begin().await?;
+    let order = orders::Entity::find_by_id(input.order_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NotFound("No order with this ID"))?;
 
     if order.user_id == token.claims.sub {
-        return HttpResponse::BadRequest().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("Can't execute this order".into()),
-        });
+        return Err(AppError::OrderNotExecutable("Can't execute this order"));
     }
 
     if order.order_type != "buy" {
-        return HttpResponse::BadRequest().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("Wrong order type".into()),
-        });
+        return Err(AppError::OrderNotExecutable("Wrong order type"));
     }
 
     if order.status != "pending" {
-        return HttpResponse::BadRequest().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("Can't execute order".into()),
-        });
+        return Err(AppError::OrderNotExecutable("Can't execute order"));
     }
 
-    let seller_balance = extract_db_response_or_http_err_with_opt_msg!(
-        user_balances::Entity::find()
-            .filter(
-                Condition::all()
-                    .add(user_balances::Column::UserId.eq(token.claims.sub))
-                    .add(user_balances::Column::AssetId.eq(order.asset_id))
-            )
-            .one(state.db.as_ref())
-            .await,
-        "No asset for this user"
-    );
+    let seller_balance = user_balances::Entity::find()
+        .filter(
+            Condition::all()
+                .add(user_balances::Column::UserId.eq(token.claims.sub))
+                .add(user_balances::Column::AssetId.eq(order.asset_id))
+        )
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::InsufficientAssets)?;
     
     if seller_balance.amount < order.amount {
-        return HttpResponse::BadRequest().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("Not enough asset amount".into()),
-        });
+        return Err(AppError::InsufficientAssets);
     }
 
-    let seller = extract_db_response_or_http_err_with_opt_msg!(
-        users::Entity::find_by_id(token.claims.sub)
-            .one(state.db.as_ref())
-            .await,
-        "Seller is not exist"
-    );
-        
-    let new_balance = seller_balance.amount - order.amount;
-    let mut active_seller_balance = seller_balance.into_active_model();
-    active_seller_balance.amount = Set(new_balance);
-    try_or_http_err!(active_seller_balance.update(state.db.as_ref()).await);
-    
-    let new_balance = (seller.balance + order.price).round_dp(3);
-    let mut active_seller = seller.into_active_model();
-    active_seller.balance = Set(new_balance);
-    try_or_http_err!(active_seller.update(state.db.as_ref()).await);
-
-    let buyer_balance = match try_or_http_err!(
-        user_balances::Entity::find()
-            .filter(
-                Condition::all()
-                    .add(user_balances::Column::UserId.eq(order.user_id))
-                    .add(user_balances::Column::AssetId.eq(order.asset_id))
-            )
-            .one(state.db.as_ref())
-            .await
-    ) {
-        Some(_data) => {_data}
-        None => {
-            user_balances::Model {
-                id: 0,
-                user_id: order.user_id,
-                asset_id: order.asset_id,
-                amount: Decimal::from(0),
-            }
-        }
-    };
-
-    let _user_amount = buyer_balance.amount;
-    let mut active_buyer_balance = buyer_balance.into_active_model();
-    active_buyer_balance.amount = Set(_user_amount + take_commission(order.amount, COMMISSION_ORDER_BUY.clone()).amount);
-    if let Err(_) = active_buyer_balance.update(state.db.as_ref()).await {
-        let _asset = user_balances::ActiveModel {
-            user_id: Set(order.user_id),
-            asset_id: Set(order.asset_id),
-            amount: Set(_user_amount + take_commission(order.amount, COMMISSION_ORDER_BUY.clone()).amount),
-            ..Default::default()
-        };
+    __change_asset_amount(&txn, token.claims.sub, order.asset_id, -order.amount).await?;
+    let new_balance = __change_balance(&txn, token.claims.sub, order.price).await?;
+    let bought = take_commission(order.amount, *COMMISSION_ORDER_BUY).amount;
+    __change_asset_amount(&txn, order.user_id, order.asset_id, bought).await?;
 
-        try_or_http_err!(_asset.insert(state.db.as_ref()).await);
-    }
-
-    let _ = trades::ActiveModel {
+    let unit_price = (order.price / order.amount).round_dp(3);
+    trades::ActiveModel {
         user_id: Set(token.claims.sub),
         asset_id: Set(order.asset_id),
         trade_type: Set("sell".into()),
-        price: Set(order.price),
+        price: Set(unit_price),
         amount: Set(order.amount),
+        total: Set(Some(order.price)),
         ..Default::default()
-    }.insert(state.db.as_ref()).await;
+    }.insert(&txn).await?;
 
-    let _ = trades::ActiveModel {
+    trades::ActiveModel {
         user_id: Set(order.user_id),
         asset_id: Set(order.asset_id),
         trade_type: Set("buy".into()),
-        price: Set(order.price),
-        amount: Set(order.amount),
+        price: Set(unit_price),
+        amount: Set(bought),
+        total: Set(Some(order.price)),
         ..Default::default()
-    }.insert(state.db.as_ref()).await;
+    }.insert(&txn).await?;
 
+    let notification = OrderNotification::new(&order, order.amount);
+    let maker_id = order.user_id;
     let mut active_order = order.into_active_model();
     active_order.status = Set("done".into());
     active_order.updated_at = Set(Utc::now().naive_utc());
-    try_or_http_err!(active_order.update(state.db.as_ref()).await);
+    let order = active_order.update(&txn).await?;
+    txn.commit().await?;
+    notify(state.db.as_ref(), state.cache.as_ref(), maker_id, NotificationKind::OrderFilled, &notification).await;
+    let trade = TradeEvent {
+        trade_type: "sell".into(),
+        price: unit_price,
+        amount: order.amount,
+        created_at: order.updated_at,
+    };
+    publish_event(state.cache.as_ref(), &Topic::Trades(order.asset_id), &trade).await;
+    publish_event(state.cache.as_ref(), &Topic::Book(order.asset_id), &BookEvent::from(&order)).await;
     
-    HttpResponse::Ok().json(CommonResponse::<BuyOrderResponse> {
+    Ok(HttpResponse::Ok().json(CommonResponse::<BuyOrderResponse> {
         status: ResponseStatus::Ok,
         data: BuyOrderResponse{
             balance: new_balance,
         },
         error: None,
-    })
+        code: None,
+    }))
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -1,19 +1,24 @@
+use crate::utils::alerts::deactivate_order_alerts;
+use crate::utils::app_error::AppError;
+use crate::structs::ws_structs::{BookEvent, Topic};
 use crate::utils::jwt::AccessToken;
+use crate::utils::market_execution::{__change_asset_amount, __change_balance};
+use crate::utils::pubsub::publish_event;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
 use chrono::Utc;
-use entity::{orders, user_balances, users};
-use migration::Condition;
-use sea_orm::ColumnTrait;
-use sea_orm::QueryFilter;
-use sea_orm::{ActiveModelTrait, DbConn, EntityTrait, IntoActiveModel, Set};
+use entity::orders;
+use redis::Client;
+use sea_orm::{ActiveModelTrait, DbConn, EntityTrait, IntoActiveModel, QuerySelect, Set, TransactionTrait};
 use serde::Deserialize;
-use std::error::Error;
 use utoipa::ToSchema;
 
 #[utoipa::path(
     request_body = OrderCancelInput,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Repeated requests with the same key return the first result")
+    ),
     tag="User",
     security(
         ("bearer_token" = [])
@@ -24,57 +29,15 @@ pub async fn order_cancel(
     state: web::Data<AppState>,
     input: web::Json<OrderCancelInput>,
     token: AccessToken,
-) -> impl Responder {
-    match try_or_http_err!(
-        orders::Entity::find_by_id(input.order_id)
-            .one(state.db.as_ref())
-            .await
-    ) {
-        Some(order) => {
-            if order.user_id != token.claims.sub {
-                return HttpResponse::BadRequest().json(CommonResponse::<()> {
-                    status: ResponseStatus::Error,
-                    data: (),
-                    error: Some("No order with this ID".into()),
-                });
-            }
-            if order.status != "pending" {
-                return HttpResponse::BadRequest().json(CommonResponse::<()> {
-                    status: ResponseStatus::Error,
-                    data: (),
-                    error: Some("Can't cancel this order".into()),
-                });
-            }
-            match order.order_type.as_str() {
-                "buy" => {
-                    try_or_http_err!(__cancel_buy_order(order, state.db.as_ref()).await);
-                    HttpResponse::Ok().json(CommonResponse::<()> {
-                        status: ResponseStatus::Ok,
-                        data: (),
-                        error: None,
-                    })
-                }
-                "sell" => {
-                    try_or_http_err!(__cancel_sell_order(order, state.db.as_ref()).await);
-                    HttpResponse::Ok().json(CommonResponse::<()> {
-                        status: ResponseStatus::Ok,
-                        data: (),
-                        error: None,
-                    })
-                }
-                _ => HttpResponse::InternalServerError().json(CommonResponse::<()> {
-                    status: ResponseStatus::Error,
-                    data: (),
-                    error: Some("Unexpected order type".into()),
-                }),
-            }
-        }
-        None => HttpResponse::BadRequest().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("No order with this ID".into()),
-        }),
-    }
+) -> Result<HttpResponse, AppError> {
+    __cancel_order(token.claims.sub, input.order_id, state.db.as_ref(), state.cache.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -82,52 +45,43 @@ pub struct OrderCancelInput {
     order_id: i32,
 }
 
-async fn __cancel_buy_order(
-    order: orders::Model,
+/// Cancels a pending order and returns what it reserved. The order is re-read under a lock
+/// inside the transaction, so a fill from the book walker or a direct fill either finishes
+/// first and the cancel is refused, or waits for the cancel and finds the order gone.
+pub(crate) async fn __cancel_order(
+    user_id: i32,
+    order_id: i32,
     db: &DbConn,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let user = match users::Entity::find_by_id(order.user_id).one(db).await? {
-        Some(_user) => _user,
-        None => return Err("No user".into()),
-    };
+    cache: &Client,
+) -> Result<(), AppError> {
+    let txn = db.begin().await?;
+    let order = orders::Entity::find_by_id(order_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .filter(|order| order.user_id == user_id)
+        .ok_or(AppError::NotFound("No order with this ID"))?;
 
-    let new_balance = (user.balance + order.price).round_dp(3);
-    let mut active_user = user.into_active_model();
-    active_user.balance = Set(new_balance);
-    let mut active_order = order.into_active_model();
-    active_order.status = Set("cancel".into());
-    active_order.updated_at = Set(Utc::now().naive_utc());
-    
-    active_user.update(db).await?;
-    active_order.update(db).await?;
-    Ok(())
-}
-
-async fn __cancel_sell_order(
-    order: orders::Model,
-    db: &DbConn,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let user_balance = match user_balances::Entity::find()
-        .filter(
-            Condition::all()
-                .add(user_balances::Column::UserId.eq(order.user_id))
-                .add(user_balances::Column::AssetId.eq(order.asset_id)),
-        )
-        .one(db)
-        .await? {
-        Some(_balance) => { _balance }
-        None => { return Err("No asset data for this user".into()) }
-    };
-    
-    let new_amount = (user_balance.amount + order.amount).round_dp(3);
-    let mut active_user_balance = user_balance.into_active_model();
-    active_user_balance.amount = Set(new_amount);
+    if order.status != "pending" {
+        return Err(AppError::OrderNotExecutable("Can't cancel this order"));
+    }
+    // A partially filled order already has its remaining amount and price here
+    match order.order_type.as_str() {
+        "buy" => {
+            __change_balance(&txn, order.user_id, order.price).await?;
+        }
+        "sell" => {
+            __change_asset_amount(&txn, order.user_id, order.asset_id, order.amount).await?;
+        }
+        _ => return Err(AppError::Internal(format!("Unexpected order type {}", order.order_type))),
+    }
     let mut active_order = order.into_active_model();
     active_order.status = Set("cancel".into());
     active_order.updated_at = Set(Utc::now().naive_utc());
-    
-    active_user_balance.update(db).await?;
-    active_order.update(db).await?;
+    let order = active_order.update(&txn).await?;
+    txn.commit().await?;
 
+    deactivate_order_alerts(db, vec![order.id]).await?;
+    publish_event(cache, &Topic::Book(order.asset_id), &BookEvent::from(&order)).await;
     Ok(())
 }
@@ -1,18 +1,23 @@
+use crate::utils::app_error::AppError;
 use crate::utils::jwt::AccessToken;
+use crate::utils::pubsub::publish_event;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{post, web, HttpResponse, Responder};
-use entity::prelude::Orders;
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
 use entity::{orders, user_balances, users};
+use redis::Client;
 use sea_orm::prelude::Decimal;
-use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, DbConn, EntityTrait, IntoActiveModel, QueryFilter, Set};
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, DbConn, EntityTrait, IntoActiveModel, QueryFilter, QuerySelect, Set, TransactionTrait};
 use serde::{Deserialize, Serialize};
-use std::error::Error;
 use utoipa::ToSchema;
 use crate::structs::order_structs::OrderType;
+use crate::structs::ws_structs::{BookEvent, Topic};
 
 #[utoipa::path(
     request_body = OrderCreateInput,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Repeated requests with the same key return the first result")
+    ),
     tag="User",
     security(
         ("bearer_token" = [])
@@ -23,43 +28,25 @@ pub async fn order_create(
     state: web::Data<AppState>,
     input: web::Json<OrderCreateInput>,
     token: AccessToken,
-) -> impl Responder {
-    match input.order_type {
+) -> Result<HttpResponse, AppError> {
+    let amount = __positive_decimal(input.amount, "Wrong amount")?;
+    let price = __positive_decimal(input.price, "Wrong price")?;
+
+    let order_id = match input.order_type {
         OrderType::Buy => {
-            let order_id = try_or_http_err!(
-                __create_buy_order(
-                    token.claims.sub,
-                    input.asset_id,
-                    try_or_http_err!(Decimal::from_f64_retain(input.amount).ok_or("Wrong amount")).round_dp(3),
-                    try_or_http_err!(Decimal::from_f64_retain(input.price).ok_or("Wrong price")).round_dp(3),
-                    state.db.as_ref()
-                )
-                .await
-            );
-            HttpResponse::Ok().json(CommonResponse::<OrderCreateResponse> {
-                status: ResponseStatus::Ok,
-                data: OrderCreateResponse { order_id },
-                error: None,
-            })
+            __create_buy_order(token.claims.sub, input.asset_id, amount, price, state.db.as_ref(), state.cache.as_ref()).await?
         }
         OrderType::Sell => {
-            let order_id = try_or_http_err!(
-                __create_sell_order(
-                    token.claims.sub,
-                    input.asset_id,
-                    try_or_http_err!(Decimal::from_f64_retain(input.amount).ok_or("Wrong amount")).round_dp(3),
-                    try_or_http_err!(Decimal::from_f64_retain(input.price).ok_or("Wrong price")).round_dp(3),
-                    state.db.as_ref()
-                )
-                .await
-            );
-            HttpResponse::Ok().json(CommonResponse::<OrderCreateResponse> {
-                status: ResponseStatus::Ok,
-                data: OrderCreateResponse { order_id },
-                error: None,
-            })
+            __create_sell_order(token.claims.sub, input.asset_id, amount, price, state.db.as_ref(), state.cache.as_ref()).await?
         }
-    }
+    };
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<OrderCreateResponse> {
+        status: ResponseStatus::Ok,
+        data: OrderCreateResponse { order_id },
+        error: None,
+        code: None,
+    }))
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -75,19 +62,29 @@ pub struct OrderCreateResponse {
     order_id: i32,
 }
 
-async fn __create_buy_order(
+fn __positive_decimal(value: f64, message: &str) -> Result<Decimal, AppError> {
+    Decimal::from_f64_retain(value)
+        .map(|value| value.round_dp(3))
+        .filter(|value| value.is_sign_positive() && !value.is_zero())
+        .ok_or(AppError::Validation(message.into()))
+}
+
+pub(crate) async fn __create_buy_order(
     user_id: i32,
     asset_id: i32,
     amount: Decimal,
     price: Decimal,
     db: &DbConn,
-) -> Result<i32, Box<dyn Error + Send + Sync>> {
-    let user = match users::Entity::find_by_id(user_id).one(db).await? {
-        Some(_user) => _user,
-        None => return Err("No user".into()),
-    };
+    cache: &Client,
+) -> Result<i32, AppError> {
+    let txn = db.begin().await?;
+    let user = users::Entity::find_by_id(user_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
     if user.balance < price {
-        return Err("Not enough balance".into());
+        return Err(AppError::InsufficientFunds);
     }
 
     let order = orders::ActiveModel {
@@ -102,33 +99,36 @@ async fn __create_buy_order(
     let new_balance = (user.balance - price).round_dp(3);
     let mut active_user = user.into_active_model();
     active_user.balance = Set(new_balance);
-    active_user.update(db).await?;
-    let data = Orders::insert(order).exec(db).await?;
+    active_user.update(&txn).await?;
+    let order = order.insert(&txn).await?;
+    txn.commit().await?;
+    publish_event(cache, &Topic::Book(asset_id), &BookEvent::from(&order)).await;
 
-    Ok(data.last_insert_id)
+    Ok(order.id)
 }
 
-async fn __create_sell_order(
+pub(crate) async fn __create_sell_order(
     user_id: i32,
     asset_id: i32,
     amount: Decimal,
     price: Decimal,
     db: &DbConn,
-) -> Result<i32, Box<dyn Error + Send + Sync>> {
-    let user_balance = match user_balances::Entity::find()
+    cache: &Client,
+) -> Result<i32, AppError> {
+    let txn = db.begin().await?;
+    let user_balance = user_balances::Entity::find()
         .filter(
             Condition::all()
                 .add(user_balances::Column::UserId.eq(user_id))
                 .add(user_balances::Column::AssetId.eq(asset_id)),
         )
-        .one(db)
-        .await? {
-        Some(_balance) => { _balance }
-        None => { return Err("No asset data for this user".into()) }
-    };
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::InsufficientAssets)?;
     
     if user_balance.amount < amount {
-        return Err("Not enough asset amount".into())
+        return Err(AppError::InsufficientAssets)
     }
 
     let order = orders::ActiveModel {
@@ -143,8 +143,10 @@ async fn __create_sell_order(
     let new_amount = (user_balance.amount - amount).round_dp(3);
     let mut active_user_balance = user_balance.into_active_model();
     active_user_balance.amount = Set(new_amount);
-    active_user_balance.update(db).await?;
-    let data = Orders::insert(order).exec(db).await?;
+    active_user_balance.update(&txn).await?;
+    let order = order.insert(&txn).await?;
+    txn.commit().await?;
+    publish_event(cache, &Topic::Book(asset_id), &BookEvent::from(&order)).await;
 
-    Ok(data.last_insert_id)
+    Ok(order.id)
 }
@@ -0,0 +1,34 @@
+use crate::utils::app_error::AppError;
+use crate::utils::portfolio::{portfolio, PortfolioInfo};
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
+use entity::users;
+use sea_orm::EntityTrait;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+#[utoipa::path(params(PortfolioPath), tag = "User")]
+#[get("/api/v1/portfolio/{user_id}")]
+pub async fn user_portfolio(
+    state: web::Data<AppState>,
+    path: web::Path<PortfolioPath>,
+) -> Result<HttpResponse, AppError> {
+    let user = users::Entity::find_by_id(path.user_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
+    let data = portfolio(state.db.as_ref(), &state.cache, user.id).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<PortfolioInfo> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct PortfolioPath {
+    pub user_id: i32,
+}
@@ -4,11 +4,17 @@ pub use super::refresh;
 pub use super::user_assets;
 pub use super::trades_history;
 pub use super::chat_history;
+pub use super::chat_read;
+pub use super::chat_message;
+pub use super::chat_rooms;
+pub use super::chat_moderation;
+pub use super::moderation;
 pub use super::private_chat;
 pub use super::market;
 pub use super::user_orders;
 pub use super::market_buy;
 pub use super::market_sell;
+pub use super::market_quote;
 pub use super::top_users;
 pub use super::order_buy;
 pub use super::order_sell;
@@ -19,6 +25,17 @@ pub use super::price_history;
 pub use super::create_event;
 pub use super::get_events;
 pub use super::create_bot;
+pub use super::admin_bots;
+pub use super::user_scripts;
+pub use super::backtest;
+pub use super::portfolio;
+pub use super::equity_history;
+pub use super::seasons;
+pub use super::admin_seasons;
+pub use super::leagues;
+pub use super::notifications;
+pub use super::ws;
+pub use super::alerts;
 pub use super::get_bots;
 pub use super::get_user_place;
 pub use super::get_chats;
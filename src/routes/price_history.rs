@@ -1,6 +1,7 @@
+use crate::utils::app_error::AppError;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
 use chrono::Utc;
 use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
@@ -11,20 +12,18 @@ use utoipa::{IntoParams, ToSchema};
 pub async fn price_history(
     state: web::Data<AppState>,
     path: web::Path<PricePath>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let day_ago = Utc::now().timestamp() - 3600;
-    let mut redis_conn = try_or_http_err!(state.cache.get_multiplexed_async_connection().await);
-    let history: Vec<(String, i64)> = try_or_http_err!(
-        redis_conn
-            .zrangebyscore_withscores(
-                format!("asset_price_history:{}", path.asset_id),
-                day_ago,
-                "+inf",
-            )
-            .await
-    );
+    let mut redis_conn = state.cache.get_multiplexed_async_connection().await?;
+    let history: Vec<(String, i64)> = redis_conn
+        .zrangebyscore_withscores(
+            format!("asset_price_history:{}", path.asset_id),
+            day_ago,
+            "+inf",
+        )
+        .await?;
 
-    HttpResponse::Ok().json(CommonResponse::<Vec<PriceResponse>> {
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<PriceResponse>> {
         status: ResponseStatus::Ok,
         data: history
             .iter()
@@ -40,7 +39,8 @@ pub async fn price_history(
             })
             .collect(),
         error: None,
-    })
+        code: None,
+    }))
 }
 
 #[derive(Deserialize, ToSchema, IntoParams)]
@@ -1,46 +1,408 @@
 use crate::utils::jwt::AccessToken;
 use crate::{AppState, CHAT_SESSIONS};
 use actix::{Actor, AsyncContext, Handler, Message as ActixMessage, StreamHandler};
-use actix_web::{error, get, web, Error, HttpRequest, HttpResponse};
+use crate::utils::app_error::{AppError, ErrorCode};
+use actix_web::{get, web, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use entity::messages::Entity as MessageEntity;
-use entity::{messages, users};
-use sea_orm::{EntityTrait, Set};
+use crate::utils::leagues::find_member;
+use crate::utils::chat_rooms::{find_room_member, room_member_ids};
+use crate::utils::attachments::resolve_attachment;
+use crate::utils::moderation::{blocked_by, check_chat_allowed, check_not_blocked};
+use crate::utils::pubsub::{
+    publish, publish_event, publish_event_skipping, publish_signal, Envelope, CHAT_CHANNEL,
+};
+use crate::structs::chat_structs::{Attachment, AttachmentInput};
+use crate::structs::ws_structs::Topic;
+use crate::traits::heartbeat::Heartbeat;
+use entity::{chat_reads, chat_room_messages, league_members, league_messages, messages, users};
+use sea_orm::prelude::Expr;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, Set, UpdateMany};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Instant;
 use utoipa::ToSchema;
 
 impl AppState {
-    async fn send_message(&self, from_id: i32, recipient_id: i32, text: String) {
-        if from_id == recipient_id { return }
-        
+    /// Chat frames don't go through the HTTP middleware, socket senders are limited here.
+    async fn __rate_limit(&self, group: &str, user_id: i32) -> Result<(), AppError> {
+        if let Some(rule) = self.rate_limiter.rule(group) {
+            let subject = format!("user:{user_id}");
+            if let Some(retry_after) = self.rate_limiter.check(&self.cache, rule, &subject).await {
+                return Err(AppError::RateLimited(retry_after));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rate limits the sender, then sends a private message to `recipient_id`, a league
+    /// chat message to `league_id` or a room message to `room_id`.
+    pub(crate) async fn send_chat(
+        &self,
+        from_id: i32,
+        recipient_id: Option<i32>,
+        league_id: Option<i32>,
+        room_id: Option<i32>,
+        text: String,
+        attachment: Option<AttachmentInput>,
+    ) -> Result<(), AppError> {
+        self.__rate_limit("chat_message", from_id).await?;
+        check_chat_allowed(self.db.as_ref(), from_id, true).await?;
+        let text = self.chat_filter.apply(&text)?;
+        if attachment.is_some() && recipient_id.is_none() {
+            return Err(AppError::Validation(
+                "Attachments are only supported in private messages".to_string(),
+            ));
+        }
+        match (room_id, league_id, recipient_id) {
+            (Some(room_id), _, _) => self.send_room_message(from_id, room_id, text).await,
+            (None, Some(league_id), _) => self.send_league_message(from_id, league_id, text).await,
+            (None, None, Some(recipient_id)) => {
+                self.send_message(from_id, recipient_id, text, attachment).await
+            }
+            (None, None, None) => Err(AppError::Validation(
+                "recipient_id, league_id or room_id is required".to_string(),
+            )),
+        }
+    }
+
+    async fn send_message(
+        &self,
+        from_id: i32,
+        recipient_id: i32,
+        text: String,
+        attachment: Option<AttachmentInput>,
+    ) -> Result<(), AppError> {
+        if from_id == recipient_id {
+            return Err(AppError::Validation("Can't message yourself".to_string()));
+        }
+        check_not_blocked(self.db.as_ref(), from_id, recipient_id).await?;
+        let attachment = match attachment {
+            Some(input) => {
+                Some(resolve_attachment(self.db.as_ref(), self.cache.as_ref(), from_id, input).await?)
+            }
+            None => None,
+        };
+        if text.trim().is_empty() && attachment.is_none() {
+            return Err(AppError::Validation("Message is empty".to_string()));
+        }
+
         let created_at = Utc::now();
         let message = messages::ActiveModel {
             from_id: Set(from_id),
             recipient_id: Set(recipient_id),
             text: Set(text.clone()),
             created_at: Set(created_at.naive_utc()),
+            attachment: Set(attachment.as_ref().and_then(|card| serde_json::to_value(card).ok())),
+            ..Default::default()
+        };
+        let message_id = MessageEntity::insert(message)
+            .exec(self.db.as_ref())
+            .await?
+            .last_insert_id;
+
+        let outgoing = OutgoingClientMessage {
+            from_id,
+            message_id,
+            text,
+            attachment,
+            created_at
+        };
+        publish_event(self.cache.as_ref(), &Topic::Chat(recipient_id), &outgoing).await;
+        let envelope = Envelope {
+            user_ids: vec![recipient_id],
+            payload: serde_json::to_string(&outgoing).unwrap_or_default(),
+        };
+        publish(self.cache.as_ref(), CHAT_CHANNEL, envelope).await;
+        Ok(())
+    }
+
+    /// Stores a league chat message and delivers it to every other connected member.
+    async fn send_league_message(&self, from_id: i32, league_id: i32, text: String) -> Result<(), AppError> {
+        find_member(self.db.as_ref(), league_id, from_id).await?;
+
+        let created_at = Utc::now();
+        let message = league_messages::ActiveModel {
+            league_id: Set(league_id),
+            from_id: Set(from_id),
+            text: Set(text.clone()),
+            created_at: Set(created_at.naive_utc()),
+            ..Default::default()
+        };
+        let message_id = league_messages::Entity::insert(message)
+            .exec(self.db.as_ref())
+            .await?
+            .last_insert_id;
+
+        let members = league_members::Entity::find()
+            .filter(league_members::Column::LeagueId.eq(league_id))
+            .filter(league_members::Column::UserId.ne(from_id))
+            .all(self.db.as_ref())
+            .await?;
+        let blockers = blocked_by(self.db.as_ref(), from_id).await?;
+        let members: Vec<_> = members
+            .into_iter()
+            .filter(|member| !blockers.contains(&member.user_id))
+            .collect();
+        let outgoing = OutgoingLeagueMessage {
+            league_id,
+            from_id,
+            message_id,
+            text,
+            created_at,
+        };
+        for member in &members {
+            publish_event(self.cache.as_ref(), &Topic::Chat(member.user_id), &outgoing).await;
+        }
+        let envelope = Envelope {
+            user_ids: members.into_iter().map(|member| member.user_id).collect(),
+            payload: serde_json::to_string(&outgoing).unwrap_or_default(),
+        };
+        publish(self.cache.as_ref(), CHAT_CHANNEL, envelope).await;
+        Ok(())
+    }
+
+    /// Stores a group or asset room message and delivers it to every other member.
+    async fn send_room_message(&self, from_id: i32, room_id: i32, text: String) -> Result<(), AppError> {
+        find_room_member(self.db.as_ref(), room_id, from_id).await?;
+
+        let created_at = Utc::now();
+        let message = chat_room_messages::ActiveModel {
+            room_id: Set(room_id),
+            from_id: Set(from_id),
+            text: Set(text.clone()),
+            created_at: Set(created_at.naive_utc()),
             ..Default::default()
         };
-        let message_id = match MessageEntity::insert(message).exec(self.db.as_ref()).await {
-            Ok(msg) => {msg.last_insert_id}
-            Err(_) => { return }
+        let message_id = chat_room_messages::Entity::insert(message)
+            .exec(self.db.as_ref())
+            .await?
+            .last_insert_id;
+
+        let mut member_ids = room_member_ids(self.db.as_ref(), room_id).await?;
+        let blockers = blocked_by(self.db.as_ref(), from_id).await?;
+        member_ids.retain(|user_id| *user_id != from_id && !blockers.contains(user_id));
+        let outgoing = OutgoingRoomMessage {
+            room_id,
+            from_id,
+            message_id,
+            text,
+            created_at,
+        };
+        // One sequenced event for the room, members subscribe to `room:{room_id}`
+        let skip_user_ids = blockers.into_iter().collect();
+        publish_event_skipping(self.cache.as_ref(), &Topic::Room(room_id), &outgoing, skip_user_ids).await;
+        let envelope = Envelope {
+            user_ids: member_ids,
+            payload: serde_json::to_string(&outgoing).unwrap_or_default(),
+        };
+        publish(self.cache.as_ref(), CHAT_CHANNEL, envelope).await;
+        Ok(())
+    }
+
+    /// Moves the reader's marker in the conversation with `peer_id` up to `message_id` and
+    /// sends the peer a read receipt. The marker never moves back.
+    pub(crate) async fn mark_read(&self, reader_id: i32, peer_id: i32, message_id: i32) -> Result<(), AppError> {
+        self.__rate_limit("chat_typing", reader_id).await?;
+        messages::Entity::find_by_id(message_id)
+            .filter(__conversation(reader_id, peer_id))
+            .one(self.db.as_ref())
+            .await?
+            .ok_or(AppError::NotFound("No message in this chat"))?;
+
+        let read_at = Utc::now();
+        chat_reads::Entity::insert(chat_reads::ActiveModel {
+            user_id: Set(reader_id),
+            peer_id: Set(peer_id),
+            last_read_message_id: Set(message_id),
+            updated_at: Set(read_at.naive_utc()),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([chat_reads::Column::UserId, chat_reads::Column::PeerId])
+                .value(
+                    chat_reads::Column::LastReadMessageId,
+                    Expr::cust("GREATEST(chat_reads.last_read_message_id, EXCLUDED.last_read_message_id)"),
+                )
+                .update_column(chat_reads::Column::UpdatedAt)
+                .to_owned(),
+        )
+        .exec_without_returning(self.db.as_ref())
+        .await?;
+
+        let receipt = OutgoingReadReceipt {
+            reader_id,
+            message_id,
+            read_at,
         };
+        publish_event(self.cache.as_ref(), &Topic::Chat(peer_id), &receipt).await;
+        let envelope = Envelope {
+            user_ids: vec![peer_id],
+            payload: serde_json::to_string(&receipt).unwrap_or_default(),
+        };
+        publish(self.cache.as_ref(), CHAT_CHANNEL, envelope).await;
+        Ok(())
+    }
+
+    /// Replaces the text of the sender's own private message and shows the change to both
+    /// sides. Only possible within the edit window.
+    pub(crate) async fn edit_message(&self, from_id: i32, message_id: i32, text: String) -> Result<(), AppError> {
+        check_chat_allowed(self.db.as_ref(), from_id, true).await?;
+        let text = self.chat_filter.apply(&text)?;
+
+        let edited_at = Utc::now();
+        let mut update = messages::Entity::update_many()
+            .col_expr(messages::Column::Text, Expr::value(text.clone()))
+            .col_expr(messages::Column::EditedAt, Expr::value(edited_at.naive_utc()));
+        if text.trim().is_empty() {
+            // Only a message with an attachment may lose its whole text
+            update = update.filter(messages::Column::Attachment.is_not_null());
+        }
+        let message = self.__update_own_message(from_id, message_id, update).await?;
+
+        let outgoing = OutgoingMessageEdit {
+            from_id,
+            message_id,
+            text,
+            edited_at,
+        };
+        self.__publish_change(&message, &outgoing).await;
+        Ok(())
+    }
+
+    /// Clears the sender's own private message, the row stays so read markers and reports
+    /// still point somewhere, and the text is kept for moderators. Only possible within the
+    /// edit window. Clients apply the delete event over the earlier message event, both stay
+    /// in the topic history.
+    pub(crate) async fn delete_message(&self, from_id: i32, message_id: i32) -> Result<(), AppError> {
+        let deleted_at = Utc::now();
+        let update = messages::Entity::update_many()
+            .col_expr(messages::Column::DeletedText, Expr::col(messages::Column::Text).into())
+            .col_expr(messages::Column::Text, Expr::value(String::new()))
+            .col_expr(messages::Column::Attachment, Expr::value(Option::<serde_json::Value>::None))
+            .col_expr(messages::Column::DeletedAt, Expr::value(deleted_at.naive_utc()));
+        let message = self.__update_own_message(from_id, message_id, update).await?;
 
-        if let Some(addr) = CHAT_SESSIONS.read().await.get(&recipient_id) {
-            let _ = addr.do_send(OutgoingClientMessage {
-                from_id,
-                message_id,
-                text,
-                created_at
+        let outgoing = OutgoingMessageDelete {
+            from_id,
+            message_id,
+            deleted_at,
+        };
+        self.__publish_change(&message, &outgoing).await;
+        Ok(())
+    }
+
+    /// Applies `update` to the sender's own message in one statement, only while it is not
+    /// deleted and still inside the edit window.
+    async fn __update_own_message(
+        &self,
+        from_id: i32,
+        message_id: i32,
+        update: UpdateMany<messages::Entity>,
+    ) -> Result<messages::Model, AppError> {
+        let window_start = Utc::now().naive_utc() - edit_window();
+        let updated = update
+            .filter(messages::Column::Id.eq(message_id))
+            .filter(messages::Column::FromId.eq(from_id))
+            .filter(messages::Column::DeletedAt.is_null())
+            .filter(messages::Column::CreatedAt.gt(window_start))
+            .exec_with_returning(self.db.as_ref())
+            .await?;
+        if let Some(message) = updated.into_iter().next() {
+            return Ok(message);
+        }
+
+        // Nothing changed, find out why
+        let message = messages::Entity::find_by_id(message_id)
+            .filter(messages::Column::FromId.eq(from_id))
+            .filter(messages::Column::DeletedAt.is_null())
+            .one(self.db.as_ref())
+            .await?
+            .ok_or(AppError::NotFound("No message"))?;
+        if message.created_at <= window_start {
+            return Err(AppError::Forbidden("Message can no longer be changed"));
+        }
+        Err(AppError::Validation("Message is empty".to_string()))
+    }
+
+    /// Sends an edit or delete to both sides, the author's other sessions included.
+    async fn __publish_change<T: Serialize>(&self, message: &messages::Model, outgoing: &T) {
+        for user_id in [message.recipient_id, message.from_id] {
+            publish_event(self.cache.as_ref(), &Topic::Chat(user_id), outgoing).await;
+        }
+        let envelope = Envelope {
+            user_ids: vec![message.recipient_id, message.from_id],
+            payload: serde_json::to_string(outgoing).unwrap_or_default(),
+        };
+        publish(self.cache.as_ref(), CHAT_CHANNEL, envelope).await;
+    }
+
+    /// Tells `recipient_id` that the user started or stopped typing. Nothing is stored.
+    pub(crate) async fn send_typing(&self, from_id: i32, recipient_id: i32, typing: bool) -> Result<(), AppError> {
+        if from_id == recipient_id {
+            return Err(AppError::Validation("Can't message yourself".to_string()));
+        }
+        self.__rate_limit("chat_typing", from_id).await?;
+        check_not_blocked(self.db.as_ref(), from_id, recipient_id).await?;
+        // Typing events only go to people the sender already talks to
+        messages::Entity::find()
+            .filter(__conversation(from_id, recipient_id))
+            .one(self.db.as_ref())
+            .await?
+            .ok_or(AppError::Forbidden("No conversation with this user"))?;
+        let outgoing = OutgoingTyping { from_id, typing };
+        publish_signal(self.cache.as_ref(), &Topic::Chat(recipient_id), &outgoing).await;
+        let envelope = Envelope {
+            user_ids: vec![recipient_id],
+            payload: serde_json::to_string(&outgoing).unwrap_or_default(),
+        };
+        publish(self.cache.as_ref(), CHAT_CHANNEL, envelope).await;
+        Ok(())
+    }
+}
+
+/// Private messages between two users, in either direction.
+fn __conversation(user_id: i32, peer_id: i32) -> Condition {
+    Condition::any()
+        .add(
+            Condition::all()
+                .add(messages::Column::FromId.eq(user_id))
+                .add(messages::Column::RecipientId.eq(peer_id)),
+        )
+        .add(
+            Condition::all()
+                .add(messages::Column::FromId.eq(peer_id))
+                .add(messages::Column::RecipientId.eq(user_id)),
+        )
+}
+
+/// How long after sending a private message its author may edit or delete it,
+/// `CHAT_EDIT_WINDOW_SECS` or 15 minutes.
+pub(crate) fn edit_window() -> Duration {
+    Duration::seconds(
+        std::env::var("CHAT_EDIT_WINDOW_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(900),
+    )
+}
+
+/// Writes a relayed frame to the chat sockets connected to this instance.
+pub(crate) async fn deliver_chat(user_ids: &[i32], message: String) {
+    let sessions = CHAT_SESSIONS.read().await;
+    for user_id in user_ids {
+        if let Some(addr) = sessions.get(user_id) {
+            addr.do_send(ChatFrame {
+                message: message.clone(),
             });
         }
     }
 }
 
 #[utoipa::path(
-    request_body=IncomingClientMessage,
+    request_body=IncomingChatFrame,
     tag="User",
     security(
         ("bearer_token" = [])
@@ -52,49 +414,178 @@ pub async fn chat_ws(
     stream: web::Payload,
     state: web::Data<AppState>,
     token: AccessToken,
-) -> Result<HttpResponse, Error> {
-    match users::Entity::find_by_id(token.claims.sub).one(state.db.as_ref()).await
-    {
-        Ok(data) => match data {
-            Some(user) => {
-                let session = ChatSession {
-                    id: user.id,
-                    state: state.into_inner(),
-                };
-                ws::start(session, &req, stream)
-            }
-            None => { Err(error::ErrorUnauthorized("No user")) }
-        },
-        Err(err) => Err(error::ErrorInternalServerError(err)),
-    }
+) -> Result<HttpResponse, AppError> {
+    let user = users::Entity::find_by_id(token.claims.sub)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::Unauthorized("No user"))?;
+    check_chat_allowed(state.db.as_ref(), user.id, false).await?;
+
+    let session = ChatSession {
+        id: user.id,
+        state: state.into_inner(),
+        last_seen: Instant::now(),
+    };
+    ws::start(session, &req, stream).map_err(AppError::internal)
 }
 
 
+/// What the client sends: a message, an edit or deletion of an own message, a typing
+/// indicator or a read marker.
+#[derive(Deserialize, Debug, ToSchema)]
+#[serde(untagged)]
+enum IncomingChatFrame {
+    Edit(IncomingEdit),
+    Delete(IncomingDelete),
+    Typing(IncomingTyping),
+    Read(IncomingRead),
+    /// Last, every field of a message is optional
+    Message(IncomingClientMessage),
+}
+
 #[derive(ActixMessage, Serialize, Deserialize, Debug, ToSchema)]
 #[rtype(result = "()")]
 struct IncomingClientMessage {
-    recipient_id: i32,
+    /// Private message recipient, ignored when `league_id` is set
+    #[serde(default)]
+    recipient_id: Option<i32>,
+    /// Sends the message to the league chat room instead
+    #[serde(default)]
+    league_id: Option<i32>,
+    /// Sends the message to a group or asset room instead
+    #[serde(default)]
+    room_id: Option<i32>,
+    #[serde(default)]
     text: String,
+    /// Trade, order or asset card, private messages only
+    #[serde(default)]
+    attachment: Option<AttachmentInput>,
 }
 
-#[derive(ActixMessage, Serialize, Deserialize, Debug)]
-#[rtype(result = "()")]
+#[derive(Deserialize, Debug, ToSchema)]
+struct IncomingEdit {
+    edit_message_id: i32,
+    text: String,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+struct IncomingDelete {
+    delete_message_id: i32,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+struct IncomingTyping {
+    recipient_id: i32,
+    typing: bool,
+}
+
+/// Marks the chat with `recipient_id` as read up to `read_message_id`
+#[derive(Deserialize, Debug, ToSchema)]
+struct IncomingRead {
+    recipient_id: i32,
+    read_message_id: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 struct OutgoingClientMessage {
+    from_id: i32,
+    message_id: i32,
+    text: String,
+    attachment: Option<Attachment>,
+    created_at: DateTime<Utc>
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename = "edit")]
+struct OutgoingMessageEdit {
+    from_id: i32,
+    message_id: i32,
+    text: String,
+    edited_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename = "delete")]
+struct OutgoingMessageDelete {
+    from_id: i32,
+    message_id: i32,
+    deleted_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug)]
+struct OutgoingLeagueMessage {
+    league_id: i32,
     from_id: i32,
     message_id: i32,
     text: String,
     created_at: DateTime<Utc>
 }
 
+#[derive(Serialize, Debug)]
+struct OutgoingRoomMessage {
+    room_id: i32,
+    from_id: i32,
+    message_id: i32,
+    text: String,
+    created_at: DateTime<Utc>
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename = "typing")]
+struct OutgoingTyping {
+    from_id: i32,
+    typing: bool,
+}
+
+/// The peer has read the chat up to `message_id`
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename = "read")]
+struct OutgoingReadReceipt {
+    reader_id: i32,
+    message_id: i32,
+    read_at: DateTime<Utc>,
+}
+
+/// Frame relayed from pub/sub, already serialized.
+#[derive(ActixMessage)]
+#[rtype(result = "()")]
+struct ChatFrame {
+    message: String,
+}
+
+#[derive(ActixMessage, Serialize, Debug)]
+#[rtype(result = "()")]
+struct ChatErrorMessage {
+    error: String,
+    code: ErrorCode,
+}
+
+impl From<AppError> for ChatErrorMessage {
+    fn from(err: AppError) -> Self {
+        Self {
+            error: err.to_string(),
+            code: err.code(),
+        }
+    }
+}
+
 pub(crate) struct ChatSession {
     id: i32,
     state: Arc<AppState>,
+    last_seen: Instant,
+}
+
+impl Heartbeat for ChatSession {
+    fn last_seen(&mut self) -> &mut Instant {
+        &mut self.last_seen
+    }
 }
 
 impl Actor for ChatSession {
     type Context = ws::WebsocketContext<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
+        self.start_heartbeat(ctx);
         let addr = ctx.address();
         let session_id = self.id;
         tokio::spawn(async move {
@@ -112,26 +603,67 @@ impl Actor for ChatSession {
         actix::Running::Stop
     }
 }
-impl Handler<OutgoingClientMessage> for ChatSession {
+impl Handler<ChatFrame> for ChatSession {
+    type Result = ();
+    fn handle(&mut self, msg: ChatFrame, ctx: &mut Self::Context) {
+        ctx.text(msg.message);
+    }
+}
+
+impl Handler<ChatErrorMessage> for ChatSession {
     type Result = ();
-    fn handle(&mut self, msg: OutgoingClientMessage, ctx: &mut Self::Context) {
+    fn handle(&mut self, msg: ChatErrorMessage, ctx: &mut Self::Context) {
         let message_json = serde_json::to_string(&msg).unwrap_or_default();
         ctx.text(message_json);
     }
 }
 
 impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ChatSession {
-    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, _ctx: &mut Self::Context) {
-        if let Ok(ws::Message::Text(text)) = msg {
-            if let Ok(client_msg) = serde_json::from_str::<IncomingClientMessage>(&text) {
-                let state = Arc::clone(&self.state);
-                let from_id = self.id;
-                tokio::spawn(async move {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        let Some(text) = self.control_frame(msg, ctx) else {
+            return;
+        };
+        let frame = match serde_json::from_str::<IncomingChatFrame>(&text) {
+            Ok(frame) => frame,
+            Err(err) => {
+                let error = AppError::Validation(format!("Malformed message: {err}"));
+                ctx.address().do_send(ChatErrorMessage::from(error));
+                return;
+            }
+        };
+        let state = Arc::clone(&self.state);
+        let from_id = self.id;
+        let addr = ctx.address();
+        tokio::spawn(async move {
+            let sent = match frame {
+                IncomingChatFrame::Message(message) => {
                     state
-                        .send_message(from_id, client_msg.recipient_id, client_msg.text)
-                        .await;
-                });
+                        .send_chat(
+                            from_id,
+                            message.recipient_id,
+                            message.league_id,
+                            message.room_id,
+                            message.text,
+                            message.attachment,
+                        )
+                        .await
+                }
+                IncomingChatFrame::Edit(edit) => {
+                    state.edit_message(from_id, edit.edit_message_id, edit.text).await
+                }
+                IncomingChatFrame::Delete(delete) => {
+                    state.delete_message(from_id, delete.delete_message_id).await
+                }
+                IncomingChatFrame::Typing(typing) => {
+                    state.send_typing(from_id, typing.recipient_id, typing.typing).await
+                }
+                IncomingChatFrame::Read(read) => {
+                    state.mark_read(from_id, read.recipient_id, read.read_message_id).await
+                }
+            };
+            if let Err(err) = sent {
+                addr.do_send(ChatErrorMessage::from(err));
             }
-        }
+        });
     }
 }
@@ -1,13 +1,10 @@
+use crate::utils::app_error::AppError;
 use crate::utils::limited_list_with_timeout::LimitedListWithTimeout;
+use crate::utils::mail::Mailer;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{extract_db_response_or_http_err_with_opt_msg, try_or_http_err, AppState};
-use crate::{unwrap_or_http_err_with_opt_msg, RECOVERSTORAGE};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::{AppState, RECOVERSTORAGE};
+use actix_web::{post, web, HttpResponse};
 use entity::users;
-use lettre::message::header::ContentType;
-use lettre::message::Mailbox;
-use lettre::transport::smtp::authentication::Credentials;
-use lettre::{Message, SmtpTransport, Transport};
 use rand::Rng;
 use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use serde::Deserialize;
@@ -22,16 +19,14 @@ use utoipa::ToSchema;
 pub async fn recover_account(
     state: web::Data<AppState>,
     input: web::Json<RecoverEmailInput>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let input = input.into_inner();
 
-    let user = extract_db_response_or_http_err_with_opt_msg!(
-        users::Entity::find()
-            .filter(users::Column::Email.eq(&input.email))
-            .one(state.db.as_ref())
-            .await,
-        "User not found"
-    );
+    let user = users::Entity::find()
+        .filter(users::Column::Email.eq(&input.email))
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("User not found"))?;
 
     let code = rand::rng().random_range(100_000..=999_999);
     {
@@ -39,11 +34,7 @@ pub async fn recover_account(
         match codes_storage.get_mut(&user.id) {
             Some(codes) => {
                 if codes.is_full().await {
-                    return HttpResponse::Ok().json(CommonResponse::<()> {
-                        status: ResponseStatus::Error,
-                        data: (),
-                        error: Some("Too many attempts".into()),
-                    });
+                    return Err(AppError::TooManyRequests);
                 }
                 codes.add(code).await;
             }
@@ -55,23 +46,19 @@ pub async fn recover_account(
         }
     }
 
-    match send_main(
+    send_main(
         input.email.as_str(),
         code,
         state.recover_from.as_str(),
         state.recover_password.as_str(),
-    ) {
-        Ok(_) => HttpResponse::Ok().json(CommonResponse::<()> {
-            status: ResponseStatus::Ok,
-            data: (),
-            error: None,
-        }),
-        Err(err) => HttpResponse::InternalServerError().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some(err.to_string()),
-        })
-    }
+    )?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
 }
 
 fn send_main(
@@ -122,25 +109,15 @@ fn send_main(
     </html>
     "#;
 
-    let email = Message::builder()
-        .from(recover_from.parse::<Mailbox>()?)
-        .to(recover_to.parse::<Mailbox>()?)
-        .subject("Восстановление пароля")
-        .header(ContentType::TEXT_HTML)
-        .body(template.replace("{{reset_code}}", code.to_string().as_str()))
-        .unwrap();
-
-    let creds = Credentials::new(recover_from.to_string(), recover_password.to_string());
-
-    let mailer = SmtpTransport::relay("smtp.mail.ru")
-        .unwrap()
-        .credentials(creds)
-        .build();
-
-    match mailer.send(&email) {
-        Ok(_) => Ok(()),
-        Err(_) => Err(format!("Failed to send email: {:?}", email).into()),
-    }
+    let mailer = Mailer {
+        from: recover_from.to_string(),
+        password: recover_password.to_string(),
+    };
+    mailer.send_html(
+        recover_to,
+        "Восстановление пароля",
+        template.replace("{{reset_code}}", code.to_string().as_str()),
+    )
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
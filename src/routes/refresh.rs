@@ -1,8 +1,8 @@
-use crate::routes::login::LoginResponse;
+use crate::utils::app_error::AppError;
 use crate::utils::jwt::{generate_access_token, generate_refresh_token, Claims};
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
 use entity::users;
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use sea_orm::EntityTrait;
@@ -14,71 +14,51 @@ use utoipa::ToSchema;
     tag="Authorization"
 )]
 #[post("/api/v1/auth/refresh")]
-pub async fn refresh(state: web::Data<AppState>, input: web::Json<RefreshInput>) -> impl Responder {
+pub async fn refresh(state: web::Data<AppState>, input: web::Json<RefreshInput>) -> Result<HttpResponse, AppError> {
     let validation = Validation::new(Algorithm::HS256);
-    let token_data = match decode::<Claims>(
+    let token_data = decode::<Claims>(
         &input.refresh_token,
         &DecodingKey::from_secret(state.jwt_secret.as_ref()),
         &validation,
-    ) {
-        Ok(data) => data,
-        Err(_) => {
-            return HttpResponse::Unauthorized().json(CommonResponse::<()> {
-                status: ResponseStatus::Error,
-                data: (),
-                error: Some("Invalid refresh token".to_string()),
-            });
-        }
-    };
+    )
+    .map_err(|_| AppError::Unauthorized("Invalid refresh token"))?;
 
     let claims = token_data.claims;
 
     if claims.token_type != "refresh" {
-        return HttpResponse::Unauthorized().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("Invalid token type".to_string()),
-        });
+        return Err(AppError::Unauthorized("Invalid token type"));
     }
 
-    let user = try_or_http_err!(
-        users::Entity::find_by_id(claims.sub)
-            .one(state.db.as_ref())
-            .await
-    );
+    let user = users::Entity::find_by_id(claims.sub)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::Unauthorized("Unauthorized user"))?;
 
-    if let Some(user) = user {
-        let access_token = try_or_http_err!(generate_access_token(
-            user.id,
-            user.username.as_str(),
-            user.email.as_str(),
-            state.jwt_secret.as_str()
-        ));
-        let refresh_token = try_or_http_err!(generate_refresh_token(
-            user.id,
-            user.username.as_str(),
-            user.email.as_str(),
-            state.jwt_secret.as_str()
-        ));
+    let access_token = generate_access_token(
+        user.id,
+        user.username.as_str(),
+        user.email.as_str(),
+        state.jwt_secret.as_str()
+    )?;
+    let refresh_token = generate_refresh_token(
+        user.id,
+        user.username.as_str(),
+        user.email.as_str(),
+        state.jwt_secret.as_str()
+    )?;
 
-        return HttpResponse::Ok().json(CommonResponse {
-            status: ResponseStatus::Ok,
-            data: RefreshResponse {
-                access_token,
-                refresh_token,
-                user_id: user.id,
-                email: user.email,
-                username: user.username,
-            },
-            error: None,
-        });
-    }
-
-    HttpResponse::Unauthorized().json(CommonResponse::<Option<LoginResponse>> {
-        status: ResponseStatus::Error,
-        data: None,
-        error: Some("Unauthorized user".into()),
-    })
+    Ok(HttpResponse::Ok().json(CommonResponse {
+        status: ResponseStatus::Ok,
+        data: RefreshResponse {
+            access_token,
+            refresh_token,
+            user_id: user.id,
+            email: user.email,
+            username: user.username,
+        },
+        error: None,
+        code: None,
+    }))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -1,12 +1,13 @@
+use crate::utils::app_error::AppError;
 use crate::utils::jwt::{generate_access_token, generate_refresh_token};
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{post, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{post, web, HttpResponse};
 use argon2::password_hash::SaltString;
 use argon2::{Argon2, PasswordHasher};
 use entity::users;
 use rand_core::OsRng;
-use sea_orm::{ActiveModelTrait, Set};
+use sea_orm::{ActiveModelTrait, Set, SqlErr};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -18,13 +19,12 @@ use utoipa::ToSchema;
 pub async fn register(
     state: web::Data<AppState>,
     input: web::Json<RegisterInput>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     let input = input.into_inner();
 
     let salt = SaltString::generate(&mut OsRng);
     let password_hash = Argon2::default()
-        .hash_password(input.password.as_bytes(), &salt)
-        .unwrap()
+        .hash_password(input.password.as_bytes(), &salt)?
         .to_string();
 
     let new_user = users::ActiveModel {
@@ -33,32 +33,36 @@ pub async fn register(
         username: Set(input.username),
         ..Default::default()
     };
-    
-    match new_user.insert(state.db.as_ref()).await {
-        Ok(data) => {
-            let access_token = try_or_http_err!(generate_access_token(data.id, data.username.as_str(), data.email.as_str(), state.jwt_secret.as_str()));
-            let refresh_token = try_or_http_err!(generate_refresh_token(data.id, data.username.as_str(), data.email.as_str(), state.jwt_secret.as_str()));
 
-            HttpResponse::Created().json(
-                CommonResponse::<Option<RegisterResponse>> {
-                    status: ResponseStatus::Ok,
-                    data: Some(RegisterResponse {
-                        access_token,
-                        refresh_token,
-                        user_id: data.id,
-                        email: data.email,
-                        username: data.username,
-                    }),
-                    error: None,
+    let data = match new_user.insert(state.db.as_ref()).await {
+        Ok(data) => data,
+        Err(err) => {
+            return match err.sql_err() {
+                Some(SqlErr::UniqueConstraintViolation(_)) => {
+                    Err(AppError::AlreadyExists("User with this email or username already exists"))
                 }
-            )
+                _ => Err(err.into()),
+            }
         }
-        Err(err) => HttpResponse::InternalServerError().json(CommonResponse::<Option<RegisterResponse>> {
-            status: ResponseStatus::Error,
-            data: None,
-            error: Some(err.to_string()),
-        }),
-    }
+    };
+
+    let access_token = generate_access_token(data.id, data.username.as_str(), data.email.as_str(), state.jwt_secret.as_str())?;
+    let refresh_token = generate_refresh_token(data.id, data.username.as_str(), data.email.as_str(), state.jwt_secret.as_str())?;
+
+    Ok(HttpResponse::Created().json(
+        CommonResponse::<RegisterResponse> {
+            status: ResponseStatus::Ok,
+            data: RegisterResponse {
+                access_token,
+                refresh_token,
+                user_id: data.id,
+                email: data.email,
+                username: data.username,
+            },
+            error: None,
+            code: None,
+        }
+    ))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -75,4 +79,4 @@ pub struct RegisterResponse {
     user_id: i32,
     email: String,
     username: String,
-}
\ No newline at end of file
+}
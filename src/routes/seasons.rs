@@ -0,0 +1,156 @@
+use crate::utils::app_error::AppError;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
+use entity::{season_results, seasons, users};
+use sea_orm::prelude::{DateTime, Decimal};
+use sea_orm::{
+    ColumnTrait, EntityTrait, FromQueryResult, JoinType, QueryFilter, QueryOrder, QuerySelect,
+    RelationTrait,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_RESULTS_PAGE: u64 = 100;
+
+#[utoipa::path(tag = "User")]
+#[get("/api/v1/seasons")]
+pub async fn seasons_list(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let data = seasons::Entity::find()
+        .select_only()
+        .column(seasons::Column::Id)
+        .column(seasons::Column::Name)
+        .column(seasons::Column::StartsAt)
+        .column(seasons::Column::EndsAt)
+        .column(seasons::Column::FinishedAt)
+        .order_by_desc(seasons::Column::StartsAt)
+        .into_model::<SeasonInfo>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<SeasonInfo>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(params(SeasonPath, SeasonResultsQuery), tag = "User")]
+#[get("/api/v1/seasons/{season_id}/results")]
+pub async fn season_results_list(
+    state: web::Data<AppState>,
+    path: web::Path<SeasonPath>,
+    query: web::Query<SeasonResultsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let season = seasons::Entity::find_by_id(path.season_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No season"))?;
+
+    let data = season_results::Entity::find()
+        .filter(season_results::Column::SeasonId.eq(season.id))
+        .join(JoinType::InnerJoin, season_results::Relation::Users.def())
+        .select_only()
+        .column(season_results::Column::UserId)
+        .column(users::Column::Username)
+        .column(season_results::Column::Place)
+        .column(season_results::Column::TotalBalance)
+        .column(season_results::Column::ReturnPercent)
+        .order_by_asc(season_results::Column::Place)
+        .order_by_asc(season_results::Column::UserId)
+        .offset(query.offset)
+        .limit(query.limit.min(MAX_RESULTS_PAGE))
+        .into_model::<SeasonStanding>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<SeasonStanding>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(params(SeasonUserPath), tag = "User")]
+#[get("/api/v1/seasons/history/{user_id}")]
+pub async fn season_history(
+    state: web::Data<AppState>,
+    path: web::Path<SeasonUserPath>,
+) -> Result<HttpResponse, AppError> {
+    let user = users::Entity::find_by_id(path.user_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
+
+    let data = season_results::Entity::find()
+        .filter(season_results::Column::UserId.eq(user.id))
+        .join(JoinType::InnerJoin, season_results::Relation::Seasons.def())
+        .select_only()
+        .column(season_results::Column::SeasonId)
+        .column(seasons::Column::Name)
+        .column(seasons::Column::StartsAt)
+        .column(seasons::Column::EndsAt)
+        .column(season_results::Column::Place)
+        .column(season_results::Column::TotalBalance)
+        .column(season_results::Column::ReturnPercent)
+        .order_by_desc(seasons::Column::StartsAt)
+        .into_model::<SeasonHistoryEntry>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<SeasonHistoryEntry>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct SeasonPath {
+    pub season_id: i32,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct SeasonUserPath {
+    pub user_id: i32,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct SeasonResultsQuery {
+    /// At most 100, larger values are clamped
+    pub limit: u64,
+    #[serde(default)]
+    pub offset: u64,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct SeasonInfo {
+    pub id: i32,
+    pub name: String,
+    pub starts_at: DateTime,
+    pub ends_at: DateTime,
+    pub finished_at: Option<DateTime>,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct SeasonStanding {
+    pub user_id: i32,
+    pub username: String,
+    pub place: i32,
+    pub total_balance: Decimal,
+    pub return_percent: Decimal,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct SeasonHistoryEntry {
+    pub season_id: i32,
+    pub name: String,
+    pub starts_at: DateTime,
+    pub ends_at: DateTime,
+    pub place: i32,
+    pub total_balance: Decimal,
+    pub return_percent: Decimal,
+}
@@ -1,83 +1,37 @@
-use sea_orm::ColumnTrait;
-use sea_orm::QueryFilter;
-use crate::traits::redis::PriceInfo;
+use crate::structs::leaderboard_structs::LeaderboardWindow;
+use crate::utils::app_error::AppError;
+use crate::utils::leaderboard::{leaderboard_page, LeaderboardEntry};
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{get, web, HttpResponse, Responder};
-use entity::users;
-use redis::AsyncCommands;
-use sea_orm::prelude::{Decimal, Expr};
-use sea_orm::{EntityTrait, FromQueryResult, QueryOrder, QuerySelect};
-use serde::{Deserialize, Serialize};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
+use serde::Deserialize;
 use utoipa::{IntoParams, ToSchema};
 
+pub(crate) const MAX_LEADERBOARD_PAGE: u64 = 100;
+
 #[utoipa::path(params(TopUsersQuery), tag = "User")]
 #[get("/api/v1/users/top")]
 pub async fn top_users(
     state: web::Data<AppState>,
     query: web::Query<TopUsersQuery>,
-) -> impl Responder {
-    let mut redis_conn = try_or_http_err!(state.cache.get_multiplexed_async_connection().await);
-
-    let assets_keys: Vec<String> = try_or_http_err!(redis_conn.keys("asset_price:*").await);
-    let mut asset_prices: Vec<(&str, PriceInfo)> = Vec::new();
-    let assets_keys_clone = assets_keys.clone();
-    for (i, key) in assets_keys.iter().enumerate() {
-        asset_prices.push((
-            assets_keys_clone[i]
-                .strip_prefix("asset_price:")
-                .unwrap_or_default(),
-            try_or_http_err!(redis_conn.hgetall::<String, PriceInfo>(key.clone()).await)));
-    }
-
-    if asset_prices.is_empty() {
-        return HttpResponse::InternalServerError().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("No asset prices found in Redis".to_string()),
-        });
-    }
-
-    let mut cases = String::new();
-    for (asset_id, price) in &asset_prices {
-        cases.push_str(&format!("WHEN user_balances.asset_id = {} THEN {} ", asset_id, price.price.unwrap_or_default()));
-    }
+) -> Result<HttpResponse, AppError> {
+    let limit = query.limit.min(MAX_LEADERBOARD_PAGE);
+    let data = leaderboard_page(&state.cache, query.window, query.offset, limit).await?;
 
-    let query_string = format!(
-        "ROUND(users.balance + COALESCE((SELECT SUM(user_balances.amount * (CASE {} ELSE 0 END)) FROM user_balances WHERE user_balances.user_id = users.id), 0), 3)",
-        cases
-    );
-
-    let data = try_or_http_err!(users::Entity::find()
-        .column(users::Column::Id)
-        .column(users::Column::Username)
-        .column_as(
-            Expr::cust(&query_string),
-            "total_balance",
-        )
-        .filter(users::Column::IsBot.eq(false))
-        .order_by_desc(Expr::cust("total_balance"))
-        .limit(query.limit)
-        .into_model::<TopUsers>()
-        .all(state.db.as_ref())
-        .await);
-
-    HttpResponse::Ok().json(CommonResponse::<Vec<TopUsers>> {
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<LeaderboardEntry>> {
         status: ResponseStatus::Ok,
         data,
         error: None,
-    })
-    
+        code: None,
+    }))
 }
 
 #[derive(Deserialize, ToSchema, IntoParams)]
 pub struct TopUsersQuery {
+    /// At most 100, larger values are clamped
     pub limit: u64,
-}
-
-#[derive(Serialize, FromQueryResult)]
-pub struct TopUsers {
-    pub id: i32,
-    pub username: String,
-    pub total_balance: Decimal,
+    #[serde(default)]
+    pub offset: u64,
+    #[serde(default)]
+    pub window: LeaderboardWindow,
 }
@@ -1,6 +1,7 @@
+use crate::utils::app_error::AppError;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
 use entity::{assets, user_balances, users};
 use sea_orm::prelude::{Decimal, Expr};
 use sea_orm::QueryFilter;
@@ -13,38 +14,29 @@ use utoipa::{IntoParams, ToSchema};
     tag="User"
 )]
 #[get("/api/v1/user/assets/{user_id}")]
-pub async fn user_assets(state: web::Data<AppState>, path: web::Path<AssetQuery>) -> impl Responder {
-    let user_data = try_or_http_err!(
-        users::Entity::find_by_id(path.user_id)
-            .one(state.db.as_ref())
-            .await
-    );
-    if let Some(user) = user_data {
-        let data = try_or_http_err!(
-            assets::Entity::find()
-                .left_join(user_balances::Entity)
-                .column_as(
-                    Expr::col((user_balances::Entity, user_balances::Column::Amount)).if_null(0),
-                    "amount"
-                )
-                .filter(user_balances::Column::UserId.eq(user.id))
-                .into_model::<AssetWithBalance>()
-                .all(state.db.as_ref())
-                .await
-        );
+pub async fn user_assets(state: web::Data<AppState>, path: web::Path<AssetQuery>) -> Result<HttpResponse, AppError> {
+    let user = users::Entity::find_by_id(path.user_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
 
-        return HttpResponse::Ok().json(CommonResponse::<Vec<AssetWithBalance>> {
-            status: ResponseStatus::Ok,
-            data,
-            error: None,
-        });
-    }
+    let data = assets::Entity::find()
+        .left_join(user_balances::Entity)
+        .column_as(
+            Expr::col((user_balances::Entity, user_balances::Column::Amount)).if_null(0),
+            "amount"
+        )
+        .filter(user_balances::Column::UserId.eq(user.id))
+        .into_model::<AssetWithBalance>()
+        .all(state.db.as_ref())
+        .await?;
 
-    HttpResponse::Ok().json(CommonResponse::<()> {
-        status: ResponseStatus::Error,
-        data: (),
-        error: Some("No user".into()),
-    })
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<AssetWithBalance>> {
+        status: ResponseStatus::Ok,
+        data,
+        error: None,
+        code: None,
+    }))
 }
 
 #[derive(Debug, FromQueryResult, Serialize)]
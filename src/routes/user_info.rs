@@ -1,38 +1,51 @@
+use crate::utils::achievements::achievement;
+use crate::utils::app_error::AppError;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
+use crate::AppState;
 use actix_web::web::Query;
-use actix_web::{get, web, HttpResponse, Responder};
-use entity::users;
+use actix_web::{get, web, HttpResponse};
+use entity::{user_achievements, users};
 use sea_orm::prelude::{DateTime, Decimal};
-use sea_orm::EntityTrait;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
 #[utoipa::path(params(UserIdQuery), tag = "User")]
 #[get("/api/v1/users/info")]
-pub async fn user_info(state: web::Data<AppState>, query: Query<UserIdQuery>) -> impl Responder {
-    match try_or_http_err!(
-        users::Entity::find_by_id(query.user_id)
-            .one(state.db.as_ref())
-            .await
-    ) {
-        Some(user) => HttpResponse::Ok().json(CommonResponse::<UserResponse> {
-            status: ResponseStatus::Ok,
-            data: UserResponse {
-                id: user.id,
-                username: user.username,
-                email: user.email,
-                balance: user.balance,
-                created_at: user.created_at,
-            },
-            error: None,
-        }),
-        None => HttpResponse::InternalServerError().json(CommonResponse::<()> {
-            status: ResponseStatus::Error,
-            data: (),
-            error: Some("No user".into()),
-        }),
-    }
+pub async fn user_info(state: web::Data<AppState>, query: Query<UserIdQuery>) -> Result<HttpResponse, AppError> {
+    let user = users::Entity::find_by_id(query.user_id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
+    let achievements = user_achievements::Entity::find()
+        .filter(user_achievements::Column::UserId.eq(user.id))
+        .order_by_asc(user_achievements::Column::AwardedAt)
+        .all(state.db.as_ref())
+        .await?
+        .into_iter()
+        .filter_map(|award| {
+            achievement(&award.code).map(|achievement| AwardedAchievement {
+                code: achievement.code,
+                title: achievement.title,
+                description: achievement.description,
+                awarded_at: award.awarded_at,
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<UserResponse> {
+        status: ResponseStatus::Ok,
+        data: UserResponse {
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            balance: user.balance,
+            created_at: user.created_at,
+            achievements,
+        },
+        error: None,
+        code: None,
+    }))
 }
 
 #[derive(Serialize)]
@@ -42,6 +55,15 @@ pub struct UserResponse {
     pub email: String,
     pub balance: Decimal,
     pub created_at: DateTime,
+    pub achievements: Vec<AwardedAchievement>,
+}
+
+#[derive(Serialize)]
+pub struct AwardedAchievement {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub awarded_at: DateTime,
 }
 
 #[derive(Deserialize, ToSchema, IntoParams)]
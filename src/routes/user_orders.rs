@@ -1,6 +1,7 @@
+use crate::utils::app_error::AppError;
 use crate::utils::response::{CommonResponse, ResponseStatus};
-use crate::{try_or_http_err, AppState};
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::AppState;
+use actix_web::{get, web, HttpResponse};
 use chrono::NaiveDateTime;
 use entity::{orders, users};
 use sea_orm::prelude::{Decimal, Expr};
@@ -15,56 +16,55 @@ pub async fn user_orders(
     state: web::Data<AppState>,
     path: web::Path<OrdersPath>,
     query: web::Query<OrderQuery>,
-) -> impl Responder {
-    let data = try_or_http_err!(
-        orders::Entity::find()
-            .left_join(users::Entity)
-            .column_as(
-                Expr::col((users::Entity, users::Column::Username)),
-                "username"
-            )
-            .filter({
-                let mut cond = Condition::all().add(orders::Column::AssetId.eq(path.asset_id));
-            
-                if let Some(status) = &query.status {
-                    match status {
-                        OrderStatus::Pending => {
-                            cond = cond.add(orders::Column::Status.eq("pending"));
-                        }
-                        OrderStatus::Done => {
-                            cond = cond.add(orders::Column::Status.eq("done"));
-                        }
-                        OrderStatus::Cancel => {
-                            cond = cond.add(orders::Column::Status.eq("cancel"));
-                        }
+) -> Result<HttpResponse, AppError> {
+    let data = orders::Entity::find()
+        .left_join(users::Entity)
+        .column_as(
+            Expr::col((users::Entity, users::Column::Username)),
+            "username"
+        )
+        .filter({
+            let mut cond = Condition::all().add(orders::Column::AssetId.eq(path.asset_id));
+        
+            if let Some(status) = &query.status {
+                match status {
+                    OrderStatus::Pending => {
+                        cond = cond.add(orders::Column::Status.eq("pending"));
+                    }
+                    OrderStatus::Done => {
+                        cond = cond.add(orders::Column::Status.eq("done"));
+                    }
+                    OrderStatus::Cancel => {
+                        cond = cond.add(orders::Column::Status.eq("cancel"));
                     }
                 }
+            }
 
-                if let Some(order_type) = &query.order_type {
-                    match order_type {
-                        OrderType::Buy => {
-                            cond = cond.add(orders::Column::OrderType.eq("buy"));
-                        }
-                        OrderType::Sell => {
-                            cond = cond.add(orders::Column::OrderType.eq("sell"));
-                        }
+            if let Some(order_type) = &query.order_type {
+                match order_type {
+                    OrderType::Buy => {
+                        cond = cond.add(orders::Column::OrderType.eq("buy"));
+                    }
+                    OrderType::Sell => {
+                        cond = cond.add(orders::Column::OrderType.eq("sell"));
                     }
                 }
+            }
 
-                cond
-            })
-            .limit(query.limit)
-            .offset(query.offset)
-            .into_model::<OrderResponse>()
-            .all(state.db.as_ref())
-            .await
-    );
+            cond
+        })
+        .limit(query.limit)
+        .offset(query.offset)
+        .into_model::<OrderResponse>()
+        .all(state.db.as_ref())
+        .await?;
 
-    HttpResponse::Ok().json(CommonResponse::<Vec<OrderResponse>> {
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<OrderResponse>> {
         status: ResponseStatus::Ok,
         data,
         error: None,
-    })
+        code: None,
+    }))
 }
 
 #[utoipa::path(params(OrderQuery, OrdersPathByUser), tag = "User")]
@@ -73,58 +73,57 @@ pub async fn user_orders_by_user(
     state: web::Data<AppState>,
     path: web::Path<OrdersPathByUser>,
     query: web::Query<OrderQuery>,
-) -> impl Responder {
-    let data = try_or_http_err!(
-        orders::Entity::find()
-            .left_join(users::Entity)
-            .column_as(
-                Expr::col((users::Entity, users::Column::Username)),
-                "username"
-            )
-            .filter({
-                let mut cond = Condition::all()
-                    .add(orders::Column::AssetId.eq(path.asset_id))
-                    .add(users::Column::Id.eq(path.user_id));
+) -> Result<HttpResponse, AppError> {
+    let data = orders::Entity::find()
+        .left_join(users::Entity)
+        .column_as(
+            Expr::col((users::Entity, users::Column::Username)),
+            "username"
+        )
+        .filter({
+            let mut cond = Condition::all()
+                .add(orders::Column::AssetId.eq(path.asset_id))
+                .add(users::Column::Id.eq(path.user_id));
 
-                if let Some(status) = &query.status {
-                    match status {
-                        OrderStatus::Pending => {
-                            cond = cond.add(orders::Column::Status.eq("pending"));
-                        }
-                        OrderStatus::Done => {
-                            cond = cond.add(orders::Column::Status.eq("done"));
-                        }
-                        OrderStatus::Cancel => {
-                            cond = cond.add(orders::Column::Status.eq("cancel"));
-                        }
+            if let Some(status) = &query.status {
+                match status {
+                    OrderStatus::Pending => {
+                        cond = cond.add(orders::Column::Status.eq("pending"));
+                    }
+                    OrderStatus::Done => {
+                        cond = cond.add(orders::Column::Status.eq("done"));
+                    }
+                    OrderStatus::Cancel => {
+                        cond = cond.add(orders::Column::Status.eq("cancel"));
                     }
                 }
+            }
 
-                if let Some(order_type) = &query.order_type {
-                    match order_type {
-                        OrderType::Buy => {
-                            cond = cond.add(orders::Column::OrderType.eq("buy"));
-                        }
-                        OrderType::Sell => {
-                            cond = cond.add(orders::Column::OrderType.eq("sell"));
-                        }
+            if let Some(order_type) = &query.order_type {
+                match order_type {
+                    OrderType::Buy => {
+                        cond = cond.add(orders::Column::OrderType.eq("buy"));
+                    }
+                    OrderType::Sell => {
+                        cond = cond.add(orders::Column::OrderType.eq("sell"));
                     }
                 }
+            }
 
-                cond
-            })
-            .limit(query.limit)
-            .offset(query.offset)
-            .into_model::<OrderResponse>()
-            .all(state.db.as_ref())
-            .await
-    );
+            cond
+        })
+        .limit(query.limit)
+        .offset(query.offset)
+        .into_model::<OrderResponse>()
+        .all(state.db.as_ref())
+        .await?;
 
-    HttpResponse::Ok().json(CommonResponse::<Vec<OrderResponse>> {
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<OrderResponse>> {
         status: ResponseStatus::Ok,
         data,
         error: None,
-    })
+        code: None,
+    }))
 }
 
 #[derive(Serialize, FromQueryResult)]
@@ -0,0 +1,247 @@
+use crate::bots::script::compile_script;
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::AccessToken;
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::AppState;
+use actix_web::{get, post, web, HttpResponse};
+use chrono::Utc;
+use entity::{script_logs, user_scripts};
+use sea_orm::prelude::DateTime;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DbConn, EntityTrait, FromQueryResult, IntoActiveModel,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const MAX_SCRIPTS_PER_USER: u64 = 5;
+const MAX_SCRIPT_LOGS_PAGE: u64 = 200;
+
+#[utoipa::path(
+    request_body = ScriptInput,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/scripts/upload")]
+pub async fn script_upload(
+    state: web::Data<AppState>,
+    input: web::Json<ScriptInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    compile_script(&input.source)?;
+
+    let scripts = user_scripts::Entity::find()
+        .filter(user_scripts::Column::UserId.eq(token.claims.sub))
+        .count(state.db.as_ref())
+        .await?;
+    if scripts >= MAX_SCRIPTS_PER_USER {
+        return Err(AppError::Validation(format!(
+            "No more than {MAX_SCRIPTS_PER_USER} scripts per user"
+        )));
+    }
+
+    let script = user_scripts::ActiveModel {
+        user_id: Set(token.claims.sub),
+        name: Set(input.name),
+        source: Set(input.source),
+        enabled: Set(input.enabled.unwrap_or(false)),
+        ..Default::default()
+    }
+    .insert(state.db.as_ref())
+    .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<ScriptCreateResponse> {
+        status: ResponseStatus::Ok,
+        data: ScriptCreateResponse {
+            script_id: script.id,
+        },
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    request_body = ScriptInput,
+    params(ScriptPath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/scripts/{script_id}/update")]
+pub async fn script_update(
+    state: web::Data<AppState>,
+    path: web::Path<ScriptPath>,
+    input: web::Json<ScriptInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let input = input.into_inner();
+    compile_script(&input.source)?;
+    let script = __find_script(state.db.as_ref(), token.claims.sub, path.script_id).await?;
+
+    let mut active_script = script.into_active_model();
+    active_script.name = Set(input.name);
+    active_script.source = Set(input.source);
+    if let Some(enabled) = input.enabled {
+        active_script.enabled = Set(enabled);
+    }
+    active_script.updated_at = Set(Utc::now().naive_utc());
+    active_script.update(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    request_body = ScriptEnabledInput,
+    params(ScriptPath),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[post("/api/v1/scripts/{script_id}/enabled")]
+pub async fn script_enabled(
+    state: web::Data<AppState>,
+    path: web::Path<ScriptPath>,
+    input: web::Json<ScriptEnabledInput>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let script = __find_script(state.db.as_ref(), token.claims.sub, path.script_id).await?;
+
+    let mut active_script = script.into_active_model();
+    active_script.enabled = Set(input.enabled);
+    active_script.update(state.db.as_ref()).await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<()> {
+        status: ResponseStatus::Ok,
+        data: (),
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/scripts")]
+pub async fn user_scripts_list(
+    state: web::Data<AppState>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let scripts = user_scripts::Entity::find()
+        .filter(user_scripts::Column::UserId.eq(token.claims.sub))
+        .order_by_asc(user_scripts::Column::Id)
+        .into_model::<ScriptResponse>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<ScriptResponse>> {
+        status: ResponseStatus::Ok,
+        data: scripts,
+        error: None,
+        code: None,
+    }))
+}
+
+#[utoipa::path(
+    params(ScriptPath, ScriptLogsQuery),
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/scripts/{script_id}/logs")]
+pub async fn script_logs_list(
+    state: web::Data<AppState>,
+    path: web::Path<ScriptPath>,
+    query: web::Query<ScriptLogsQuery>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let script = __find_script(state.db.as_ref(), token.claims.sub, path.script_id).await?;
+    let logs = script_logs::Entity::find()
+        .filter(script_logs::Column::ScriptId.eq(script.id))
+        .order_by_desc(script_logs::Column::Id)
+        .limit(query.limit.min(MAX_SCRIPT_LOGS_PAGE))
+        .offset(query.offset)
+        .into_model::<ScriptLogResponse>()
+        .all(state.db.as_ref())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(CommonResponse::<Vec<ScriptLogResponse>> {
+        status: ResponseStatus::Ok,
+        data: logs,
+        error: None,
+        code: None,
+    }))
+}
+
+async fn __find_script(
+    db: &DbConn,
+    user_id: i32,
+    script_id: i32,
+) -> Result<user_scripts::Model, AppError> {
+    user_scripts::Entity::find_by_id(script_id)
+        .one(db)
+        .await?
+        .filter(|script| script.user_id == user_id)
+        .ok_or(AppError::NotFound("No script with this ID"))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ScriptInput {
+    name: String,
+    /// Rhai source defining `fn on_tick(ctx)`
+    source: String,
+    enabled: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ScriptEnabledInput {
+    enabled: bool,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct ScriptPath {
+    pub script_id: i32,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct ScriptLogsQuery {
+    /// At most 200, larger values are clamped
+    pub limit: u64,
+    pub offset: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct ScriptCreateResponse {
+    script_id: i32,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct ScriptResponse {
+    pub id: i32,
+    pub name: String,
+    pub source: String,
+    pub enabled: bool,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Serialize, FromQueryResult)]
+pub struct ScriptLogResponse {
+    pub id: i32,
+    pub level: String,
+    pub message: String,
+    pub created_at: DateTime,
+}
@@ -0,0 +1,405 @@
+use crate::structs::ws_structs::{
+    ClientEnvelope, ClientFrame, ServerEnvelope, ServerFrame, Topic, PROTOCOL_VERSION,
+};
+use crate::traits::heartbeat::{Heartbeat, HEARTBEAT_INTERVAL, IDLE_TIMEOUT};
+use crate::utils::app_error::AppError;
+use crate::utils::chat_rooms::check_room_readable;
+use crate::utils::jwt::AccessToken;
+use crate::utils::moderation::check_chat_allowed;
+use crate::utils::pubsub::missed_events;
+use crate::{AppState, STREAM_SUBSCRIPTIONS};
+use actix::{Actor, AsyncContext, Handler, Message as ActixMessage, StreamHandler};
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use entity::users;
+use sea_orm::EntityTrait;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Every topic costs an entry in the shared subscription map, this bounds one session
+const MAX_TOPICS: usize = 100;
+
+#[utoipa::path(
+    request_body = ClientEnvelope,
+    tag="User",
+    security(
+        ("bearer_token" = [])
+    )
+)]
+#[get("/api/v1/ws")]
+pub async fn stream_ws(
+    req: HttpRequest,
+    stream: web::Payload,
+    state: web::Data<AppState>,
+    token: AccessToken,
+) -> Result<HttpResponse, AppError> {
+    let user = users::Entity::find_by_id(token.claims.sub)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::Unauthorized("No user"))?;
+
+    let session = StreamSession {
+        user_id: user.id,
+        state: state.into_inner(),
+        topics: HashSet::new(),
+        last_seen: Instant::now(),
+    };
+    ws::start(session, &req, stream).map_err(AppError::internal)
+}
+
+/// Writes a relayed event to the sessions of this instance subscribed to the topic.
+pub(crate) async fn deliver_event(topic: &str, message: String, skip_user_ids: Vec<i32>) {
+    let subscriptions = STREAM_SUBSCRIPTIONS.read().await;
+    let Some(sessions) = subscriptions.get(topic) else {
+        return;
+    };
+    if skip_user_ids.is_empty() {
+        for addr in sessions {
+            addr.do_send(StreamFrame {
+                message: message.clone(),
+            });
+        }
+        return;
+    }
+    let skip_user_ids = Arc::new(skip_user_ids);
+    for addr in sessions {
+        addr.do_send(TopicFrame {
+            message: message.clone(),
+            skip_user_ids: Arc::clone(&skip_user_ids),
+        });
+    }
+}
+
+/// Unsubscribes the user's sessions of this instance from the topic.
+pub(crate) async fn drop_subscriber(topic: &str, user_id: i32) {
+    let subscriptions = STREAM_SUBSCRIPTIONS.read().await;
+    let Some(sessions) = subscriptions.get(topic) else {
+        return;
+    };
+    for addr in sessions {
+        addr.do_send(LeaveTopic {
+            key: topic.to_string(),
+            user_id,
+        });
+    }
+}
+
+/// Serialized frame for the client.
+#[derive(ActixMessage)]
+#[rtype(result = "()")]
+struct StreamFrame {
+    message: String,
+}
+
+/// Serialized frame for every subscriber except the sessions of `skip_user_ids`.
+#[derive(ActixMessage)]
+#[rtype(result = "()")]
+struct TopicFrame {
+    message: String,
+    skip_user_ids: Arc<Vec<i32>>,
+}
+
+/// Drops a topic from the session when it belongs to `user_id`.
+#[derive(ActixMessage)]
+#[rtype(result = "()")]
+struct LeaveTopic {
+    key: String,
+    user_id: i32,
+}
+
+impl StreamFrame {
+    fn new(frame: ServerFrame) -> Self {
+        Self {
+            message: serde_json::to_string(&ServerEnvelope::from(frame)).unwrap_or_default(),
+        }
+    }
+
+    fn error(id: Option<String>, err: AppError) -> Self {
+        Self::new(ServerFrame::Error {
+            id,
+            code: err.code(),
+            error: err.to_string(),
+        })
+    }
+}
+
+pub(crate) struct StreamSession {
+    user_id: i32,
+    state: Arc<AppState>,
+    /// Keys of the subscribed topics
+    topics: HashSet<String>,
+    last_seen: Instant,
+}
+
+impl Heartbeat for StreamSession {
+    fn last_seen(&mut self) -> &mut Instant {
+        &mut self.last_seen
+    }
+}
+
+impl StreamSession {
+    fn __parse_topics(&self, names: &[String]) -> Result<Vec<Topic>, AppError> {
+        names
+            .iter()
+            .map(|name| {
+                Topic::parse(name, self.user_id)
+                    .ok_or_else(|| AppError::Validation(format!("Unknown topic {name}")))
+            })
+            .collect()
+    }
+
+    fn __subscribe(
+        &mut self,
+        id: Option<String>,
+        names: Vec<String>,
+        last_seq: HashMap<String, u64>,
+        ctx: &mut ws::WebsocketContext<Self>,
+    ) {
+        let topics = match self.__parse_topics(&names) {
+            Ok(topics) => topics,
+            Err(err) => return ctx.text(StreamFrame::error(id, err).message),
+        };
+        let new_topics = topics
+            .iter()
+            .map(Topic::key)
+            .filter(|key| !self.topics.contains(key))
+            .collect::<HashSet<_>>();
+        if self.topics.len() + new_topics.len() > MAX_TOPICS {
+            let err = AppError::Validation(format!("At most {MAX_TOPICS} topics per connection"));
+            return ctx.text(StreamFrame::error(id, err).message);
+        }
+        self.topics.extend(new_topics.iter().cloned());
+
+        let addr = ctx.address();
+        let user_id = self.user_id;
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            // Only chat topics are closed to users banned from chat
+            let has_chat = topics
+                .iter()
+                .any(|topic| matches!(topic, Topic::Chat(_) | Topic::Room(_)));
+            let mut allowed = if has_chat {
+                check_chat_allowed(state.db.as_ref(), user_id, false).await
+            } else {
+                Ok(())
+            };
+            for topic in &topics {
+                let Topic::Room(room_id) = topic else {
+                    continue;
+                };
+                if allowed.is_err() {
+                    break;
+                }
+                allowed = check_room_readable(state.db.as_ref(), *room_id, user_id).await;
+            }
+            if let Err(err) = allowed {
+                for key in new_topics {
+                    addr.do_send(LeaveTopic { key, user_id });
+                }
+                addr.do_send(StreamFrame::error(id, err));
+                return;
+            }
+            {
+                let mut subscriptions = STREAM_SUBSCRIPTIONS.write().await;
+                for topic in &topics {
+                    subscriptions.entry(topic.key()).or_default().insert(addr.clone());
+                }
+            }
+            // Registered before the replay, so nothing falls in between. Clients drop
+            // frames with a `seq` they already have.
+            for topic in &topics {
+                let Some(last_seq) = last_seq.get(&topic.name()) else {
+                    continue;
+                };
+                match missed_events(&state.cache, topic, *last_seq).await {
+                    Ok(frames) => {
+                        for message in frames {
+                            addr.do_send(StreamFrame { message });
+                        }
+                    }
+                    Err(err) => {
+                        addr.do_send(StreamFrame::error(id.clone(), AppError::from(err)));
+                    }
+                }
+            }
+            addr.do_send(StreamFrame::new(ServerFrame::Ack { id }));
+        });
+    }
+
+    fn __unsubscribe(&mut self, id: Option<String>, names: Vec<String>, ctx: &mut ws::WebsocketContext<Self>) {
+        let topics = match self.__parse_topics(&names) {
+            Ok(topics) => topics,
+            Err(err) => return ctx.text(StreamFrame::error(id, err).message),
+        };
+        let keys: Vec<String> = topics.iter().map(Topic::key).collect();
+        for key in &keys {
+            self.topics.remove(key);
+        }
+
+        let addr = ctx.address();
+        tokio::spawn(async move {
+            __remove_subscriptions(&addr, keys).await;
+            addr.do_send(StreamFrame::new(ServerFrame::Ack { id }));
+        });
+    }
+
+    /// Runs a client request in the background and answers with an ack or an error frame.
+    fn __reply<F>(&self, id: Option<String>, ctx: &mut ws::WebsocketContext<Self>, request: F)
+    where
+        F: Future<Output = Result<(), AppError>> + Send + 'static,
+    {
+        let addr = ctx.address();
+        tokio::spawn(async move {
+            let frame = match request.await {
+                Ok(()) => StreamFrame::new(ServerFrame::Ack { id }),
+                Err(err) => StreamFrame::error(id, err),
+            };
+            addr.do_send(frame);
+        });
+    }
+}
+
+async fn __remove_subscriptions(addr: &actix::Addr<StreamSession>, keys: Vec<String>) {
+    let mut subscriptions = STREAM_SUBSCRIPTIONS.write().await;
+    for key in keys {
+        if let Some(sessions) = subscriptions.get_mut(&key) {
+            sessions.remove(addr);
+            if sessions.is_empty() {
+                subscriptions.remove(&key);
+            }
+        }
+    }
+}
+
+impl Actor for StreamSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.start_heartbeat(ctx);
+        let welcome = StreamFrame::new(ServerFrame::Welcome {
+            heartbeat_interval: HEARTBEAT_INTERVAL.as_secs(),
+            idle_timeout: IDLE_TIMEOUT.as_secs(),
+        });
+        ctx.text(welcome.message);
+    }
+
+    fn stopping(&mut self, ctx: &mut Self::Context) -> actix::Running {
+        let addr = ctx.address();
+        let keys: Vec<String> = self.topics.drain().collect();
+        tokio::spawn(async move {
+            __remove_subscriptions(&addr, keys).await;
+        });
+        actix::Running::Stop
+    }
+}
+
+impl Handler<StreamFrame> for StreamSession {
+    type Result = ();
+    fn handle(&mut self, msg: StreamFrame, ctx: &mut Self::Context) {
+        ctx.text(msg.message);
+    }
+}
+
+impl Handler<TopicFrame> for StreamSession {
+    type Result = ();
+    fn handle(&mut self, msg: TopicFrame, ctx: &mut Self::Context) {
+        if !msg.skip_user_ids.contains(&self.user_id) {
+            ctx.text(msg.message);
+        }
+    }
+}
+
+impl Handler<LeaveTopic> for StreamSession {
+    type Result = ();
+    fn handle(&mut self, msg: LeaveTopic, ctx: &mut Self::Context) {
+        if msg.user_id != self.user_id || !self.topics.remove(&msg.key) {
+            return;
+        }
+        let addr = ctx.address();
+        tokio::spawn(async move {
+            __remove_subscriptions(&addr, vec![msg.key]).await;
+        });
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for StreamSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        let Some(text) = self.control_frame(msg, ctx) else {
+            return;
+        };
+        let envelope = match serde_json::from_str::<ClientEnvelope>(&text) {
+            Ok(envelope) => envelope,
+            Err(err) => {
+                let error = AppError::Validation(format!("Malformed frame: {err}"));
+                return ctx.text(StreamFrame::error(None, error).message);
+            }
+        };
+        if envelope.v != PROTOCOL_VERSION {
+            let error = AppError::Validation(format!(
+                "Unsupported protocol version {}, the server speaks {PROTOCOL_VERSION}",
+                envelope.v
+            ));
+            return ctx.text(StreamFrame::error(None, error).message);
+        }
+
+        match envelope.frame {
+            ClientFrame::Subscribe {
+                id,
+                topics,
+                last_seq,
+            } => self.__subscribe(id, topics, last_seq, ctx),
+            ClientFrame::Unsubscribe { id, topics } => self.__unsubscribe(id, topics, ctx),
+            ClientFrame::Ping { id } => ctx.text(StreamFrame::new(ServerFrame::Pong { id }).message),
+            ClientFrame::ChatSend {
+                id,
+                recipient_id,
+                league_id,
+                room_id,
+                text,
+                attachment,
+            } => {
+                let state = Arc::clone(&self.state);
+                let from_id = self.user_id;
+                self.__reply(id, ctx, async move {
+                    state
+                        .send_chat(from_id, recipient_id, league_id, room_id, text, attachment)
+                        .await
+                });
+            }
+            ClientFrame::ChatEdit {
+                id,
+                message_id,
+                text,
+            } => {
+                let state = Arc::clone(&self.state);
+                let from_id = self.user_id;
+                self.__reply(id, ctx, async move { state.edit_message(from_id, message_id, text).await });
+            }
+            ClientFrame::ChatDelete { id, message_id } => {
+                let state = Arc::clone(&self.state);
+                let from_id = self.user_id;
+                self.__reply(id, ctx, async move { state.delete_message(from_id, message_id).await });
+            }
+            ClientFrame::ChatTyping {
+                id,
+                recipient_id,
+                typing,
+            } => {
+                let state = Arc::clone(&self.state);
+                let from_id = self.user_id;
+                self.__reply(id, ctx, async move { state.send_typing(from_id, recipient_id, typing).await });
+            }
+            ClientFrame::ChatRead {
+                id,
+                recipient_id,
+                message_id,
+            } => {
+                let state = Arc::clone(&self.state);
+                let reader_id = self.user_id;
+                self.__reply(id, ctx, async move { state.mark_read(reader_id, recipient_id, message_id).await });
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// Price of `asset_id` rises to `threshold` or higher
+    Above,
+    /// Price of `asset_id` falls to `threshold` or lower
+    Below,
+    /// Price of `asset_id` moves by `threshold` percent either way within `window_minutes`
+    Change,
+    /// Limit order `order_id` is filled
+    OrderFilled,
+}
+
+impl AlertKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Above => "above",
+            Self::Below => "below",
+            Self::Change => "change",
+            Self::OrderFilled => "order_filled",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "above" => Some(Self::Above),
+            "below" => Some(Self::Below),
+            "change" => Some(Self::Change),
+            "order_filled" => Some(Self::OrderFilled),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoomKind {
+    /// Private conversation, members are added by admins
+    Group,
+    /// Public discussion of one asset, anyone can join
+    Asset,
+}
+
+impl RoomKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Group => "group",
+            Self::Asset => "asset",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoomRole {
+    /// Manages members and other admins
+    Admin,
+    Member,
+}
+
+impl RoomRole {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Admin => "admin",
+            Self::Member => "member",
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::NaiveDateTime;
+use sea_orm::prelude::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What the sender attaches to a private message. The server checks it and stores an
+/// [`Attachment`] card in its place.
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AttachmentInput {
+    /// One of the sender's trades
+    Trade { trade_id: i32 },
+    /// One of the sender's orders
+    Order { order_id: i32 },
+    Asset { asset_id: i32 },
+}
+
+/// Card the client renders under the message. Taken when the message is sent, later
+/// fills or price moves don't change it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Attachment {
+    Trade {
+        trade_id: i32,
+        asset_id: i32,
+        symbol: String,
+        trade_type: String,
+        /// Unit price
+        price: Decimal,
+        amount: Decimal,
+        created_at: NaiveDateTime,
+    },
+    Order {
+        order_id: i32,
+        asset_id: i32,
+        symbol: String,
+        order_type: String,
+        /// Unit price
+        price: Decimal,
+        /// Amount still resting in the book
+        amount: Decimal,
+        status: String,
+    },
+    Asset {
+        asset_id: i32,
+        symbol: String,
+        name: String,
+        /// `null` when the price is not known yet
+        price: Option<Decimal>,
+    },
+}
@@ -0,0 +1,35 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaderboardWindow {
+    /// Return since registration, ranks the same way as the total balance
+    #[default]
+    All,
+    Day,
+    Week,
+    Month,
+}
+
+impl LeaderboardWindow {
+    pub const ALL: [LeaderboardWindow; 4] = [Self::All, Self::Day, Self::Week, Self::Month];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        match self {
+            Self::All => None,
+            Self::Day => Some(chrono::Duration::days(1)),
+            Self::Week => Some(chrono::Duration::weeks(1)),
+            Self::Month => Some(chrono::Duration::days(30)),
+        }
+    }
+}
@@ -1 +1,8 @@
-pub mod order_structs;
\ No newline at end of file
+pub mod order_structs;
+pub mod leaderboard_structs;
+pub mod alert_structs;
+pub mod notification_structs;
+pub mod ws_structs;
+pub mod chat_room_structs;
+pub mod moderation_structs;
+pub mod chat_structs;
\ No newline at end of file
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Which chat the reported message was sent to.
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageType {
+    Private,
+    League,
+    Room,
+}
+
+impl MessageType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::League => "league",
+            Self::Room => "room",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportStatus {
+    /// Waiting in the moderation queue
+    Open,
+    /// A moderator acted on the report
+    Resolved,
+    /// A moderator found nothing wrong
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Resolved => "resolved",
+            Self::Dismissed => "dismissed",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SanctionKind {
+    /// Can't send chat messages
+    Mute,
+    /// Can't send chat messages or open the chat socket
+    Ban,
+}
+
+impl SanctionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mute => "mute",
+            Self::Ban => "ban",
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// Someone traded against the user's resting order, fully or partially
+    OrderFilled,
+    /// A pending order was closed by the game, e.g. at the end of a season
+    OrderExpired,
+    /// New market event
+    Event,
+    /// Price or order alert triggered
+    Alert,
+    Achievement,
+}
+
+impl NotificationKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OrderFilled => "order_filled",
+            Self::OrderExpired => "order_expired",
+            Self::Event => "event",
+            Self::Alert => "alert",
+            Self::Achievement => "achievement",
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use serde::Deserialize;
 use utoipa::ToSchema;
 
-#[derive(Deserialize, ToSchema)]
+#[derive(Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderType {
     Buy,
@@ -15,3 +15,13 @@ pub enum OrderStatus {
     Done,
     Cancel,
 }
+
+#[derive(Deserialize, ToSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MarketMode {
+    /// Fill everything at the simulated house price
+    #[default]
+    House,
+    /// Take resting limit orders first, the house price covers what the book can't
+    Book,
+}
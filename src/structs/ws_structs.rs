@@ -0,0 +1,222 @@
+use crate::structs::chat_structs::AttachmentInput;
+use crate::utils::app_error::ErrorCode;
+use chrono::NaiveDateTime;
+use entity::orders;
+use sea_orm::prelude::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+pub const PROTOCOL_VERSION: u8 = 1;
+
+fn __protocol_version() -> u8 {
+    PROTOCOL_VERSION
+}
+
+/// Everything a client sends over `/api/v1/ws`. `v` defaults to the current version.
+#[derive(Deserialize, ToSchema)]
+pub struct ClientEnvelope {
+    #[serde(default = "__protocol_version")]
+    pub v: u8,
+    #[serde(flatten)]
+    pub frame: ClientFrame,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientFrame {
+    /// Topics are `prices`, `book:{asset_id}`, `trades:{asset_id}`, `chat`, `notifications`.
+    /// `last_seq` maps a topic to the last sequence number the client saw, missed events
+    /// are replayed before the acknowledgement.
+    Subscribe {
+        id: Option<String>,
+        topics: Vec<String>,
+        #[serde(default)]
+        last_seq: HashMap<String, u64>,
+    },
+    Unsubscribe {
+        id: Option<String>,
+        topics: Vec<String>,
+    },
+    Ping {
+        id: Option<String>,
+    },
+    /// Private message with `recipient_id`, league chat message with `league_id` or room
+    /// message with `room_id`
+    ChatSend {
+        id: Option<String>,
+        recipient_id: Option<i32>,
+        league_id: Option<i32>,
+        room_id: Option<i32>,
+        #[serde(default)]
+        text: String,
+        /// Trade, order or asset card, private messages only
+        attachment: Option<AttachmentInput>,
+    },
+    /// Changes the text of an own private message within the edit window
+    ChatEdit {
+        id: Option<String>,
+        message_id: i32,
+        text: String,
+    },
+    /// Deletes an own private message within the edit window
+    ChatDelete {
+        id: Option<String>,
+        message_id: i32,
+    },
+    ChatTyping {
+        id: Option<String>,
+        recipient_id: i32,
+        typing: bool,
+    },
+    /// Marks the chat with `recipient_id` as read up to `message_id`
+    ChatRead {
+        id: Option<String>,
+        recipient_id: i32,
+        message_id: i32,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerFrame {
+    Welcome {
+        heartbeat_interval: u64,
+        idle_timeout: u64,
+    },
+    Ack {
+        id: Option<String>,
+    },
+    Pong {
+        id: Option<String>,
+    },
+    Error {
+        id: Option<String>,
+        code: ErrorCode,
+        error: String,
+    },
+    /// Events after `last_seq` are no longer kept, the client should reload the topic's
+    /// state over REST
+    Resync {
+        topic: String,
+        seq: u64,
+    },
+    Event {
+        topic: String,
+        seq: u64,
+        data: Value,
+    },
+    /// Short-lived event like a typing indicator, not numbered and never replayed
+    Signal {
+        topic: String,
+        data: Value,
+    },
+}
+
+/// Server frame tagged with the protocol version.
+#[derive(Serialize)]
+pub struct ServerEnvelope {
+    pub v: u8,
+    #[serde(flatten)]
+    pub frame: ServerFrame,
+}
+
+impl From<ServerFrame> for ServerEnvelope {
+    fn from(frame: ServerFrame) -> Self {
+        Self {
+            v: PROTOCOL_VERSION,
+            frame,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Topic {
+    Prices,
+    Book(i32),
+    Trades(i32),
+    /// Chat of one user
+    Chat(i32),
+    /// Notifications of one user
+    Notifications(i32),
+    /// Messages of one chat room
+    Room(i32),
+}
+
+impl Topic {
+    /// Reads a topic name sent by `user_id`. Personal topics always point to the user's own feed.
+    pub fn parse(name: &str, user_id: i32) -> Option<Self> {
+        match name.split_once(':') {
+            None => match name {
+                "prices" => Some(Self::Prices),
+                "chat" => Some(Self::Chat(user_id)),
+                "notifications" => Some(Self::Notifications(user_id)),
+                _ => None,
+            },
+            Some(("book", asset_id)) => asset_id.parse().ok().map(Self::Book),
+            Some(("trades", asset_id)) => asset_id.parse().ok().map(Self::Trades),
+            Some(("room", room_id)) => room_id.parse().ok().map(Self::Room),
+            Some(_) => None,
+        }
+    }
+
+    /// Name the client subscribes with.
+    pub fn name(&self) -> String {
+        match self {
+            Self::Prices => "prices".to_string(),
+            Self::Book(asset_id) => format!("book:{asset_id}"),
+            Self::Trades(asset_id) => format!("trades:{asset_id}"),
+            Self::Chat(_) => "chat".to_string(),
+            Self::Notifications(_) => "notifications".to_string(),
+            Self::Room(room_id) => format!("room:{room_id}"),
+        }
+    }
+
+    /// Key the topic is stored and routed under, unique across users.
+    pub fn key(&self) -> String {
+        match self {
+            Self::Chat(user_id) => format!("chat:{user_id}"),
+            Self::Notifications(user_id) => format!("notifications:{user_id}"),
+            _ => self.name(),
+        }
+    }
+}
+
+/// `book:{asset_id}` event, the new state of an order that was placed, filled or closed.
+#[derive(Serialize)]
+pub struct BookEvent {
+    pub order_id: i32,
+    pub order_type: String,
+    /// Unit price
+    pub price: Decimal,
+    /// Amount still resting in the book
+    pub amount: Decimal,
+    pub status: String,
+}
+
+impl From<&orders::Model> for BookEvent {
+    fn from(order: &orders::Model) -> Self {
+        Self {
+            order_id: order.id,
+            order_type: order.order_type.clone(),
+            price: if order.amount.is_zero() {
+                Decimal::ZERO
+            } else {
+                (order.price / order.amount).round_dp(3)
+            },
+            amount: order.amount,
+            status: order.status.clone(),
+        }
+    }
+}
+
+/// `trades:{asset_id}` event.
+#[derive(Serialize)]
+pub struct TradeEvent {
+    /// Side of the party that took the liquidity
+    pub trade_type: String,
+    pub price: Decimal,
+    pub amount: Decimal,
+    pub created_at: NaiveDateTime,
+}
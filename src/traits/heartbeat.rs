@@ -0,0 +1,47 @@
+use actix::{Actor, ActorContext, AsyncContext};
+use actix_web_actors::ws;
+use std::time::{Duration, Instant};
+
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Sockets that sent nothing, not even a pong, for this long are closed.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// WebSocket actors that ping their client and drop it once it goes quiet.
+pub trait Heartbeat: Actor<Context = ws::WebsocketContext<Self>> {
+    /// Time of the last frame received from the client.
+    fn last_seen(&mut self) -> &mut Instant;
+
+    fn start_heartbeat(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        ctx.run_interval(HEARTBEAT_INTERVAL, |actor, ctx| {
+            if actor.last_seen().elapsed() > IDLE_TIMEOUT {
+                ctx.stop();
+            } else {
+                ctx.ping(b"");
+            }
+        });
+    }
+
+    /// Handles the control frames every socket shares. Returns the text of a text frame,
+    /// the only kind left to the actor itself.
+    fn control_frame(
+        &mut self,
+        msg: Result<ws::Message, ws::ProtocolError>,
+        ctx: &mut ws::WebsocketContext<Self>,
+    ) -> Option<String> {
+        let Ok(msg) = msg else {
+            ctx.stop();
+            return None;
+        };
+        *self.last_seen() = Instant::now();
+        match msg {
+            ws::Message::Ping(msg) => ctx.pong(&msg),
+            ws::Message::Close(reason) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            ws::Message::Text(text) => return Some(text.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
@@ -1 +1,2 @@
-pub mod redis;
\ No newline at end of file
+pub mod redis;
+pub mod heartbeat;
\ No newline at end of file
@@ -0,0 +1,260 @@
+use crate::structs::leaderboard_structs::LeaderboardWindow;
+use crate::structs::notification_structs::NotificationKind;
+use crate::utils::leaderboard::leaderboard_page;
+use crate::utils::net_worth::net_worth_all;
+use crate::utils::notifications::notify;
+use crate::utils::portfolio::STARTING_BALANCE;
+use chrono::{NaiveDateTime, Utc};
+use entity::{orders, season_results, trades, user_achievements, user_balances, users};
+use sea_orm::prelude::Decimal;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ColumnTrait, DbConn, EntityTrait, FromQueryResult, QueryFilter, QuerySelect, Set,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::time::Duration;
+use tokio::time::interval;
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Achievement {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        code: "first_trade",
+        title: "First trade",
+        description: "Complete a trade",
+    },
+    Achievement {
+        code: "trades_10",
+        title: "Regular",
+        description: "Complete 10 trades",
+    },
+    Achievement {
+        code: "trades_100",
+        title: "Market veteran",
+        description: "Complete 100 trades",
+    },
+    Achievement {
+        code: "first_order",
+        title: "Patient trader",
+        description: "Place a limit order",
+    },
+    Achievement {
+        code: "diversified",
+        title: "Diversified",
+        description: "Hold 5 different assets at once",
+    },
+    Achievement {
+        code: "return_2x",
+        title: "Double up",
+        description: "Reach twice the starting balance",
+    },
+    Achievement {
+        code: "return_10x",
+        title: "Ten bagger",
+        description: "Reach ten times the starting balance",
+    },
+    Achievement {
+        code: "top_10",
+        title: "Top 10",
+        description: "Enter the top 10 of the leaderboard",
+    },
+    Achievement {
+        code: "season_top_10",
+        title: "Season finalist",
+        description: "Finish a season in the top 10",
+    },
+    Achievement {
+        code: "season_winner",
+        title: "Champion",
+        description: "Win a season",
+    },
+];
+
+pub fn achievement(code: &str) -> Option<&'static Achievement> {
+    ACHIEVEMENTS.iter().find(|achievement| achievement.code == code)
+}
+
+#[derive(Serialize)]
+struct AchievementNotification {
+    achievement: Achievement,
+    awarded_at: NaiveDateTime,
+}
+
+#[derive(Default)]
+struct UserStats {
+    trades: i64,
+    orders: i64,
+    assets_held: i64,
+    /// `None` when prices are unavailable, return based rules wait for the next run
+    net_worth: Option<Decimal>,
+    place: Option<u64>,
+    best_season_place: Option<i32>,
+}
+
+fn __earned(code: &str, stats: &UserStats) -> bool {
+    let starting_balance = Decimal::from(STARTING_BALANCE);
+    match code {
+        "first_trade" => stats.trades >= 1,
+        "trades_10" => stats.trades >= 10,
+        "trades_100" => stats.trades >= 100,
+        "first_order" => stats.orders >= 1,
+        "diversified" => stats.assets_held >= 5,
+        "return_2x" => stats.net_worth.is_some_and(|worth| worth >= starting_balance * Decimal::from(2)),
+        "return_10x" => stats.net_worth.is_some_and(|worth| worth >= starting_balance * Decimal::from(10)),
+        "top_10" => stats.place.is_some_and(|place| place <= 10),
+        "season_top_10" => stats.best_season_place.is_some_and(|place| place <= 10),
+        "season_winner" => stats.best_season_place == Some(1),
+        _ => false,
+    }
+}
+
+#[derive(FromQueryResult)]
+struct UserCount {
+    user_id: i32,
+    count: i64,
+}
+
+#[derive(FromQueryResult)]
+struct UserBestPlace {
+    user_id: i32,
+    place: i32,
+}
+
+pub async fn run_achievements(db: DbConn, redis_client: redis::Client, n: u64) {
+    let mut interval = interval(Duration::from_secs(n));
+    loop {
+        interval.tick().await;
+        if let Err(err) = evaluate_achievements(&db, &redis_client).await {
+            eprintln!("Error evaluating achievements: {err}");
+        }
+    }
+}
+
+/// Checks every rule for every player and awards what was earned since the last run.
+pub async fn evaluate_achievements(db: &DbConn, redis_client: &redis::Client) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let players = users::Entity::find()
+        .filter(users::Column::IsBot.eq(false))
+        .all(db)
+        .await?;
+    let mut stats: HashMap<i32, UserStats> = players
+        .iter()
+        .map(|user| (user.id, UserStats::default()))
+        .collect();
+
+    let trade_counts = trades::Entity::find()
+        .select_only()
+        .column(trades::Column::UserId)
+        .column_as(trades::Column::Id.count(), "count")
+        .group_by(trades::Column::UserId)
+        .into_model::<UserCount>()
+        .all(db)
+        .await?;
+    for row in trade_counts {
+        if let Some(user_stats) = stats.get_mut(&row.user_id) {
+            user_stats.trades = row.count;
+        }
+    }
+    let order_counts = orders::Entity::find()
+        .select_only()
+        .column(orders::Column::UserId)
+        .column_as(orders::Column::Id.count(), "count")
+        .group_by(orders::Column::UserId)
+        .into_model::<UserCount>()
+        .all(db)
+        .await?;
+    for row in order_counts {
+        if let Some(user_stats) = stats.get_mut(&row.user_id) {
+            user_stats.orders = row.count;
+        }
+    }
+    let holding_counts = user_balances::Entity::find()
+        .filter(user_balances::Column::Amount.gt(Decimal::ZERO))
+        .select_only()
+        .column(user_balances::Column::UserId)
+        .column_as(user_balances::Column::AssetId.count(), "count")
+        .group_by(user_balances::Column::UserId)
+        .into_model::<UserCount>()
+        .all(db)
+        .await?;
+    for row in holding_counts {
+        if let Some(user_stats) = stats.get_mut(&row.user_id) {
+            user_stats.assets_held = row.count;
+        }
+    }
+    let best_places = season_results::Entity::find()
+        .select_only()
+        .column(season_results::Column::UserId)
+        .column_as(season_results::Column::Place.min(), "place")
+        .group_by(season_results::Column::UserId)
+        .into_model::<UserBestPlace>()
+        .all(db)
+        .await?;
+    for row in best_places {
+        if let Some(user_stats) = stats.get_mut(&row.user_id) {
+            user_stats.best_season_place = Some(row.place);
+        }
+    }
+    if let Ok(worths) = net_worth_all(db, redis_client).await {
+        for (user, worth) in worths {
+            if let Some(user_stats) = stats.get_mut(&user.id) {
+                user_stats.net_worth = Some(worth.total);
+            }
+        }
+    }
+    if let Ok(top) = leaderboard_page(redis_client, LeaderboardWindow::All, 0, 10).await {
+        for entry in top {
+            if let Some(user_stats) = stats.get_mut(&entry.id) {
+                user_stats.place = Some(entry.place);
+            }
+        }
+    }
+
+    let awarded: HashSet<(i32, String)> = user_achievements::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|award| (award.user_id, award.code))
+        .collect();
+
+    for (user_id, user_stats) in &stats {
+        for achievement in ACHIEVEMENTS {
+            if awarded.contains(&(*user_id, achievement.code.to_string()))
+                || !__earned(achievement.code, user_stats)
+            {
+                continue;
+            }
+            let awarded_at = Utc::now().naive_utc();
+            let inserted = user_achievements::Entity::insert(user_achievements::ActiveModel {
+                user_id: Set(*user_id),
+                code: Set(achievement.code.to_string()),
+                awarded_at: Set(awarded_at),
+                ..Default::default()
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    user_achievements::Column::UserId,
+                    user_achievements::Column::Code,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+            if inserted > 0 {
+                let notification = AchievementNotification {
+                    achievement: *achievement,
+                    awarded_at,
+                };
+                notify(db, redis_client, *user_id, NotificationKind::Achievement, &notification).await;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use crate::utils::app_error::AppError;
+use crate::utils::jwt::AccessToken;
+use crate::AppState;
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use entity::users;
+use sea_orm::EntityTrait;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+
+/// Access token of a user with the `admin` role. The role is read from the database on
+/// every request, so revoking it takes effect without waiting for the token to expire.
+#[derive(Debug)]
+pub struct AdminToken(pub AccessToken);
+
+impl Deref for AdminToken {
+    type Target = AccessToken;
+    fn deref(&self) -> &AccessToken {
+        &self.0
+    }
+}
+
+impl FromRequest for AdminToken {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let token = __token_with_role(req, payload, &["admin"], "Admin role required");
+        Box::pin(async move { Ok(AdminToken(token.await?)) })
+    }
+}
+
+/// Access token of a user with the `moderator` or `admin` role, checked the same way as
+/// [`AdminToken`].
+#[derive(Debug)]
+pub struct ModeratorToken(pub AccessToken);
+
+impl Deref for ModeratorToken {
+    type Target = AccessToken;
+    fn deref(&self) -> &AccessToken {
+        &self.0
+    }
+}
+
+impl FromRequest for ModeratorToken {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let token = __token_with_role(req, payload, &["moderator", "admin"], "Moderator role required");
+        Box::pin(async move { Ok(ModeratorToken(token.await?)) })
+    }
+}
+
+fn __token_with_role(
+    req: &HttpRequest,
+    payload: &mut Payload,
+    roles: &'static [&'static str],
+    error: &'static str,
+) -> impl Future<Output = Result<AccessToken, AppError>> {
+    let token = AccessToken::from_request(req, payload).into_inner();
+    let state = req.app_data::<web::Data<AppState>>().cloned();
+
+    async move {
+        let token = token?;
+        let state = state.ok_or(AppError::Internal("AppState not configured".to_string()))?;
+        let user = users::Entity::find_by_id(token.claims.sub)
+            .one(state.db.as_ref())
+            .await?
+            .ok_or(AppError::Unauthorized("Invalid access token"))?;
+        if !roles.contains(&user.role.as_str()) {
+            return Err(AppError::Forbidden(error));
+        }
+        Ok(token)
+    }
+}
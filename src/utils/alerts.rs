@@ -0,0 +1,207 @@
+use crate::structs::alert_structs::AlertKind;
+use crate::structs::notification_structs::NotificationKind;
+use crate::utils::app_error::AppError;
+use crate::utils::mail::Mailer;
+use crate::utils::notifications::notify;
+use crate::utils::price_calculation::PriceTick;
+use chrono::{NaiveDateTime, Utc};
+use entity::{alerts, assets, orders, users};
+use redis::{AsyncCommands, Client};
+use sea_orm::prelude::{Decimal, Expr};
+use sea_orm::{ColumnTrait, ConnectionTrait, DbConn, EntityTrait, QueryFilter};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+pub const MAX_ACTIVE_ALERTS: u64 = 20;
+pub const DEFAULT_CHANGE_WINDOW: i32 = 60;
+
+#[derive(Serialize)]
+pub struct AlertNotification {
+    pub alert_id: i32,
+    pub kind: AlertKind,
+    pub asset_id: Option<i32>,
+    pub order_id: Option<i32>,
+    pub price: Option<Decimal>,
+    pub message: String,
+    pub triggered_at: NaiveDateTime,
+}
+
+/// Switches off `order_filled` alerts of cancelled orders, they can't fire anymore.
+pub async fn deactivate_order_alerts<C: ConnectionTrait>(conn: &C, order_ids: Vec<i32>) -> Result<(), AppError> {
+    if order_ids.is_empty() {
+        return Ok(());
+    }
+    alerts::Entity::update_many()
+        .col_expr(alerts::Column::Active, Expr::value(false))
+        .filter(alerts::Column::OrderId.is_in(order_ids))
+        .filter(alerts::Column::Active.eq(true))
+        .exec(conn)
+        .await?;
+    Ok(())
+}
+
+/// Checks alerts on every price tick. Alerts fire once and are switched off afterwards.
+pub async fn run_alerts(db: DbConn, cache: Client, mut ticks: broadcast::Receiver<PriceTick>, mailer: Mailer) {
+    loop {
+        let tick = match ticks.recv().await {
+            Ok(tick) => tick,
+            Err(RecvError::Lagged(skipped)) => {
+                eprintln!("Alerts skipped {skipped} price ticks");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if let Err(err) = __evaluate_alerts(&db, &cache, &mailer, &tick).await {
+            eprintln!("Alerts error: {err:?}");
+        }
+    }
+}
+
+async fn __evaluate_alerts(db: &DbConn, cache: &Client, mailer: &Mailer, tick: &PriceTick) -> Result<(), AppError> {
+    let active = alerts::Entity::find()
+        .filter(alerts::Column::Active.eq(true))
+        .all(db)
+        .await?;
+    if active.is_empty() {
+        return Ok(());
+    }
+
+    let order_ids: Vec<i32> = active.iter().filter_map(|alert| alert.order_id).collect();
+    let filled: HashMap<i32, orders::Model> = orders::Entity::find()
+        .filter(orders::Column::Id.is_in(order_ids))
+        .filter(orders::Column::Status.eq("done"))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|order| (order.id, order))
+        .collect();
+    let symbols: HashMap<i32, String> = assets::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|asset| (asset.id, asset.symbol))
+        .collect();
+
+    let mut window_starts: HashMap<(i32, i32), Option<Decimal>> = HashMap::new();
+    for alert in active {
+        let Some(kind) = AlertKind::from_name(&alert.kind) else {
+            continue;
+        };
+        let price = alert.asset_id.and_then(|asset_id| tick.prices.get(&asset_id).copied());
+        let symbol = alert
+            .asset_id
+            .and_then(|asset_id| symbols.get(&asset_id).cloned())
+            .unwrap_or_default();
+        let threshold = alert.threshold.unwrap_or_default();
+
+        let message = match (kind, price) {
+            (AlertKind::Above, Some(price)) if price >= threshold => {
+                Some(format!("{symbol} is at {price}, above {threshold}"))
+            }
+            (AlertKind::Below, Some(price)) if price <= threshold => {
+                Some(format!("{symbol} is at {price}, below {threshold}"))
+            }
+            (AlertKind::Change, Some(price)) => {
+                let window = alert.window_minutes.unwrap_or(DEFAULT_CHANGE_WINDOW);
+                let asset_id = alert.asset_id.unwrap_or_default();
+                let start = match window_starts.get(&(asset_id, window)) {
+                    Some(start) => *start,
+                    None => {
+                        let start = __window_start_price(cache, asset_id, window).await?;
+                        window_starts.insert((asset_id, window), start);
+                        start
+                    }
+                };
+                start
+                    .filter(|start| !start.is_zero())
+                    .map(|start| (price - start) / start * Decimal::from(100))
+                    .filter(|change| change.abs() >= threshold)
+                    .map(|change| {
+                        format!("{symbol} moved {}% in {window} minutes, now at {price}", change.round_dp(2))
+                    })
+            }
+            (AlertKind::OrderFilled, _) => alert
+                .order_id
+                .and_then(|order_id| filled.get(&order_id))
+                .map(|order| {
+                    format!(
+                        "Your {} order #{} for {} {} was filled",
+                        order.order_type,
+                        order.id,
+                        order.amount,
+                        symbols.get(&order.asset_id).cloned().unwrap_or_default()
+                    )
+                }),
+            _ => None,
+        };
+        let Some(message) = message else {
+            continue;
+        };
+
+        // Only the update that switches the alert off delivers it
+        let triggered_at = Utc::now().naive_utc();
+        let switched = alerts::Entity::update_many()
+            .col_expr(alerts::Column::Active, Expr::value(false))
+            .col_expr(alerts::Column::TriggeredAt, Expr::value(triggered_at))
+            .filter(alerts::Column::Id.eq(alert.id))
+            .filter(alerts::Column::Active.eq(true))
+            .exec(db)
+            .await?;
+        if switched.rows_affected == 0 {
+            continue;
+        }
+
+        let notification = AlertNotification {
+            alert_id: alert.id,
+            kind,
+            asset_id: alert.asset_id,
+            order_id: alert.order_id,
+            price,
+            message,
+            triggered_at,
+        };
+        notify(db, cache, alert.user_id, NotificationKind::Alert, &notification).await;
+        if alert.email {
+            tokio::spawn(__send_email(db.clone(), mailer.clone(), alert.user_id, notification.message));
+        }
+    }
+    Ok(())
+}
+
+/// Oldest price of the minute history inside the window.
+async fn __window_start_price(cache: &Client, asset_id: i32, window_minutes: i32) -> Result<Option<Decimal>, AppError> {
+    let since = Utc::now().timestamp() - i64::from(window_minutes) * 60;
+    let mut redis_conn = cache.get_multiplexed_async_connection().await?;
+    let history: Vec<String> = redis_conn
+        .zrangebyscore_limit(format!("asset_price_history:{asset_id}"), since, "+inf", 0, 1)
+        .await?;
+    Ok(history
+        .first()
+        .and_then(|item| item.split_once(':'))
+        .and_then(|(price, _)| Decimal::from_str(price).ok()))
+}
+
+async fn __send_email(db: DbConn, mailer: Mailer, user_id: i32, message: String) {
+    let user = match users::Entity::find_by_id(user_id).one(&db).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return,
+        Err(err) => {
+            eprintln!("Alert email failed: {err}");
+            return;
+        }
+    };
+    let body = format!("<p>{}</p>", __escape_html(&message));
+    let sent = tokio::task::spawn_blocking(move || mailer.send_html(&user.email, "Trade game alert", body)).await;
+    match sent {
+        Ok(Err(err)) => eprintln!("Alert email failed: {err}"),
+        Err(err) => eprintln!("Alert email failed: {err}"),
+        Ok(Ok(())) => {}
+    }
+}
+
+fn __escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
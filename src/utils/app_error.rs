@@ -0,0 +1,180 @@
+use crate::utils::response::{CommonResponse, ResponseStatus};
+use actix_web::http::header::RETRY_AFTER;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use sea_orm::{DbErr, SqlErr};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    Unauthorized,
+    Forbidden,
+    InvalidCredentials,
+    NotFound,
+    ValidationError,
+    AlreadyExists,
+    IdempotencyConflict,
+    InsufficientFunds,
+    InsufficientAssets,
+    OrderNotExecutable,
+    WrongRecoverCode,
+    TooManyRequests,
+    PriceUnavailable,
+    PriceMoved,
+    QuoteExpired,
+    ServiceUnavailable,
+    InternalError,
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    Unauthorized(&'static str),
+    Forbidden(&'static str),
+    InvalidCredentials,
+    NotFound(&'static str),
+    Validation(String),
+    AlreadyExists(&'static str),
+    IdempotencyConflict(&'static str),
+    InsufficientFunds,
+    InsufficientAssets,
+    OrderNotExecutable(&'static str),
+    WrongRecoverCode,
+    TooManyRequests,
+    /// Seconds until the client may retry, sent back in `Retry-After`.
+    RateLimited(u64),
+    PriceUnavailable,
+    /// The execution price is worse than the quote or the limit the client sent.
+    PriceMoved,
+    QuoteExpired,
+    /// A dependency the request can't safely run without is down.
+    ServiceUnavailable(&'static str),
+    /// The message is written to the server log and never sent to the client.
+    Internal(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::InvalidCredentials => ErrorCode::InvalidCredentials,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::Validation(_) => ErrorCode::ValidationError,
+            AppError::AlreadyExists(_) => ErrorCode::AlreadyExists,
+            AppError::IdempotencyConflict(_) => ErrorCode::IdempotencyConflict,
+            AppError::InsufficientFunds => ErrorCode::InsufficientFunds,
+            AppError::InsufficientAssets => ErrorCode::InsufficientAssets,
+            AppError::OrderNotExecutable(_) => ErrorCode::OrderNotExecutable,
+            AppError::WrongRecoverCode => ErrorCode::WrongRecoverCode,
+            AppError::TooManyRequests | AppError::RateLimited(_) => ErrorCode::TooManyRequests,
+            AppError::PriceUnavailable => ErrorCode::PriceUnavailable,
+            AppError::PriceMoved => ErrorCode::PriceMoved,
+            AppError::QuoteExpired => ErrorCode::QuoteExpired,
+            AppError::ServiceUnavailable(_) => ErrorCode::ServiceUnavailable,
+            AppError::Internal(_) => ErrorCode::InternalError,
+        }
+    }
+
+    pub fn internal(err: impl Display) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Unauthorized(msg)
+            | AppError::Forbidden(msg)
+            | AppError::NotFound(msg)
+            | AppError::AlreadyExists(msg)
+            | AppError::IdempotencyConflict(msg)
+            | AppError::OrderNotExecutable(msg)
+            | AppError::ServiceUnavailable(msg) => write!(f, "{msg}"),
+            AppError::Validation(msg) => write!(f, "{msg}"),
+            AppError::InvalidCredentials => write!(f, "Wrong username or password"),
+            AppError::InsufficientFunds => write!(f, "Not enough money"),
+            AppError::InsufficientAssets => write!(f, "Not enough asset amount"),
+            AppError::WrongRecoverCode => write!(f, "Wrong code"),
+            AppError::TooManyRequests => write!(f, "Too many attempts"),
+            AppError::RateLimited(retry_after) => write!(f, "Rate limit exceeded, retry in {retry_after}s"),
+            AppError::PriceUnavailable => write!(f, "Price is not available"),
+            AppError::PriceMoved => write!(f, "Price moved beyond the limit"),
+            AppError::QuoteExpired => write!(f, "Quote expired"),
+            AppError::Internal(_) => write!(f, "Internal server error"),
+        }
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) | AppError::WrongRecoverCode => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::AlreadyExists(_)
+            | AppError::IdempotencyConflict(_)
+            | AppError::OrderNotExecutable(_)
+            | AppError::PriceMoved
+            | AppError::QuoteExpired => StatusCode::CONFLICT,
+            AppError::InvalidCredentials
+            | AppError::Validation(_)
+            | AppError::InsufficientFunds
+            | AppError::InsufficientAssets => StatusCode::BAD_REQUEST,
+            AppError::TooManyRequests | AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::PriceUnavailable | AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let AppError::Internal(err) = self {
+            eprintln!("Internal error: {err}");
+        }
+        let mut response = HttpResponse::build(self.status_code());
+        if let AppError::RateLimited(retry_after) = self {
+            response.insert_header((RETRY_AFTER, retry_after.to_string()));
+        }
+        response.json(CommonResponse::<()> {
+            status: ResponseStatus::Error,
+            data: (),
+            error: Some(self.to_string()),
+            code: Some(self.code()),
+        })
+    }
+}
+
+impl From<DbErr> for AppError {
+    fn from(err: DbErr) -> Self {
+        if let Some(SqlErr::UniqueConstraintViolation(_)) = err.sql_err() {
+            return AppError::AlreadyExists("Record already exists");
+        }
+        AppError::Internal(err.to_string())
+    }
+}
+
+impl From<redis::RedisError> for AppError {
+    fn from(err: redis::RedisError) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for AppError {
+    fn from(err: jsonwebtoken::errors::Error) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
+
+impl From<argon2::password_hash::Error> for AppError {
+    fn from(err: argon2::password_hash::Error) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for AppError {
+    fn from(err: Box<dyn Error + Send + Sync>) -> Self {
+        AppError::Internal(err.to_string())
+    }
+}
@@ -0,0 +1,77 @@
+use crate::structs::chat_structs::{Attachment, AttachmentInput};
+use crate::structs::ws_structs::BookEvent;
+use crate::utils::app_error::AppError;
+use crate::utils::get_price::get_price_by_asset_id;
+use entity::{assets, orders, trades};
+use redis::Client;
+use sea_orm::{DbConn, EntityTrait, ModelTrait};
+
+/// Builds the card for an attachment. Trades and orders can only be shared by their owner.
+pub async fn resolve_attachment(
+    db: &DbConn,
+    cache: &Client,
+    user_id: i32,
+    input: AttachmentInput,
+) -> Result<Attachment, AppError> {
+    match input {
+        AttachmentInput::Trade { trade_id } => {
+            let trade = trades::Entity::find_by_id(trade_id)
+                .one(db)
+                .await?
+                .filter(|trade| trade.user_id == user_id)
+                .ok_or(AppError::NotFound("No trade"))?;
+            let asset = __find_asset(db, &trade).await?;
+            Ok(Attachment::Trade {
+                trade_id: trade.id,
+                asset_id: asset.id,
+                symbol: asset.symbol,
+                trade_type: trade.trade_type,
+                price: trade.price,
+                amount: trade.amount,
+                created_at: trade.created_at,
+            })
+        }
+        AttachmentInput::Order { order_id } => {
+            let order = orders::Entity::find_by_id(order_id)
+                .one(db)
+                .await?
+                .filter(|order| order.user_id == user_id)
+                .ok_or(AppError::NotFound("No order"))?;
+            let asset = __find_asset(db, &order).await?;
+            let price = BookEvent::from(&order).price;
+            Ok(Attachment::Order {
+                order_id: order.id,
+                asset_id: asset.id,
+                symbol: asset.symbol,
+                order_type: order.order_type,
+                price,
+                amount: order.amount,
+                status: order.status,
+            })
+        }
+        AttachmentInput::Asset { asset_id } => {
+            let asset = assets::Entity::find_by_id(asset_id)
+                .one(db)
+                .await?
+                .ok_or(AppError::NotFound("No asset"))?;
+            Ok(Attachment::Asset {
+                price: get_price_by_asset_id(cache, asset.id).await.ok(),
+                asset_id: asset.id,
+                symbol: asset.symbol,
+                name: asset.name,
+            })
+        }
+    }
+}
+
+async fn __find_asset<M>(db: &DbConn, model: &M) -> Result<assets::Model, AppError>
+where
+    M: ModelTrait + Sync,
+    M::Entity: sea_orm::Related<assets::Entity>,
+{
+    model
+        .find_related(assets::Entity)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("No asset"))
+}
@@ -0,0 +1,136 @@
+use crate::utils::app_error::AppError;
+use std::collections::HashSet;
+
+const LINK_PLACEHOLDER: &str = "[link]";
+/// Bare `name.tld` tokens count as links only with one of these, so `e.g.` or `3.5x` stay text
+const KNOWN_TLDS: [&str; 32] = [
+    "com", "net", "org", "info", "biz", "io", "co", "me", "app", "dev", "xyz", "site", "online",
+    "ru", "su", "by", "kz", "ua", "uk", "de", "fr", "eu", "us", "cn", "jp", "tv", "cc", "gg",
+    "ly", "to", "link", "club",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterAction {
+    /// Replace banned words with `*` and links with `[link]`
+    Mask,
+    /// Refuse the whole message
+    Reject,
+}
+
+/// Profanity and link filter applied to every chat message before it is stored.
+pub struct ChatFilter {
+    banned_words: HashSet<String>,
+    filter_links: bool,
+    allowed_domains: Vec<String>,
+    action: FilterAction,
+}
+
+impl ChatFilter {
+    /// Reads `CHAT_BANNED_WORDS` and `CHAT_ALLOWED_DOMAINS` (comma separated),
+    /// `CHAT_FILTER_LINKS` (`false` by default) and `CHAT_FILTER_ACTION` (`mask` or `reject`).
+    pub fn from_env() -> Self {
+        let list = |name: &str| -> Vec<String> {
+            std::env::var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(|item| item.trim().to_lowercase())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+
+        Self {
+            banned_words: list("CHAT_BANNED_WORDS").into_iter().collect(),
+            filter_links: std::env::var("CHAT_FILTER_LINKS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(false),
+            allowed_domains: list("CHAT_ALLOWED_DOMAINS"),
+            action: match std::env::var("CHAT_FILTER_ACTION").as_deref() {
+                Ok("reject") => FilterAction::Reject,
+                _ => FilterAction::Mask,
+            },
+        }
+    }
+
+    /// Returns the text to store, masked if needed, or a validation error in `reject` mode.
+    pub fn apply(&self, text: &str) -> Result<String, AppError> {
+        let mut filtered = String::with_capacity(text.len());
+        let mut changed = false;
+
+        for piece in text.split_inclusive(char::is_whitespace) {
+            let word = piece.trim_end();
+            let space = &piece[word.len()..];
+            if self.filter_links && self.__is_blocked_link(word) {
+                filtered.push_str(LINK_PLACEHOLDER);
+                changed = true;
+            } else {
+                changed |= self.__mask_words(word, &mut filtered);
+            }
+            filtered.push_str(space);
+        }
+
+        match (changed, self.action) {
+            (true, FilterAction::Reject) => Err(AppError::Validation(
+                "Message contains banned words or links".to_string(),
+            )),
+            _ => Ok(filtered),
+        }
+    }
+
+    /// Copies `word` into `out` with every banned alphanumeric run replaced by `*`.
+    fn __mask_words(&self, word: &str, out: &mut String) -> bool {
+        if self.banned_words.is_empty() {
+            out.push_str(word);
+            return false;
+        }
+        let mut changed = false;
+        let mut rest = word;
+        while let Some(start) = rest.find(char::is_alphanumeric) {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest.find(|c: char| !c.is_alphanumeric()).unwrap_or(rest.len());
+            let run = &rest[..end];
+            if self.banned_words.contains(&run.to_lowercase()) {
+                out.extend(std::iter::repeat_n('*', run.chars().count()));
+                changed = true;
+            } else {
+                out.push_str(run);
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(rest);
+        changed
+    }
+
+    /// A token is a link when it has a scheme, starts with `www.` or looks like `name.tld`
+    /// with a known TLD.
+    fn __is_blocked_link(&self, word: &str) -> bool {
+        let word = word
+            .trim_matches(|c: char| !c.is_alphanumeric() && c != '/')
+            .to_lowercase();
+        let (explicit, rest) = match word.split_once("://") {
+            Some((_, rest)) => (true, rest),
+            None => (false, word.as_str()),
+        };
+        let (explicit, rest) = match rest.strip_prefix("www.") {
+            Some(rest) => (true, rest),
+            None => (explicit, rest),
+        };
+        let host = rest.split(['/', '?', '#', ':']).next().unwrap_or_default();
+
+        let looks_like_domain = host.rsplit_once('.').is_some_and(|(name, tld)| {
+            !name.is_empty()
+                && KNOWN_TLDS.contains(&tld)
+                && name.split('.').all(|label| {
+                    !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+                })
+        });
+        if !explicit && !looks_like_domain {
+            return false;
+        }
+        !self
+            .allowed_domains
+            .iter()
+            .any(|domain| host == domain || host.ends_with(&format!(".{domain}")))
+    }
+}
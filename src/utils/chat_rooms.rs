@@ -0,0 +1,93 @@
+use crate::structs::chat_room_structs::{RoomKind, RoomRole};
+use crate::utils::app_error::AppError;
+use entity::{assets, chat_room_members, chat_rooms};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect, Set};
+
+pub async fn find_room<C: ConnectionTrait>(conn: &C, room_id: i32) -> Result<chat_rooms::Model, AppError> {
+    chat_rooms::Entity::find_by_id(room_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NotFound("No room"))
+}
+
+/// Room messages are only sent and delivered to members.
+pub async fn find_room_member<C: ConnectionTrait>(
+    conn: &C,
+    room_id: i32,
+    user_id: i32,
+) -> Result<chat_room_members::Model, AppError> {
+    chat_room_members::Entity::find()
+        .filter(chat_room_members::Column::RoomId.eq(room_id))
+        .filter(chat_room_members::Column::UserId.eq(user_id))
+        .one(conn)
+        .await?
+        .ok_or(AppError::Forbidden("Not a room member"))
+}
+
+/// Asset rooms are public, group rooms can only be read by their members.
+pub async fn check_room_readable<C: ConnectionTrait>(conn: &C, room_id: i32, user_id: i32) -> Result<(), AppError> {
+    let room = find_room(conn, room_id).await?;
+    if room.kind == RoomKind::Group.name() {
+        find_room_member(conn, room.id, user_id).await?;
+    }
+    Ok(())
+}
+
+pub async fn find_room_admin<C: ConnectionTrait>(
+    conn: &C,
+    room_id: i32,
+    user_id: i32,
+) -> Result<chat_room_members::Model, AppError> {
+    let member = find_room_member(conn, room_id, user_id).await?;
+    if member.role != RoomRole::Admin.name() {
+        return Err(AppError::Forbidden("Not a room admin"));
+    }
+    Ok(member)
+}
+
+/// Returns the public room of the asset, creating it on first use.
+pub async fn asset_room<C: ConnectionTrait>(conn: &C, asset_id: i32) -> Result<chat_rooms::Model, AppError> {
+    if let Some(room) = chat_rooms::Entity::find()
+        .filter(chat_rooms::Column::AssetId.eq(asset_id))
+        .one(conn)
+        .await?
+    {
+        return Ok(room);
+    }
+
+    let asset = assets::Entity::find_by_id(asset_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NotFound("No asset"))?;
+    // Two first visitors may race here, the unique asset_id keeps a single room.
+    chat_rooms::Entity::insert(chat_rooms::ActiveModel {
+        kind: Set(RoomKind::Asset.name().to_string()),
+        name: Set(asset.symbol),
+        asset_id: Set(Some(asset_id)),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::column(chat_rooms::Column::AssetId)
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(conn)
+    .await?;
+
+    chat_rooms::Entity::find()
+        .filter(chat_rooms::Column::AssetId.eq(asset_id))
+        .one(conn)
+        .await?
+        .ok_or(AppError::NotFound("No room"))
+}
+
+pub async fn room_member_ids<C: ConnectionTrait>(conn: &C, room_id: i32) -> Result<Vec<i32>, AppError> {
+    Ok(chat_room_members::Entity::find()
+        .filter(chat_room_members::Column::RoomId.eq(room_id))
+        .select_only()
+        .column(chat_room_members::Column::UserId)
+        .into_tuple()
+        .all(conn)
+        .await?)
+}
@@ -74,10 +74,7 @@ pub async fn initialize_assets(db: &DbConn) -> Result<(), sea_orm::DbErr> {
     ];
     
     for asset in assets {
-        match assets::Entity::insert(asset).exec(db).await {
-            Ok(_) => {}
-            Err(_) => {}
-        };
+        let _ = assets::Entity::insert(asset).exec(db).await;
     }
 
     Ok(())
@@ -1,7 +1,6 @@
-use crate::utils::response::{CommonResponse, ResponseStatus};
+use crate::utils::app_error::AppError;
 use crate::AppState;
-use actix_web::error::InternalError;
-use actix_web::{dev::Payload, Error, FromRequest, HttpRequest, HttpResponse};
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
 use chrono::{Duration, Utc};
 use futures::future::{ready, Ready};
 use jsonwebtoken::{decode, DecodingKey, Validation};
@@ -76,23 +75,14 @@ impl Deref for AccessToken {
 }
 
 impl FromRequest for AccessToken {
-    type Error = Error;
+    type Error = AppError;
     type Future = Ready<Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        fn unauthorized_response(message: String) -> Error {
-            let response = HttpResponse::Unauthorized().json(CommonResponse::<()> {
-                status: ResponseStatus::Error,
-                data: (),
-                error: Some(message.clone()),
-            });
-            InternalError::from_response(message, response).into()
-        }
-
         let state = match req.app_data::<actix_web::web::Data<AppState>>() {
             Some(data) => data.get_ref().jwt_secret.clone(),
             None => {
-                return ready(Err(unauthorized_response(
+                return ready(Err(AppError::Internal(
                     "AppState not configured".to_string(),
                 )))
             }
@@ -112,16 +102,14 @@ impl FromRequest for AccessToken {
                             if token_data.claims.token_type == "access" {
                                 ready(Ok(AccessToken(token_data)))
                             } else {
-                                ready(Err(unauthorized_response("Invalid token type".to_string())))
+                                ready(Err(AppError::Unauthorized("Invalid token type")))
                             }
                         }
-                        Err(_) => ready(Err(unauthorized_response(
-                            "Invalid access token".to_string(),
-                        ))),
+                        Err(_) => ready(Err(AppError::Unauthorized("Invalid access token"))),
                     };
                 }
             }
         }
-        ready(Err(unauthorized_response("Missing token".to_string())))
+        ready(Err(AppError::Unauthorized("Missing token")))
     }
 }
@@ -0,0 +1,216 @@
+use crate::structs::leaderboard_structs::LeaderboardWindow;
+use crate::utils::app_error::AppError;
+use crate::utils::net_worth::net_worth_all;
+use crate::utils::portfolio::STARTING_BALANCE;
+use crate::utils::seasons::current_season;
+use chrono::Utc;
+use redis::{AsyncCommands, Client};
+use sea_orm::prelude::{DateTime, Decimal};
+use sea_orm::{DatabaseBackend, DbConn, FromQueryResult, Statement};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::Duration;
+use tokio::time::interval;
+
+const USERS_KEY: &str = "leaderboard:users";
+
+/// Sorted set of user ids scored by the return over the window, in percent.
+fn __ranking_key(window: LeaderboardWindow) -> String {
+    format!("leaderboard:{}", window.name())
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct LeaderboardUser {
+    username: String,
+    total_balance: Decimal,
+    previous_place: Option<i32>,
+    /// Return in percent per window name
+    returns: HashMap<String, Decimal>,
+}
+
+#[derive(Serialize)]
+pub struct LeaderboardEntry {
+    pub place: u64,
+    pub id: i32,
+    pub username: String,
+    pub total_balance: Decimal,
+    /// Percent over the requested window
+    pub return_percent: Decimal,
+    /// Place in the latest net worth snapshot
+    pub previous_place: Option<i32>,
+}
+
+#[derive(FromQueryResult)]
+struct SnapshotValue {
+    user_id: i32,
+    total: Decimal,
+}
+
+#[derive(FromQueryResult)]
+struct SnapshotPlace {
+    user_id: i32,
+    place: Option<i32>,
+}
+
+pub async fn update_leaderboards(db: DbConn, redis_client: Client, n: u64) {
+    let mut interval = interval(Duration::from_secs(n));
+    loop {
+        interval.tick().await;
+        if let Err(err) = update_leaderboards_executor(&db, &redis_client).await {
+            eprintln!("Error updating leaderboards: {err}");
+        }
+    }
+}
+
+/// Recomputes every window and swaps the sorted sets in at once, so readers never see a
+/// half-built ranking.
+pub async fn update_leaderboards_executor(db: &DbConn, redis_client: &Client) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let worths = net_worth_all(db, redis_client)
+        .await
+        .map_err(|err| err.to_string())?;
+    let previous_places: HashMap<i32, Option<i32>> = SnapshotPlace::find_by_statement(Statement::from_string(
+        DatabaseBackend::Postgres,
+        "SELECT DISTINCT ON (user_id) user_id, place FROM net_worth_snapshots ORDER BY user_id, created_at DESC",
+    ))
+    .all(db)
+    .await?
+    .into_iter()
+    .map(|snapshot| (snapshot.user_id, snapshot.place))
+    .collect();
+
+    let season_start = current_season(db).await?.map(|season| season.starts_at);
+    let now = Utc::now().naive_utc();
+    let mut users: HashMap<i32, LeaderboardUser> = HashMap::new();
+    let mut rankings: Vec<(String, Vec<(f64, i32)>)> = Vec::new();
+    for window in LeaderboardWindow::ALL {
+        let start = window.duration().map(|duration| now - duration);
+        let baselines = match start {
+            Some(start) => __baselines(db, start).await?,
+            None => HashMap::new(),
+        };
+        let mut scores = Vec::new();
+        for (user, worth) in worths.iter().filter(|(user, _)| !user.is_bot) {
+            // Accounts created and seasons started inside the window began from the starting balance
+            let baseline = match start {
+                Some(start)
+                    if user.created_at < start
+                        && season_start.is_none_or(|season_start| season_start < start) =>
+                {
+                    baselines.get(&user.id).copied()
+                }
+                _ => None,
+            }
+            .unwrap_or(Decimal::from(STARTING_BALANCE));
+            let return_percent = if baseline.is_zero() {
+                Decimal::ZERO
+            } else {
+                ((worth.total - baseline) / baseline * Decimal::from(100)).round_dp(2)
+            };
+            scores.push((f64::try_from(return_percent).unwrap_or_default(), user.id));
+            users
+                .entry(user.id)
+                .or_insert_with(|| LeaderboardUser {
+                    username: user.username.clone(),
+                    total_balance: worth.total,
+                    previous_place: previous_places.get(&user.id).copied().flatten(),
+                    returns: HashMap::new(),
+                })
+                .returns
+                .insert(window.name().to_string(), return_percent);
+        }
+        rankings.push((__ranking_key(window), scores));
+    }
+
+    let mut users_json = Vec::with_capacity(users.len());
+    for (user_id, user) in &users {
+        users_json.push((*user_id, serde_json::to_string(user)?));
+    }
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    pipe.del(USERS_KEY);
+    if !users_json.is_empty() {
+        pipe.hset_multiple(USERS_KEY, &users_json);
+    }
+    for (key, scores) in &rankings {
+        pipe.del(key);
+        if !scores.is_empty() {
+            pipe.zadd_multiple(key, scores);
+        }
+    }
+    let mut redis_conn = redis_client.get_multiplexed_async_connection().await?;
+    let _: () = pipe.query_async(&mut redis_conn).await?;
+    Ok(())
+}
+
+/// Net worth at the window start: the last snapshot before it, or the first one after it for
+/// users that were not snapshotted yet.
+async fn __baselines(db: &DbConn, start: DateTime) -> Result<HashMap<i32, Decimal>, Box<dyn Error + Send + Sync>> {
+    let mut baselines = HashMap::new();
+    for sql in [
+        "SELECT DISTINCT ON (user_id) user_id, total FROM net_worth_snapshots WHERE created_at > $1 ORDER BY user_id, created_at ASC",
+        "SELECT DISTINCT ON (user_id) user_id, total FROM net_worth_snapshots WHERE created_at <= $1 ORDER BY user_id, created_at DESC",
+    ] {
+        let snapshots = SnapshotValue::find_by_statement(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            sql,
+            [start.into()],
+        ))
+        .all(db)
+        .await?;
+        baselines.extend(snapshots.into_iter().map(|snapshot| (snapshot.user_id, snapshot.total)));
+    }
+    Ok(baselines)
+}
+
+/// Entries with zero-based ranks `offset..offset + limit`.
+pub async fn leaderboard_page(
+    cache: &Client,
+    window: LeaderboardWindow,
+    offset: u64,
+    limit: u64,
+) -> Result<Vec<LeaderboardEntry>, AppError> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    let mut redis_conn = cache.get_multiplexed_async_connection().await?;
+    let ranking: Vec<(i32, f64)> = redis_conn
+        .zrevrange_withscores(
+            __ranking_key(window),
+            offset as isize,
+            (offset + limit - 1) as isize,
+        )
+        .await?;
+    if ranking.is_empty() {
+        return Ok(Vec::new());
+    }
+    let user_ids: Vec<i32> = ranking.iter().map(|(user_id, _)| *user_id).collect();
+    let users: Vec<Option<String>> = redis_conn.hget(USERS_KEY, &user_ids).await?;
+
+    let mut entries = Vec::with_capacity(ranking.len());
+    for (index, ((user_id, _), user)) in ranking.into_iter().zip(users).enumerate() {
+        let Some(user) = user else { continue };
+        let user: LeaderboardUser = serde_json::from_str(&user).map_err(AppError::internal)?;
+        entries.push(LeaderboardEntry {
+            place: offset + index as u64 + 1,
+            id: user_id,
+            return_percent: user.returns.get(window.name()).copied().unwrap_or_default(),
+            username: user.username,
+            total_balance: user.total_balance,
+            previous_place: user.previous_place,
+        });
+    }
+    Ok(entries)
+}
+
+/// One-based place of the user, `None` if they are not ranked yet.
+pub async fn leaderboard_place(
+    cache: &Client,
+    window: LeaderboardWindow,
+    user_id: i32,
+) -> Result<Option<u64>, AppError> {
+    let mut redis_conn = cache.get_multiplexed_async_connection().await?;
+    let rank: Option<u64> = redis_conn.zrevrank(__ranking_key(window), user_id).await?;
+    Ok(rank.map(|rank| rank + 1))
+}
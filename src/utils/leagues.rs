@@ -0,0 +1,228 @@
+use crate::structs::order_structs::OrderType;
+use crate::utils::app_error::AppError;
+use crate::utils::get_price::get_price_by_asset_id;
+use crate::utils::market_execution::MarketFill;
+use crate::utils::take_commission::take_commission;
+use crate::{COMMISSION_MARKET_BUY, COMMISSION_MARKET_SELL};
+use chrono::Utc;
+use entity::{league_balances, league_members, league_trades, leagues, users};
+use rand::distr::{Alphanumeric, SampleString};
+use redis::Client;
+use sea_orm::prelude::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn, EntityTrait, IntoActiveModel,
+    QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+const INVITE_CODE_LENGTH: usize = 8;
+
+pub fn generate_invite_code() -> String {
+    Alphanumeric
+        .sample_string(&mut rand::rng(), INVITE_CODE_LENGTH)
+        .to_uppercase()
+}
+
+pub fn league_asset_ids(league: &leagues::Model) -> Vec<i32> {
+    serde_json::from_value(league.asset_ids.clone()).unwrap_or_default()
+}
+
+pub async fn find_league<C: ConnectionTrait>(conn: &C, league_id: i32) -> Result<leagues::Model, AppError> {
+    leagues::Entity::find_by_id(league_id)
+        .one(conn)
+        .await?
+        .ok_or(AppError::NotFound("No league"))
+}
+
+/// League data is only visible to its members.
+pub async fn find_member<C: ConnectionTrait>(
+    conn: &C,
+    league_id: i32,
+    user_id: i32,
+) -> Result<league_members::Model, AppError> {
+    league_members::Entity::find()
+        .filter(league_members::Column::LeagueId.eq(league_id))
+        .filter(league_members::Column::UserId.eq(user_id))
+        .one(conn)
+        .await?
+        .ok_or(AppError::Forbidden("Not a league member"))
+}
+
+/// Trades the league's virtual balance at the house price with the usual market commissions.
+/// `users.balance` and `user_balances` are never touched.
+pub async fn execute_league_market_order(
+    db: &DbConn,
+    cache: &Client,
+    side: OrderType,
+    league_id: i32,
+    user_id: i32,
+    asset_id: i32,
+    amount: Decimal,
+) -> Result<MarketFill, AppError> {
+    if !amount.is_sign_positive() || amount.is_zero() {
+        return Err(AppError::Validation("Wrong amount".into()));
+    }
+    let league = find_league(db, league_id).await?;
+    let now = Utc::now().naive_utc();
+    if now < league.starts_at || now >= league.ends_at {
+        return Err(AppError::Validation("League is not running".into()));
+    }
+    if !league_asset_ids(&league).contains(&asset_id) {
+        return Err(AppError::Validation("Asset is not allowed in this league".into()));
+    }
+    let price = get_price_by_asset_id(cache, asset_id).await?;
+    let value = (price * amount).round_dp(3);
+
+    let txn = db.begin().await?;
+    let member = league_members::Entity::find()
+        .filter(league_members::Column::LeagueId.eq(league_id))
+        .filter(league_members::Column::UserId.eq(user_id))
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::Forbidden("Not a league member"))?;
+    let holding = league_balances::Entity::find()
+        .filter(league_balances::Column::MemberId.eq(member.id))
+        .filter(league_balances::Column::AssetId.eq(asset_id))
+        .lock_exclusive()
+        .one(&txn)
+        .await?;
+    let held = holding.as_ref().map(|holding| holding.amount).unwrap_or_default();
+
+    let (received, commission, balance, asset_delta, total) = match side {
+        OrderType::Buy => {
+            if member.balance < value {
+                return Err(AppError::InsufficientFunds);
+            }
+            let share = take_commission(amount, *COMMISSION_MARKET_BUY);
+            (share.amount, share.commission, member.balance - value, share.amount, value)
+        }
+        OrderType::Sell => {
+            if held < amount {
+                return Err(AppError::InsufficientAssets);
+            }
+            let share = take_commission(value, *COMMISSION_MARKET_SELL);
+            (share.amount, share.commission, member.balance + share.amount, -amount, share.amount)
+        }
+    };
+
+    match holding {
+        Some(holding) => {
+            let mut active_holding = holding.into_active_model();
+            active_holding.amount = Set(held + asset_delta);
+            active_holding.update(&txn).await?;
+        }
+        None => {
+            league_balances::ActiveModel {
+                member_id: Set(member.id),
+                asset_id: Set(asset_id),
+                amount: Set(asset_delta),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+    }
+    league_trades::ActiveModel {
+        member_id: Set(member.id),
+        asset_id: Set(asset_id),
+        trade_type: Set(match side {
+            OrderType::Buy => "buy".into(),
+            OrderType::Sell => "sell".into(),
+        }),
+        price: Set(price),
+        amount: Set(match side {
+            OrderType::Buy => received,
+            OrderType::Sell => amount,
+        }),
+        total: Set(total),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    let balance = balance.round_dp(3);
+    let mut active_member = member.into_active_model();
+    active_member.balance = Set(balance);
+    active_member.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(MarketFill {
+        amount: received,
+        commission,
+        balance,
+        avg_price: price,
+        book_amount: Decimal::ZERO,
+    })
+}
+
+#[derive(Serialize)]
+pub struct LeagueStanding {
+    pub place: u64,
+    pub user_id: i32,
+    pub username: String,
+    pub cash: Decimal,
+    pub assets: Decimal,
+    pub total: Decimal,
+    /// Percent against the league's starting capital
+    pub return_percent: Decimal,
+}
+
+/// Leagues are small, so standings are valued on request at the current Redis prices.
+pub async fn league_standings(
+    db: &DbConn,
+    cache: &Client,
+    league: &leagues::Model,
+) -> Result<Vec<LeagueStanding>, AppError> {
+    let members: Vec<(league_members::Model, Option<users::Model>)> = league_members::Entity::find()
+        .filter(league_members::Column::LeagueId.eq(league.id))
+        .find_also_related(users::Entity)
+        .all(db)
+        .await?;
+    let holdings = league_balances::Entity::find()
+        .filter(
+            league_balances::Column::MemberId
+                .is_in(members.iter().map(|(member, _)| member.id).collect::<Vec<_>>()),
+        )
+        .filter(league_balances::Column::Amount.gt(Decimal::ZERO))
+        .all(db)
+        .await?;
+
+    let mut prices: HashMap<i32, Decimal> = HashMap::new();
+    let mut assets: HashMap<i32, Decimal> = HashMap::new();
+    for holding in holdings {
+        let price = match prices.entry(holding.asset_id) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => *entry.insert(get_price_by_asset_id(cache, holding.asset_id).await?),
+        };
+        *assets.entry(holding.member_id).or_default() += holding.amount * price;
+    }
+
+    let hundred = Decimal::from(100);
+    let mut standings: Vec<LeagueStanding> = members
+        .into_iter()
+        .map(|(member, user)| {
+            let assets = assets.get(&member.id).copied().unwrap_or_default().round_dp(3);
+            let total = (member.balance + assets).round_dp(3);
+            LeagueStanding {
+                place: 0,
+                user_id: member.user_id,
+                username: user.map(|user| user.username).unwrap_or_default(),
+                cash: member.balance,
+                assets,
+                total,
+                return_percent: if league.starting_capital.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    ((total - league.starting_capital) / league.starting_capital * hundred).round_dp(2)
+                },
+            }
+        })
+        .collect();
+    standings.sort_by(|a, b| b.total.cmp(&a.total).then(a.user_id.cmp(&b.user_id)));
+    for (index, standing) in standings.iter_mut().enumerate() {
+        standing.place = index as u64 + 1;
+    }
+    Ok(standings)
+}
@@ -39,6 +39,7 @@ impl<T: Send + 'static + Clone + Debug> LimitedListWithTimeout<T>
         list.all().into_iter().cloned().collect()
     }
 
+    #[allow(dead_code)]
     pub async fn del(&mut self, item: &T) {
         let mut list = self.inner.lock().await;
         list.del(item)
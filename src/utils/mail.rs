@@ -0,0 +1,35 @@
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use std::error::Error;
+
+/// SMTP account the game sends mail from, `RECOVER_FROM` / `RECOVER_PASSWORD`.
+#[derive(Clone)]
+pub struct Mailer {
+    pub from: String,
+    pub password: String,
+}
+
+impl Mailer {
+    /// Blocking, call it from `spawn_blocking` outside of request handlers.
+    pub fn send_html(&self, to: &str, subject: &str, body: String) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let email = Message::builder()
+            .from(self.from.parse::<Mailbox>()?)
+            .to(to.parse::<Mailbox>()?)
+            .subject(subject)
+            .header(ContentType::TEXT_HTML)
+            .body(body)?;
+
+        let creds = Credentials::new(self.from.clone(), self.password.clone());
+
+        let mailer = SmtpTransport::relay("smtp.mail.ru")?
+            .credentials(creds)
+            .build();
+
+        match mailer.send(&email) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("Failed to send email: {:?}", email).into()),
+        }
+    }
+}
@@ -0,0 +1,408 @@
+use crate::structs::notification_structs::NotificationKind;
+use crate::structs::order_structs::{MarketMode, OrderType};
+use crate::structs::ws_structs::{BookEvent, Topic, TradeEvent};
+use crate::utils::app_error::AppError;
+use crate::utils::get_price::get_price_by_asset_id;
+use crate::utils::notifications::{notify, OrderNotification};
+use crate::utils::pubsub::publish_event;
+use crate::utils::take_commission::take_commission;
+use crate::{
+    COMMISSION_MARKET_BUY, COMMISSION_MARKET_SELL, COMMISSION_ORDER_BUY, COMMISSION_ORDER_SELL,
+};
+use chrono::Utc;
+use entity::{orders, trades, user_balances, users};
+use redis::Client;
+use sea_orm::prelude::Decimal;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbConn, EntityTrait, IntoActiveModel,
+    QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde::Serialize;
+
+pub struct MarketOrder {
+    pub user_id: i32,
+    pub asset_id: i32,
+    pub amount: Decimal,
+    pub mode: MarketMode,
+    /// Worst acceptable book level, in percent away from the house price
+    pub max_slippage: Option<Decimal>,
+    /// Worst acceptable average unit price, from a quote or the client's own limit
+    pub limit_price: Option<Decimal>,
+}
+
+#[derive(Serialize)]
+pub struct MarketFill {
+    /// Asset received for a buy, money received for a sell, after commission
+    pub amount: Decimal,
+    pub commission: Decimal,
+    pub balance: Decimal,
+    pub avg_price: Decimal,
+    /// Part of the order filled against other players' orders
+    pub book_amount: Decimal,
+}
+
+/// What one party of a trade got or gave: the asset amount that moved on its balance
+/// and the money that moved, both after commission.
+struct TradeLeg {
+    amount: Decimal,
+    total: Decimal,
+}
+
+struct BookFill {
+    order: orders::Model,
+    amount: Decimal,
+    value: Decimal,
+}
+
+pub async fn execute_market_order(
+    db: &DbConn,
+    cache: &Client,
+    side: OrderType,
+    order: MarketOrder,
+) -> Result<MarketFill, AppError> {
+    if !order.amount.is_sign_positive() || order.amount.is_zero() {
+        return Err(AppError::Validation("Wrong amount".into()));
+    }
+    let house_price = get_price_by_asset_id(cache, order.asset_id).await?;
+
+    let txn = db.begin().await?;
+    let user = users::Entity::find_by_id(order.user_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or(AppError::NotFound("Can't find user"))?;
+
+    let book = match order.mode {
+        MarketMode::House => Vec::new(),
+        MarketMode::Book => __walk_book(&txn, &side, &order, house_price).await?,
+    };
+    let book_amount: Decimal = book.iter().map(|fill| fill.amount).sum();
+    let book_value: Decimal = book.iter().map(|fill| fill.value).sum();
+    let house_amount = order.amount - book_amount;
+    let house_value = (house_price * house_amount).round_dp(3);
+    let total_value = book_value + house_value;
+    let avg_price = (total_value / order.amount).round_dp(3);
+    let price_moved = match (&side, order.limit_price) {
+        (OrderType::Buy, Some(limit)) => avg_price > limit,
+        (OrderType::Sell, Some(limit)) => avg_price < limit,
+        (_, None) => false,
+    };
+    if price_moved {
+        return Err(AppError::PriceMoved);
+    }
+
+    let mut received = Decimal::ZERO;
+    let mut commission = Decimal::ZERO;
+    let mut book_updates = Vec::with_capacity(book.len());
+    let mut trade_events = Vec::with_capacity(book.len() + 1);
+    let taker_type = match side {
+        OrderType::Buy => "buy",
+        OrderType::Sell => "sell",
+    };
+    let new_balance = match side {
+        OrderType::Buy => {
+            if user.balance < total_value {
+                return Err(AppError::InsufficientFunds);
+            }
+            for fill in &book {
+                let seller_share = take_commission(fill.value, *COMMISSION_ORDER_SELL);
+                __change_balance(&txn, fill.order.user_id, seller_share.amount).await?;
+                let taker_share = take_commission(fill.amount, *COMMISSION_MARKET_BUY);
+                received += taker_share.amount;
+                commission += taker_share.commission;
+                let maker = TradeLeg {
+                    amount: fill.amount,
+                    total: seller_share.amount,
+                };
+                let taker = TradeLeg {
+                    amount: taker_share.amount,
+                    total: fill.value,
+                };
+                book_updates.push(__record_fill(&txn, fill, order.user_id, maker, taker).await?);
+                trade_events.push(__trade_event(taker_type, (fill.value / fill.amount).round_dp(3), fill.amount));
+            }
+            if !house_amount.is_zero() {
+                let taker_share = take_commission(house_amount, *COMMISSION_MARKET_BUY);
+                received += taker_share.amount;
+                commission += taker_share.commission;
+                let taker = TradeLeg {
+                    amount: taker_share.amount,
+                    total: house_value,
+                };
+                __record_trade(&txn, order.user_id, order.asset_id, "buy", house_price, taker)
+                    .await?;
+                trade_events.push(__trade_event(taker_type, house_price, house_amount));
+            }
+            __change_asset_amount(&txn, order.user_id, order.asset_id, received).await?;
+            __set_balance(&txn, user, -total_value).await?
+        }
+        OrderType::Sell => {
+            let user_asset = user_balances::Entity::find()
+                .filter(user_balances::Column::UserId.eq(order.user_id))
+                .filter(user_balances::Column::AssetId.eq(order.asset_id))
+                .lock_exclusive()
+                .one(&txn)
+                .await?;
+            if user_asset.is_none_or(|asset| asset.amount < order.amount) {
+                return Err(AppError::InsufficientAssets);
+            }
+            for fill in &book {
+                let buyer_share = take_commission(fill.amount, *COMMISSION_ORDER_BUY);
+                __change_asset_amount(&txn, fill.order.user_id, order.asset_id, buyer_share.amount)
+                    .await?;
+                let taker_share = take_commission(fill.value, *COMMISSION_MARKET_SELL);
+                received += taker_share.amount;
+                commission += taker_share.commission;
+                let maker = TradeLeg {
+                    amount: buyer_share.amount,
+                    total: fill.value,
+                };
+                let taker = TradeLeg {
+                    amount: fill.amount,
+                    total: taker_share.amount,
+                };
+                book_updates.push(__record_fill(&txn, fill, order.user_id, maker, taker).await?);
+                trade_events.push(__trade_event(taker_type, (fill.value / fill.amount).round_dp(3), fill.amount));
+            }
+            if !house_amount.is_zero() {
+                let taker_share = take_commission(house_value, *COMMISSION_MARKET_SELL);
+                received += taker_share.amount;
+                commission += taker_share.commission;
+                let taker = TradeLeg {
+                    amount: house_amount,
+                    total: taker_share.amount,
+                };
+                __record_trade(&txn, order.user_id, order.asset_id, "sell", house_price, taker)
+                    .await?;
+                trade_events.push(__trade_event(taker_type, house_price, house_amount));
+            }
+            __change_asset_amount(&txn, order.user_id, order.asset_id, -order.amount).await?;
+            __set_balance(&txn, user, received).await?
+        }
+    };
+    txn.commit().await?;
+
+    for fill in &book {
+        let notification = OrderNotification::new(&fill.order, fill.amount);
+        notify(db, cache, fill.order.user_id, NotificationKind::OrderFilled, &notification).await;
+    }
+    for updated in &book_updates {
+        publish_event(cache, &Topic::Book(order.asset_id), &BookEvent::from(updated)).await;
+    }
+    for trade in &trade_events {
+        publish_event(cache, &Topic::Trades(order.asset_id), trade).await;
+    }
+
+    Ok(MarketFill {
+        amount: received.round_dp(3),
+        commission: commission.round_dp(3),
+        balance: new_balance,
+        avg_price,
+        book_amount,
+    })
+}
+
+/// Picks resting orders of other players from the best price to the worst, stopping at the
+/// slippage limit or once the requested amount is covered. Partially taken orders keep
+/// their unit price, so the remaining `price` shrinks together with `amount`.
+async fn __walk_book<C: ConnectionTrait>(
+    conn: &C,
+    side: &OrderType,
+    order: &MarketOrder,
+    house_price: Decimal,
+) -> Result<Vec<BookFill>, AppError> {
+    let resting_type = match side {
+        OrderType::Buy => "sell",
+        OrderType::Sell => "buy",
+    };
+    let mut levels = orders::Entity::find()
+        .filter(orders::Column::AssetId.eq(order.asset_id))
+        .filter(orders::Column::OrderType.eq(resting_type))
+        .filter(orders::Column::Status.eq("pending"))
+        .filter(orders::Column::UserId.ne(order.user_id))
+        .filter(orders::Column::Amount.gt(Decimal::ZERO))
+        .lock_exclusive()
+        .all(conn)
+        .await?;
+
+    let unit_price = |level: &orders::Model| level.price / level.amount;
+    match side {
+        OrderType::Buy => levels.sort_by_key(|level| (unit_price(level), level.created_at)),
+        OrderType::Sell => levels.sort_by_key(|level| (-unit_price(level), level.created_at)),
+    }
+
+    let limit = order.max_slippage.map(|slippage| {
+        let shift = house_price * slippage / Decimal::from(100);
+        match side {
+            OrderType::Buy => house_price + shift,
+            OrderType::Sell => house_price - shift,
+        }
+    });
+
+    let mut remaining = order.amount;
+    let mut fills = Vec::new();
+    for level in levels {
+        if remaining.is_zero() {
+            break;
+        }
+        let price = unit_price(&level);
+        let beyond_limit = match (side, limit) {
+            (OrderType::Buy, Some(limit)) => price > limit,
+            (OrderType::Sell, Some(limit)) => price < limit,
+            (_, None) => false,
+        };
+        if beyond_limit {
+            break;
+        }
+
+        let amount = remaining.min(level.amount);
+        let value = if amount == level.amount {
+            level.price
+        } else {
+            (price * amount).round_dp(3)
+        };
+        remaining -= amount;
+        fills.push(BookFill {
+            order: level,
+            amount,
+            value,
+        });
+    }
+    Ok(fills)
+}
+
+async fn __record_fill<C: ConnectionTrait>(
+    conn: &C,
+    fill: &BookFill,
+    taker_id: i32,
+    maker: TradeLeg,
+    taker: TradeLeg,
+) -> Result<orders::Model, AppError> {
+    let price = (fill.value / fill.amount).round_dp(3);
+    let (maker_type, taker_type) = match fill.order.order_type.as_str() {
+        "sell" => ("sell", "buy"),
+        _ => ("buy", "sell"),
+    };
+    __record_trade(
+        conn,
+        fill.order.user_id,
+        fill.order.asset_id,
+        maker_type,
+        price,
+        maker,
+    )
+    .await?;
+    __record_trade(
+        conn,
+        taker_id,
+        fill.order.asset_id,
+        taker_type,
+        price,
+        taker,
+    )
+    .await?;
+
+    let remaining_amount = fill.order.amount - fill.amount;
+    let remaining_price = fill.order.price - fill.value;
+    let mut active_order = fill.order.clone().into_active_model();
+    if remaining_amount.is_zero() {
+        active_order.status = Set("done".into());
+    } else {
+        active_order.amount = Set(remaining_amount);
+        active_order.price = Set(remaining_price);
+    }
+    active_order.updated_at = Set(Utc::now().naive_utc());
+    Ok(active_order.update(conn).await?)
+}
+
+fn __trade_event(trade_type: &str, price: Decimal, amount: Decimal) -> TradeEvent {
+    TradeEvent {
+        trade_type: trade_type.to_string(),
+        price,
+        amount,
+        created_at: Utc::now().naive_utc(),
+    }
+}
+
+async fn __record_trade<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    asset_id: i32,
+    trade_type: &str,
+    price: Decimal,
+    leg: TradeLeg,
+) -> Result<(), AppError> {
+    trades::ActiveModel {
+        user_id: Set(user_id),
+        asset_id: Set(asset_id),
+        trade_type: Set(trade_type.into()),
+        price: Set(price),
+        amount: Set(leg.amount),
+        total: Set(Some(leg.total.round_dp(3))),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
+
+async fn __set_balance<C: ConnectionTrait>(
+    conn: &C,
+    user: users::Model,
+    delta: Decimal,
+) -> Result<Decimal, AppError> {
+    let new_balance = (user.balance + delta).round_dp(3);
+    let mut active_user = user.into_active_model();
+    active_user.balance = Set(new_balance);
+    active_user.update(conn).await?;
+    Ok(new_balance)
+}
+
+pub(crate) async fn __change_balance<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    delta: Decimal,
+) -> Result<Decimal, AppError> {
+    let user = users::Entity::find_by_id(user_id)
+        .lock_exclusive()
+        .one(conn)
+        .await?
+        .ok_or(AppError::Internal(format!("No user {user_id}")))?;
+    __set_balance(conn, user, delta).await
+}
+
+pub(crate) async fn __change_asset_amount<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    asset_id: i32,
+    delta: Decimal,
+) -> Result<Decimal, AppError> {
+    let user_asset = user_balances::Entity::find()
+        .filter(user_balances::Column::UserId.eq(user_id))
+        .filter(user_balances::Column::AssetId.eq(asset_id))
+        .lock_exclusive()
+        .one(conn)
+        .await?;
+
+    match user_asset {
+        Some(user_asset) => {
+            let new_amount = (user_asset.amount + delta).round_dp(3);
+            let mut active_user_asset = user_asset.into_active_model();
+            active_user_asset.amount = Set(new_amount);
+            active_user_asset.update(conn).await?;
+            Ok(new_amount)
+        }
+        None => {
+            let new_amount = delta.round_dp(3);
+            user_balances::ActiveModel {
+                user_id: Set(user_id),
+                asset_id: Set(asset_id),
+                amount: Set(new_amount),
+                ..Default::default()
+            }
+            .insert(conn)
+            .await?;
+            Ok(new_amount)
+        }
+    }
+}
@@ -1,11 +1,31 @@
 pub mod establish_connection;
 pub mod jwt;
+pub mod admin;
 pub mod response;
+pub mod app_error;
 pub mod init_assets;
 pub mod seed_assets;
 pub mod price_calculation;
+pub mod price_broadcaster;
 pub mod prices_snapshot;
 pub mod get_price;
 pub mod take_commission;
+pub mod market_execution;
+pub mod quote;
+pub mod net_worth;
+pub mod net_worth_snapshot;
+pub mod leaderboard;
+pub mod seasons;
+pub mod leagues;
+pub mod chat_rooms;
+pub mod chat_filter;
+pub mod moderation;
+pub mod attachments;
+pub mod achievements;
+pub mod mail;
+pub mod alerts;
+pub mod notifications;
+pub mod pubsub;
+pub mod portfolio;
 pub mod limited_list;
-pub mod limited_list_with_timeout;
\ No newline at end of file
+pub mod limited_list_with_timeout;
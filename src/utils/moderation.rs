@@ -0,0 +1,84 @@
+use crate::structs::moderation_structs::SanctionKind;
+use crate::utils::app_error::AppError;
+use chrono::Utc;
+use entity::{chat_sanctions, user_blocks};
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use std::collections::HashSet;
+
+/// Sanctions that have not expired yet, of one user or of everyone.
+pub async fn active_sanctions<C: ConnectionTrait>(
+    conn: &C,
+    user_id: Option<i32>,
+) -> Result<Vec<chat_sanctions::Model>, AppError> {
+    let mut select = chat_sanctions::Entity::find().filter(
+        Condition::any()
+            .add(chat_sanctions::Column::ExpiresAt.is_null())
+            .add(chat_sanctions::Column::ExpiresAt.gt(Utc::now().naive_utc())),
+    );
+    if let Some(user_id) = user_id {
+        select = select.filter(chat_sanctions::Column::UserId.eq(user_id));
+    }
+    Ok(select
+        .order_by_desc(chat_sanctions::Column::Id)
+        .all(conn)
+        .await?)
+}
+
+/// Refuses banned users. Muted users are refused only when `sending`.
+pub async fn check_chat_allowed<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    sending: bool,
+) -> Result<(), AppError> {
+    let sanctions = active_sanctions(conn, Some(user_id)).await?;
+    if sanctions.iter().any(|s| s.kind == SanctionKind::Ban.name()) {
+        return Err(AppError::Forbidden("Banned from chat"));
+    }
+    if sending && sanctions.iter().any(|s| s.kind == SanctionKind::Mute.name()) {
+        return Err(AppError::Forbidden("Muted in chat"));
+    }
+    Ok(())
+}
+
+/// Private messages are refused when either side blocked the other.
+pub async fn check_not_blocked<C: ConnectionTrait>(
+    conn: &C,
+    from_id: i32,
+    recipient_id: i32,
+) -> Result<(), AppError> {
+    let blocks = user_blocks::Entity::find()
+        .filter(
+            Condition::any()
+                .add(
+                    Condition::all()
+                        .add(user_blocks::Column::UserId.eq(recipient_id))
+                        .add(user_blocks::Column::BlockedId.eq(from_id)),
+                )
+                .add(
+                    Condition::all()
+                        .add(user_blocks::Column::UserId.eq(from_id))
+                        .add(user_blocks::Column::BlockedId.eq(recipient_id)),
+                ),
+        )
+        .all(conn)
+        .await?;
+    match blocks.first() {
+        Some(block) if block.user_id == recipient_id => Err(AppError::Forbidden("Blocked by this user")),
+        Some(_) => Err(AppError::Forbidden("Unblock this user first")),
+        None => Ok(()),
+    }
+}
+
+/// Users who blocked `user_id`, they don't get its league and room messages.
+pub async fn blocked_by<C: ConnectionTrait>(conn: &C, user_id: i32) -> Result<HashSet<i32>, AppError> {
+    let user_ids: Vec<i32> = user_blocks::Entity::find()
+        .filter(user_blocks::Column::BlockedId.eq(user_id))
+        .select_only()
+        .column(user_blocks::Column::UserId)
+        .into_tuple()
+        .all(conn)
+        .await?;
+    Ok(user_ids.into_iter().collect())
+}
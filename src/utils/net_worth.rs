@@ -0,0 +1,147 @@
+use crate::utils::app_error::AppError;
+use crate::utils::get_price::get_price_by_asset_id;
+use entity::{orders, user_balances, users};
+use redis::Client;
+use sea_orm::prelude::Decimal;
+use sea_orm::{ColumnTrait, ConnectionTrait, DbConn, EntityTrait, QueryFilter};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct NetWorth {
+    pub cash: Decimal,
+    /// Money and assets locked in pending orders, at the current price
+    pub in_orders: Decimal,
+    pub assets: Decimal,
+    pub total: Decimal,
+}
+
+/// Values everything the user owns at the current Redis prices.
+pub async fn net_worth(db: &DbConn, cache: &Client, user_id: i32) -> Result<NetWorth, AppError> {
+    let user = users::Entity::find_by_id(user_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("No user"))?;
+    let balances = user_balances::Entity::find()
+        .filter(user_balances::Column::UserId.eq(user_id))
+        .all(db)
+        .await?;
+    let pending = orders::Entity::find()
+        .filter(orders::Column::UserId.eq(user_id))
+        .filter(orders::Column::Status.eq("pending"))
+        .all(db)
+        .await?;
+
+    let mut prices: HashMap<i32, Decimal> = HashMap::new();
+    __load_prices(cache, &mut prices, &balances, &pending).await?;
+
+    Ok(__value(user.balance, &balances, &pending, &prices))
+}
+
+/// Same valuation as [`net_worth`] for every user at once, with a single pass over the tables.
+pub async fn net_worth_all<C: ConnectionTrait>(db: &C, cache: &Client) -> Result<Vec<(users::Model, NetWorth)>, AppError> {
+    let users = users::Entity::find().all(db).await?;
+    let balances = user_balances::Entity::find().all(db).await?;
+    let pending = orders::Entity::find()
+        .filter(orders::Column::Status.eq("pending"))
+        .all(db)
+        .await?;
+
+    let mut prices: HashMap<i32, Decimal> = HashMap::new();
+    __load_prices(cache, &mut prices, &balances, &pending).await?;
+
+    let mut balances_by_user: HashMap<i32, Vec<user_balances::Model>> = HashMap::new();
+    for balance in balances {
+        balances_by_user.entry(balance.user_id).or_default().push(balance);
+    }
+    let mut pending_by_user: HashMap<i32, Vec<orders::Model>> = HashMap::new();
+    for order in pending {
+        pending_by_user.entry(order.user_id).or_default().push(order);
+    }
+
+    Ok(users
+        .into_iter()
+        .map(|user| {
+            let worth = __value(
+                user.balance,
+                balances_by_user.get(&user.id).map(Vec::as_slice).unwrap_or_default(),
+                pending_by_user.get(&user.id).map(Vec::as_slice).unwrap_or_default(),
+                &prices,
+            );
+            (user, worth)
+        })
+        .collect())
+}
+
+/// Sorts by net worth and assigns places to non-bot users, equal totals share a place.
+pub fn ranked(mut worths: Vec<(users::Model, NetWorth)>) -> Vec<(users::Model, NetWorth, Option<i32>)> {
+    worths.sort_by_key(|(_, worth)| Reverse(worth.total));
+
+    let mut place = 0;
+    let mut ranked = 0;
+    let mut previous_total = None;
+    worths
+        .into_iter()
+        .map(|(user, worth)| {
+            let user_place = if user.is_bot {
+                None
+            } else {
+                ranked += 1;
+                if previous_total != Some(worth.total) {
+                    place = ranked;
+                    previous_total = Some(worth.total);
+                }
+                Some(place)
+            };
+            (user, worth, user_place)
+        })
+        .collect()
+}
+
+async fn __load_prices(
+    cache: &Client,
+    prices: &mut HashMap<i32, Decimal>,
+    balances: &[user_balances::Model],
+    pending: &[orders::Model],
+) -> Result<(), AppError> {
+    for asset_id in balances
+        .iter()
+        .filter(|balance| !balance.amount.is_zero())
+        .map(|balance| balance.asset_id)
+        .chain(pending.iter().map(|order| order.asset_id))
+    {
+        if let Entry::Vacant(entry) = prices.entry(asset_id) {
+            entry.insert(get_price_by_asset_id(cache, asset_id).await?);
+        }
+    }
+    Ok(())
+}
+
+fn __value(
+    cash: Decimal,
+    balances: &[user_balances::Model],
+    pending: &[orders::Model],
+    prices: &HashMap<i32, Decimal>,
+) -> NetWorth {
+    let assets: Decimal = balances
+        .iter()
+        .filter(|balance| !balance.amount.is_zero())
+        .map(|balance| balance.amount * prices[&balance.asset_id])
+        .sum();
+    let in_orders: Decimal = pending
+        .iter()
+        .map(|order| match order.order_type.as_str() {
+            "buy" => order.price,
+            _ => order.amount * prices[&order.asset_id],
+        })
+        .sum();
+
+    NetWorth {
+        cash,
+        in_orders: in_orders.round_dp(3),
+        assets: assets.round_dp(3),
+        total: (cash + in_orders + assets).round_dp(3),
+    }
+}
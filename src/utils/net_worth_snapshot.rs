@@ -0,0 +1,73 @@
+use crate::utils::net_worth::{net_worth_all, ranked};
+use chrono::Utc;
+use entity::net_worth_snapshots;
+use sea_orm::{ColumnTrait, DbConn, DbErr, EntityTrait, QueryFilter, Set};
+use std::error::Error;
+use std::time::Duration;
+use tokio::time::interval;
+
+pub fn net_worth_retention_days() -> i64 {
+    std::env::var("NET_WORTH_RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(365)
+}
+
+pub async fn save_net_worth_to_db(db: DbConn, redis_client: redis::Client, n: u64) {
+    let mut interval = interval(Duration::from_secs(n));
+    loop {
+        interval.tick().await;
+        if let Err(err) = save_net_worth_executor(&db, &redis_client).await {
+            eprintln!("Error saving net worth snapshots: {err}");
+        }
+    }
+}
+
+/// Stores every user's net worth. `place` ranks non-bot users by total net worth, the
+/// leaderboard shows the latest one as `previous_place`.
+pub async fn save_net_worth_executor(db: &DbConn, redis_client: &redis::Client) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let worths = net_worth_all(db, redis_client)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let snapshots: Vec<net_worth_snapshots::ActiveModel> = ranked(worths)
+        .into_iter()
+        .map(|(user, worth, place)| net_worth_snapshots::ActiveModel {
+            user_id: Set(user.id),
+            cash: Set(worth.cash),
+            in_orders: Set(worth.in_orders),
+            assets: Set(worth.assets),
+            total: Set(worth.total),
+            place: Set(place),
+            ..Default::default()
+        })
+        .collect();
+    // Keeps each insert well under the Postgres bind parameter limit
+    for chunk in snapshots.chunks(1000) {
+        net_worth_snapshots::Entity::insert_many(chunk.to_vec())
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn prune_net_worth(db: DbConn, n: u64) {
+    let mut interval = interval(Duration::from_secs(n));
+    loop {
+        interval.tick().await;
+        if let Err(err) = prune_net_worth_executor(&db).await {
+            eprintln!("Error pruning net worth snapshots: {err}");
+        }
+    }
+}
+
+/// Drops snapshots older than the retention period.
+pub async fn prune_net_worth_executor(db: &DbConn) -> Result<(), DbErr> {
+    let cutoff = Utc::now().naive_utc() - chrono::Duration::days(net_worth_retention_days());
+    net_worth_snapshots::Entity::delete_many()
+        .filter(net_worth_snapshots::Column::CreatedAt.lt(cutoff))
+        .exec(db)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::routes::notifications::push;
+use crate::structs::notification_structs::NotificationKind;
+use entity::{notifications, orders};
+use sea_orm::prelude::{DateTime, Decimal};
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DatabaseBackend, DbConn, DbErr, FromQueryResult, Set,
+    Statement,
+};
+use redis::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::time::interval;
+
+pub fn notifications_retention_days() -> i64 {
+    std::env::var("NOTIFICATIONS_RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(30)
+}
+
+pub fn notifications_per_user() -> i64 {
+    std::env::var("NOTIFICATIONS_PER_USER")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(500)
+}
+
+/// What the notification socket sends and `/api/v1/notifications` returns.
+#[derive(Serialize, Clone, Debug)]
+pub struct Notification {
+    pub id: i32,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub data: Value,
+    pub read: bool,
+    pub created_at: DateTime,
+}
+
+impl From<notifications::Model> for Notification {
+    fn from(notification: notifications::Model) -> Self {
+        Self {
+            id: notification.id,
+            kind: notification.kind,
+            data: notification.payload,
+            read: notification.read,
+            created_at: notification.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct OrderNotification {
+    pub order_id: i32,
+    pub asset_id: i32,
+    pub order_type: String,
+    /// Amount traded against the order this time
+    pub amount: Decimal,
+    /// Amount still resting in the book
+    pub remaining: Decimal,
+}
+
+impl OrderNotification {
+    pub fn new(order: &orders::Model, amount: Decimal) -> Self {
+        Self {
+            order_id: order.id,
+            asset_id: order.asset_id,
+            order_type: order.order_type.clone(),
+            amount,
+            remaining: order.amount - amount,
+        }
+    }
+}
+
+#[derive(FromQueryResult)]
+struct InsertedNotification {
+    id: i32,
+    user_id: i32,
+    created_at: DateTime,
+}
+
+/// Stores the notification as unread and pushes it to the user's socket. Delivery problems
+/// are logged, they never fail the action that caused the notification.
+pub async fn notify<C: ConnectionTrait, T: Serialize>(
+    db: &C,
+    cache: &Client,
+    user_id: i32,
+    kind: NotificationKind,
+    data: &T,
+) {
+    let payload = serde_json::to_value(data).unwrap_or_default();
+    let notification = notifications::ActiveModel {
+        user_id: Set(user_id),
+        kind: Set(kind.name().to_string()),
+        payload: Set(payload),
+        ..Default::default()
+    }
+    .insert(db)
+    .await;
+    match notification {
+        Ok(notification) => push(cache, user_id, &Notification::from(notification)).await,
+        Err(err) => eprintln!("Failed to store notification: {err}"),
+    }
+}
+
+/// Same as [`notify`] for every player at once.
+pub async fn notify_players<C: ConnectionTrait, T: Serialize>(
+    db: &C,
+    cache: &Client,
+    kind: NotificationKind,
+    data: &T,
+) {
+    let payload = serde_json::to_value(data).unwrap_or_default();
+    let inserted = InsertedNotification::find_by_statement(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "INSERT INTO notifications (user_id, kind, payload) SELECT id, $1, $2 FROM users WHERE is_bot = FALSE RETURNING id, user_id, created_at",
+        [kind.name().into(), payload.clone().into()],
+    ))
+    .all(db)
+    .await;
+    match inserted {
+        Ok(inserted) => {
+            for row in inserted {
+                let notification = Notification {
+                    id: row.id,
+                    kind: kind.name().to_string(),
+                    data: payload.clone(),
+                    read: false,
+                    created_at: row.created_at,
+                };
+                push(cache, row.user_id, &notification).await;
+            }
+        }
+        Err(err) => eprintln!("Failed to store notifications: {err}"),
+    }
+}
+
+pub async fn prune_notifications(db: DbConn, n: u64) {
+    let mut interval = interval(Duration::from_secs(n));
+    loop {
+        interval.tick().await;
+        if let Err(err) = prune_notifications_executor(&db).await {
+            eprintln!("Error pruning notifications: {err}");
+        }
+    }
+}
+
+/// Drops notifications older than the retention period and everything past the newest
+/// `NOTIFICATIONS_PER_USER` of each user, read or not.
+pub async fn prune_notifications_executor(db: &DbConn) -> Result<(), DbErr> {
+    db.execute(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "DELETE FROM notifications WHERE created_at < NOW() - make_interval(days => $1)",
+        [(notifications_retention_days() as i32).into()],
+    ))
+    .await?;
+    db.execute(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "DELETE FROM notifications WHERE id IN (SELECT id FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY id DESC) AS position FROM notifications) ranked WHERE position > $1)",
+        [notifications_per_user().into()],
+    ))
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use crate::utils::app_error::AppError;
+use crate::utils::get_price::get_price_by_asset_id;
+use crate::utils::net_worth::net_worth;
+use crate::utils::seasons::current_season;
+use entity::{assets, orders, trades, user_balances};
+use redis::Client;
+use sea_orm::prelude::Decimal;
+use sea_orm::{ColumnTrait, DbConn, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Cash every account starts with, the `users.balance` default in the first migration.
+pub const STARTING_BALANCE: i64 = 10_000;
+
+#[derive(Serialize)]
+pub struct PositionInfo {
+    pub asset_id: i32,
+    pub symbol: String,
+    /// Free amount plus the amount locked in pending sell orders
+    pub amount: Decimal,
+    /// Average price paid per unit, commission included
+    pub avg_cost: Decimal,
+    pub cost_basis: Decimal,
+    pub price: Decimal,
+    pub market_value: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub realized_pnl: Decimal,
+    /// Percent of the total net worth
+    pub allocation: Decimal,
+}
+
+#[derive(Serialize)]
+pub struct PortfolioInfo {
+    pub cash: Decimal,
+    pub cash_in_orders: Decimal,
+    pub cash_allocation: Decimal,
+    pub positions: Vec<PositionInfo>,
+    pub net_worth: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub realized_pnl: Decimal,
+    /// Percent against the starting balance
+    pub total_return: Decimal,
+}
+
+#[derive(Default)]
+struct CostBasis {
+    amount: Decimal,
+    cost: Decimal,
+    realized: Decimal,
+}
+
+/// Replays the user's trades with the average cost method. `trades.total` holds the money
+/// that actually moved; older rows were backfilled by a migration, `price * amount` is only
+/// a fallback.
+fn __cost_basis(trades: &[trades::Model]) -> BTreeMap<i32, CostBasis> {
+    let mut basis: BTreeMap<i32, CostBasis> = BTreeMap::new();
+    for trade in trades {
+        let total = trade.total.unwrap_or(trade.price * trade.amount);
+        let position = basis.entry(trade.asset_id).or_default();
+        match trade.trade_type.as_str() {
+            "buy" => {
+                position.amount += trade.amount;
+                position.cost += total;
+            }
+            _ => {
+                let sold = trade.amount.min(position.amount);
+                let avg_cost = if position.amount.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    position.cost / position.amount
+                };
+                // Only the part of the sale that was in the tracked position counts
+                let proceeds = if trade.amount.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    total * sold / trade.amount
+                };
+                position.realized += proceeds - avg_cost * sold;
+                position.cost -= avg_cost * sold;
+                position.amount -= sold;
+            }
+        }
+    }
+    basis
+}
+
+fn __percent(part: Decimal, total: Decimal) -> Decimal {
+    if total.is_zero() {
+        Decimal::ZERO
+    } else {
+        (part / total * Decimal::from(100)).round_dp(2)
+    }
+}
+
+pub async fn portfolio(db: &DbConn, cache: &Client, user_id: i32) -> Result<PortfolioInfo, AppError> {
+    let worth = net_worth(db, cache, user_id).await?;
+    // Holdings are wiped between seasons, earlier trades no longer describe the position
+    let season_start = current_season(db)
+        .await?
+        .map(|season| season.starts_at)
+        .unwrap_or_default();
+    let trades = trades::Entity::find()
+        .filter(trades::Column::UserId.eq(user_id))
+        .filter(trades::Column::CreatedAt.gte(season_start))
+        .order_by_asc(trades::Column::CreatedAt)
+        .order_by_asc(trades::Column::Id)
+        .all(db)
+        .await?;
+    let balances = user_balances::Entity::find()
+        .filter(user_balances::Column::UserId.eq(user_id))
+        .all(db)
+        .await?;
+    let pending = orders::Entity::find()
+        .filter(orders::Column::UserId.eq(user_id))
+        .filter(orders::Column::Status.eq("pending"))
+        .all(db)
+        .await?;
+    let symbols: BTreeMap<i32, String> = assets::Entity::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|asset| (asset.id, asset.symbol))
+        .collect();
+
+    let mut amounts: BTreeMap<i32, Decimal> = BTreeMap::new();
+    for balance in &balances {
+        *amounts.entry(balance.asset_id).or_default() += balance.amount;
+    }
+    for order in pending.iter().filter(|order| order.order_type == "sell") {
+        *amounts.entry(order.asset_id).or_default() += order.amount;
+    }
+    let cash_in_orders: Decimal = pending
+        .iter()
+        .filter(|order| order.order_type == "buy")
+        .map(|order| order.price)
+        .sum();
+
+    let mut basis = __cost_basis(&trades);
+    let mut positions = Vec::new();
+    for (asset_id, amount) in amounts {
+        let position = basis.remove(&asset_id).unwrap_or_default();
+        if amount.is_zero() && position.realized.is_zero() {
+            continue;
+        }
+        let avg_cost = if position.amount.is_zero() {
+            Decimal::ZERO
+        } else {
+            position.cost / position.amount
+        };
+        let price = if amount.is_zero() {
+            Decimal::ZERO
+        } else {
+            get_price_by_asset_id(cache, asset_id).await?
+        };
+        let cost_basis = (avg_cost * amount).round_dp(3);
+        let market_value = (price * amount).round_dp(3);
+        positions.push(PositionInfo {
+            asset_id,
+            symbol: symbols.get(&asset_id).cloned().unwrap_or_default(),
+            amount,
+            avg_cost: avg_cost.round_dp(3),
+            cost_basis,
+            price,
+            market_value,
+            unrealized_pnl: market_value - cost_basis,
+            realized_pnl: position.realized.round_dp(3),
+            allocation: __percent(market_value, worth.total),
+        });
+    }
+    // Fully closed positions only contribute realized P&L
+    for (asset_id, position) in basis {
+        if position.realized.is_zero() {
+            continue;
+        }
+        positions.push(PositionInfo {
+            asset_id,
+            symbol: symbols.get(&asset_id).cloned().unwrap_or_default(),
+            amount: Decimal::ZERO,
+            avg_cost: Decimal::ZERO,
+            cost_basis: Decimal::ZERO,
+            price: Decimal::ZERO,
+            market_value: Decimal::ZERO,
+            unrealized_pnl: Decimal::ZERO,
+            realized_pnl: position.realized.round_dp(3),
+            allocation: Decimal::ZERO,
+        });
+    }
+
+    let starting_balance = Decimal::from(STARTING_BALANCE);
+    Ok(PortfolioInfo {
+        cash: worth.cash,
+        cash_in_orders,
+        cash_allocation: __percent(worth.cash + cash_in_orders, worth.total),
+        unrealized_pnl: positions.iter().map(|position| position.unrealized_pnl).sum(),
+        realized_pnl: positions.iter().map(|position| position.realized_pnl).sum(),
+        positions,
+        net_worth: worth.total,
+        total_return: __percent(worth.total - starting_balance, starting_balance),
+    })
+}
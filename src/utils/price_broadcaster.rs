@@ -0,0 +1,268 @@
+use crate::routes::market::WebSocketMessage;
+use crate::utils::price_calculation::PriceTick;
+use crate::structs::ws_structs::Topic;
+use crate::utils::pubsub::{publish, publish_event, Envelope, MARKET_CHANNEL};
+use actix::prelude::*;
+use chrono::Utc;
+use entity::assets;
+use rand_core::{OsRng, RngCore};
+use lazy_static::lazy_static;
+use redis::{Client, Script};
+use sea_orm::prelude::Decimal;
+use sea_orm::{DbConn, EntityTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MarketData {
+    pub symbol: String,
+    pub name: String,
+    pub price: Decimal,
+    pub trend: String,
+    pub change_percent: Decimal,
+}
+
+const MARKET_LEADER_KEY: &str = "market_data:leader";
+const MARKET_LEADER_TTL_MS: i64 = 30_000;
+
+lazy_static! {
+    // Checking the holder and extending must be one step, otherwise the lock can expire
+    // and go to another instance in between and both would publish
+    static ref LEADER_LOCK: Script = Script::new(
+        r#"
+        if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+            return 1
+        end
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            redis.call('PEXPIRE', KEYS[1], ARGV[2])
+            return 1
+        end
+        return 0
+        "#
+    );
+}
+
+/// Market data of every asset, keyed by asset id.
+pub type MarketSnapshot = HashMap<String, MarketData>;
+
+/// Keeps the latest market snapshot and pushes every new one to the market sockets of this
+/// instance, each filtered down to the symbols the socket asked for.
+#[derive(Default)]
+pub struct PriceBroadcaster {
+    subscribers: HashMap<Recipient<WebSocketMessage>, Option<HashSet<String>>>,
+    snapshot: Option<MarketSnapshot>,
+}
+
+impl PriceBroadcaster {
+    /// Runs the broadcaster in its own actix system, the server itself is started from a
+    /// plain tokio runtime.
+    pub fn spawn() -> Addr<Self> {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let system = System::new();
+            system.block_on(async {
+                let _ = sender.send(PriceBroadcaster::default().start());
+            });
+            let _ = system.run();
+        });
+        receiver.recv().expect("Price broadcaster failed to start")
+    }
+
+    fn __send(recipient: &Recipient<WebSocketMessage>, symbols: &Option<HashSet<String>>, snapshot: &MarketSnapshot) {
+        let message = match symbols {
+            None => serde_json::to_string(snapshot),
+            Some(symbols) => serde_json::to_string(
+                &snapshot
+                    .iter()
+                    .filter(|(_, data)| symbols.contains(&data.symbol))
+                    .collect::<HashMap<_, _>>(),
+            ),
+        };
+        recipient.do_send(WebSocketMessage {
+            message: message.unwrap_or_default(),
+        });
+    }
+}
+
+impl Actor for PriceBroadcaster {
+    type Context = Context<Self>;
+}
+
+/// Starts or updates a subscription. `symbols: None` means every asset.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Subscribe {
+    pub recipient: Recipient<WebSocketMessage>,
+    pub symbols: Option<HashSet<String>>,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Unsubscribe {
+    pub recipient: Recipient<WebSocketMessage>,
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Broadcast {
+    pub snapshot: MarketSnapshot,
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<MarketSnapshot>")]
+pub struct GetSnapshot;
+
+impl Handler<Subscribe> for PriceBroadcaster {
+    type Result = ();
+
+    fn handle(&mut self, msg: Subscribe, _ctx: &mut Self::Context) {
+        if let Some(snapshot) = &self.snapshot {
+            Self::__send(&msg.recipient, &msg.symbols, snapshot);
+        }
+        self.subscribers.insert(msg.recipient, msg.symbols);
+    }
+}
+
+impl Handler<Unsubscribe> for PriceBroadcaster {
+    type Result = ();
+
+    fn handle(&mut self, msg: Unsubscribe, _ctx: &mut Self::Context) {
+        self.subscribers.remove(&msg.recipient);
+    }
+}
+
+impl Handler<Broadcast> for PriceBroadcaster {
+    type Result = ();
+
+    fn handle(&mut self, msg: Broadcast, _ctx: &mut Self::Context) {
+        self.subscribers.retain(|recipient, _| recipient.connected());
+        let full = serde_json::to_string(&msg.snapshot).unwrap_or_default();
+        for (recipient, symbols) in &self.subscribers {
+            match symbols {
+                None => recipient.do_send(WebSocketMessage {
+                    message: full.clone(),
+                }),
+                Some(_) => Self::__send(recipient, symbols, &msg.snapshot),
+            }
+        }
+        self.snapshot = Some(msg.snapshot);
+    }
+}
+
+impl Handler<GetSnapshot> for PriceBroadcaster {
+    type Result = Option<MarketSnapshot>;
+
+    fn handle(&mut self, _msg: GetSnapshot, _ctx: &mut Self::Context) -> Self::Result {
+        self.snapshot.clone()
+    }
+}
+
+/// Builds the market snapshot once per price tick, right after the prices are recalculated,
+/// and publishes it to the market sockets of every instance. Only the instance holding
+/// the leader lock publishes, the others get the snapshot through the relay like everyone.
+pub async fn publish_market_data(db: Arc<DbConn>, cache: Arc<Client>, mut ticks: broadcast::Receiver<PriceTick>) {
+    let instance_id = OsRng.next_u64().to_string();
+    loop {
+        match ticks.recv().await {
+            Ok(_) => {}
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        }
+        match __hold_leader_lock(cache.as_ref(), &instance_id).await {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                eprintln!("Error taking the market data lock: {err}");
+                continue;
+            }
+        }
+        match market_snapshot(db.clone(), cache.clone()).await {
+            Ok(snapshot) => {
+                publish_event(cache.as_ref(), &Topic::Prices, &snapshot).await;
+                let envelope = Envelope {
+                    user_ids: Vec::new(),
+                    payload: serde_json::to_string(&snapshot).unwrap_or_default(),
+                };
+                publish(cache.as_ref(), MARKET_CHANNEL, envelope).await;
+            }
+            Err(err) => eprintln!("Error building market data: {err}"),
+        }
+    }
+}
+
+/// Takes the lock or extends it when this instance already holds it. The lock outlives a
+/// few ticks, so another instance takes over soon after the leader stops.
+async fn __hold_leader_lock(cache: &Client, instance_id: &str) -> redis::RedisResult<bool> {
+    let mut redis_conn = cache.get_multiplexed_async_connection().await?;
+    LEADER_LOCK
+        .key(MARKET_LEADER_KEY)
+        .arg(instance_id)
+        .arg(MARKET_LEADER_TTL_MS)
+        .invoke_async(&mut redis_conn)
+        .await
+}
+
+pub async fn market_snapshot(
+    db: Arc<DbConn>,
+    cache: Arc<Client>,
+) -> Result<MarketSnapshot, Box<dyn Error + Sync + Send>> {
+    let assets = assets::Entity::find().all(db.as_ref()).await?;
+    if assets.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let now = Utc::now().timestamp();
+    let day_ago = now - 3600;
+    let mut pipe = redis::pipe();
+    for asset in &assets {
+        pipe.zrevrangebyscore_withscores(format!("asset_price_history:{}", asset.id), now, day_ago);
+    }
+    let mut redis_conn = cache.get_multiplexed_async_connection().await?;
+    let histories: Vec<Vec<(String, f64)>> = pipe.query_async(&mut redis_conn).await?;
+
+    let mut result = HashMap::new();
+    for (asset, prices) in assets.into_iter().zip(histories) {
+        // Assets without prices in the last hour are left out until they get one
+        let (Some((first_price_data, _)), Some((last_price_data, _))) = (prices.last(), prices.first()) else {
+            continue;
+        };
+        let (first_price_data, _) = first_price_data.split_once(":").unwrap_or_default();
+        let (last_price_data, _) = last_price_data.split_once(":").unwrap_or_default();
+
+        let first_price = Decimal::from_str(first_price_data).unwrap_or(Decimal::ZERO);
+        let last_price = Decimal::from_str(last_price_data).unwrap_or(Decimal::ZERO);
+
+        let change = if first_price != Decimal::ZERO {
+            (((last_price - first_price) / first_price) * Decimal::from(100)).abs()
+        } else {
+            Decimal::ZERO
+        };
+
+        let trend = if last_price > first_price {
+            "up"
+        } else if last_price < first_price {
+            "down"
+        } else {
+            "unchanged"
+        };
+
+        result.insert(
+            asset.id.to_string(),
+            MarketData {
+                symbol: asset.symbol,
+                name: asset.name,
+                price: last_price.round_dp(3),
+                trend: trend.to_string(),
+                change_percent: change.round_dp(2),
+            },
+        );
+    }
+    Ok(result)
+}
@@ -6,8 +6,10 @@ use lazy_static::lazy_static;
 use redis::AsyncCommands;
 use sea_orm::prelude::Decimal;
 use sea_orm::{ColumnTrait, DbConn, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::HashMap;
 use std::error::Error;
 use std::str::FromStr;
+use tokio::sync::broadcast;
 use tokio::time::{interval, Duration};
 
 lazy_static! {
@@ -15,17 +17,35 @@ lazy_static! {
     static ref EPSILON: Decimal = Decimal::from_str("0.0001").unwrap();
 }
 
-pub async fn calculate_asset_prices(db: DbConn, redis_client: redis::Client, n: u64) {
+/// Prices of every asset after one recalculation round.
+#[derive(Clone, Debug)]
+pub struct PriceTick {
+    pub prices: HashMap<i32, Decimal>,
+}
+
+pub async fn calculate_asset_prices(
+    db: DbConn,
+    redis_client: redis::Client,
+    ticks: broadcast::Sender<PriceTick>,
+    n: u64,
+) {
     let mut interval = interval(Duration::from_secs(n));
     loop {
         interval.tick().await;
-        if let Err(err) = update_all_asset_prices(&db, &redis_client).await {
-            eprintln!("Error updating asset prices: {err}");
+        match update_all_asset_prices(&db, &redis_client).await {
+            Ok(prices) => {
+                // Nobody listening is fine, the bot engine may be disabled
+                let _ = ticks.send(PriceTick { prices });
+            }
+            Err(err) => eprintln!("Error updating asset prices: {err}"),
         }
     }
 }
 
-async fn update_all_asset_prices(db: &DbConn, redis_client: &redis::Client) -> Result<(), DbErr> {
+async fn update_all_asset_prices(
+    db: &DbConn,
+    redis_client: &redis::Client,
+) -> Result<HashMap<i32, Decimal>, DbErr> {
     let assets: Vec<assets::Model> = Assets::find().all(db).await?;
 
     let mut handles = vec![];
@@ -33,28 +53,35 @@ async fn update_all_asset_prices(db: &DbConn, redis_client: &redis::Client) -> R
         let db = db.clone();
         let redis_client = redis_client.clone();
         let handle = tokio::spawn(async move {
-            if let Err(err) = calculate_asset_price(&db, &redis_client, asset.id).await {
-                eprintln!("Failed to update price for asset {}: {err}", asset.id);
+            match calculate_asset_price(&db, &redis_client, asset.id).await {
+                Ok(price) => Some((asset.id, price)),
+                Err(err) => {
+                    eprintln!("Failed to update price for asset {}: {err}", asset.id);
+                    None
+                }
             }
         });
         handles.push(handle);
     }
 
+    let mut prices = HashMap::new();
     for handle in handles {
-        let _ = handle.await;
+        if let Ok(Some((asset_id, price))) = handle.await {
+            prices.insert(asset_id, price);
+        }
     }
-    Ok(())
+    Ok(prices)
 }
 
 async fn calculate_asset_price(
     db: &DbConn,
     redis_client: &redis::Client,
     asset_id: i32,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
+) -> Result<Decimal, Box<dyn Error + Send + Sync>> {
     let mut redis_conn = redis_client.get_multiplexed_async_connection().await?;
 
     let mut old_price: PriceInfo = redis_conn
-        .hgetall(&format!("asset_price:{asset_id}"))
+        .hgetall(format!("asset_price:{asset_id}"))
         .await?;
     if old_price.price.is_none() {
         if let Some(snapshot) = price_snapshot::Entity::find()
@@ -63,7 +90,7 @@ async fn calculate_asset_price(
             .one(db)
             .await?
         {
-            old_price.price = Some(Decimal::from(snapshot.price));
+            old_price.price = Some(snapshot.price);
             old_price.created_at = Some(snapshot.created_at.and_utc());
         } else {
             old_price.price = Some(Decimal::from(1));
@@ -129,7 +156,7 @@ async fn calculate_asset_price(
         let _: () = redis_conn
             .zadd(
                 &history_key,
-                &format!("{}:{}", final_price.round_dp(3), timestamp),
+                format!("{}:{}", final_price.round_dp(3), timestamp),
                 minute_timestamp,
             )
             .await?;
@@ -139,5 +166,5 @@ async fn calculate_asset_price(
         .zrembyscore(&history_key, "-inf", day_ago)
         .await?;
 
-    Ok(())
+    Ok(final_price)
 }
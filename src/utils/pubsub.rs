@@ -0,0 +1,254 @@
+use crate::routes::notifications::deliver_notification;
+use crate::routes::private_chat::deliver_chat;
+use crate::routes::ws::{deliver_event, drop_subscriber};
+use crate::structs::ws_structs::{ServerEnvelope, ServerFrame, Topic};
+use crate::utils::price_broadcaster::Broadcast;
+use crate::{PRICE_BROADCASTER, PUBSUB_CONNECTION};
+use futures::StreamExt;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, Client, RedisResult};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Messages for users connected to the chat socket of any instance.
+pub const CHAT_CHANNEL: &str = "ws:chat";
+/// Messages for users connected to the notification socket of any instance.
+pub const NOTIFICATION_CHANNEL: &str = "ws:notifications";
+/// Market snapshots for every market socket.
+pub const MARKET_CHANNEL: &str = "ws:market";
+/// Sequenced topic events for `/api/v1/ws`.
+pub const EVENT_CHANNEL: &str = "ws:events";
+/// Events kept per topic for clients resuming after a reconnect.
+pub const HISTORY_LIMIT: isize = 200;
+const HISTORY_TTL: i64 = 86_400;
+
+/// What goes through a pub/sub channel: an already serialized socket frame and the users it is
+/// meant for. Market snapshots go to everyone and leave `user_ids` empty.
+#[derive(Serialize, Deserialize)]
+pub struct Envelope {
+    #[serde(default)]
+    pub user_ids: Vec<i32>,
+    pub payload: String,
+}
+
+/// An event frame routed to the subscribers of one topic key.
+#[derive(Serialize, Deserialize)]
+pub struct TopicEvent {
+    pub topic: String,
+    pub payload: String,
+    /// Users whose sessions don't get the frame, e.g. those who blocked the author
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip_user_ids: Vec<i32>,
+    /// Set instead of a frame: the user's sessions stop receiving the topic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsubscribe_user_id: Option<i32>,
+}
+
+/// The shared publishing connection. Commands on it are pipelined, so callers clone it
+/// instead of opening a connection per frame.
+async fn __connection(cache: &Client) -> RedisResult<MultiplexedConnection> {
+    let mut connection = PUBSUB_CONNECTION.lock().await;
+    if let Some(connection) = connection.as_ref() {
+        return Ok(connection.clone());
+    }
+    let opened = cache.get_multiplexed_async_connection().await?;
+    *connection = Some(opened.clone());
+    Ok(opened)
+}
+
+/// Drops the shared connection after an error, the next caller opens a new one.
+async fn __reset_connection() {
+    *PUBSUB_CONNECTION.lock().await = None;
+}
+
+/// Publishes a frame for other instances. When Redis is unreachable the frame is delivered to
+/// this instance's own sessions, so a single server keeps working.
+pub async fn publish(cache: &Client, channel: &'static str, envelope: Envelope) {
+    let message = serde_json::to_string(&envelope).unwrap_or_default();
+    let published: RedisResult<()> = async {
+        let mut redis_conn = __connection(cache).await?;
+        redis_conn.publish(channel, message).await
+    }
+    .await;
+    if let Err(err) = published {
+        eprintln!("Failed to publish to {channel}: {err}");
+        __reset_connection().await;
+        __deliver(channel, envelope).await;
+    }
+}
+
+/// Numbers the event within its topic, keeps it in the topic history and publishes it to the
+/// `/api/v1/ws` subscribers of every instance. `ws:seq:*` counters never expire: a counter
+/// starting over would make clients with a higher `last_seq` skip new events. There is one
+/// per market topic and per user, the history next to it expires.
+pub async fn publish_event<T: Serialize>(cache: &Client, topic: &Topic, data: &T) {
+    publish_event_skipping(cache, topic, data, Vec::new()).await;
+}
+
+/// Same as [`publish_event`], live delivery leaves out the sessions of `skip_user_ids`.
+pub async fn publish_event_skipping<T: Serialize>(cache: &Client, topic: &Topic, data: &T, skip_user_ids: Vec<i32>) {
+    let published: RedisResult<()> = async {
+        let key = topic.key();
+        let history_key = format!("ws:history:{key}");
+        let mut redis_conn = __connection(cache).await?;
+        let seq: u64 = redis_conn.incr(format!("ws:seq:{key}"), 1).await?;
+        let frame = ServerEnvelope::from(ServerFrame::Event {
+            topic: topic.name(),
+            seq,
+            data: serde_json::to_value(data).unwrap_or_default(),
+        });
+        let payload = serde_json::to_string(&frame).unwrap_or_default();
+        let event = TopicEvent {
+            topic: key,
+            payload: payload.clone(),
+            skip_user_ids,
+            unsubscribe_user_id: None,
+        };
+        redis::pipe()
+            .atomic()
+            .zadd(&history_key, payload, seq)
+            .ignore()
+            .zremrangebyrank(&history_key, 0, -(HISTORY_LIMIT + 1))
+            .ignore()
+            .expire(&history_key, HISTORY_TTL)
+            .ignore()
+            .publish(EVENT_CHANNEL, serde_json::to_string(&event).unwrap_or_default())
+            .ignore()
+            .query_async(&mut redis_conn)
+            .await
+    }
+    .await;
+    if let Err(err) = published {
+        eprintln!("Failed to publish {} event: {err}", topic.key());
+        __reset_connection().await;
+    }
+}
+
+/// Publishes a short-lived event to the `/api/v1/ws` subscribers of every instance, without a
+/// sequence number or history.
+pub async fn publish_signal<T: Serialize>(cache: &Client, topic: &Topic, data: &T) {
+    let frame = ServerEnvelope::from(ServerFrame::Signal {
+        topic: topic.name(),
+        data: serde_json::to_value(data).unwrap_or_default(),
+    });
+    let event = TopicEvent {
+        topic: topic.key(),
+        payload: serde_json::to_string(&frame).unwrap_or_default(),
+        skip_user_ids: Vec::new(),
+        unsubscribe_user_id: None,
+    };
+    let published: RedisResult<()> = async {
+        let mut redis_conn = __connection(cache).await?;
+        redis_conn
+            .publish(EVENT_CHANNEL, serde_json::to_string(&event).unwrap_or_default())
+            .await
+    }
+    .await;
+    if let Err(err) = published {
+        eprintln!("Failed to publish {} signal: {err}", topic.key());
+        __reset_connection().await;
+    }
+}
+
+/// Drops the topic from every session of the user, on all instances. Used when the user
+/// loses access to it, like a member removed from a group room.
+pub async fn publish_unsubscribe(cache: &Client, topic: &Topic, user_id: i32) {
+    let event = TopicEvent {
+        topic: topic.key(),
+        payload: String::new(),
+        skip_user_ids: Vec::new(),
+        unsubscribe_user_id: Some(user_id),
+    };
+    let published: RedisResult<()> = async {
+        let mut redis_conn = __connection(cache).await?;
+        redis_conn
+            .publish(EVENT_CHANNEL, serde_json::to_string(&event).unwrap_or_default())
+            .await
+    }
+    .await;
+    if let Err(err) = published {
+        eprintln!("Failed to publish {} unsubscribe: {err}", topic.key());
+        __reset_connection().await;
+        drop_subscriber(&event.topic, user_id).await;
+    }
+}
+
+/// Frames a client missed since `last_seq`, preceded by a resync frame when part of them is
+/// no longer kept.
+pub async fn missed_events(cache: &Client, topic: &Topic, last_seq: u64) -> RedisResult<Vec<String>> {
+    let key = topic.key();
+    let mut redis_conn = __connection(cache).await?;
+    let current: Option<u64> = redis_conn.get(format!("ws:seq:{key}")).await?;
+    let current = current.unwrap_or_default();
+    if current <= last_seq {
+        return Ok(Vec::new());
+    }
+    let history: Vec<(String, u64)> = redis_conn
+        .zrangebyscore_withscores(format!("ws:history:{key}"), format!("({last_seq}"), "+inf")
+        .await?;
+
+    let mut frames = Vec::with_capacity(history.len() + 1);
+    if history.first().is_none_or(|(_, seq)| *seq > last_seq + 1) {
+        let resync = ServerEnvelope::from(ServerFrame::Resync {
+            topic: topic.name(),
+            seq: current,
+        });
+        frames.push(serde_json::to_string(&resync).unwrap_or_default());
+    }
+    frames.extend(history.into_iter().map(|(frame, _)| frame));
+    Ok(frames)
+}
+
+/// Relays everything published on the socket channels to the sessions of this instance.
+/// Reconnects when the subscription drops.
+pub async fn run_ws_relay(redis_client: Client) {
+    loop {
+        if let Err(err) = __relay(&redis_client).await {
+            eprintln!("WebSocket relay error: {err}");
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn __relay(redis_client: &Client) -> RedisResult<()> {
+    let mut pubsub = redis_client.get_async_pubsub().await?;
+    pubsub
+        .subscribe(&[CHAT_CHANNEL, NOTIFICATION_CHANNEL, MARKET_CHANNEL, EVENT_CHANNEL])
+        .await?;
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let Ok(payload) = message.get_payload::<String>() else {
+            continue;
+        };
+        if message.get_channel_name() == EVENT_CHANNEL {
+            if let Ok(event) = serde_json::from_str::<TopicEvent>(&payload) {
+                match event.unsubscribe_user_id {
+                    Some(user_id) => drop_subscriber(&event.topic, user_id).await,
+                    None => deliver_event(&event.topic, event.payload, event.skip_user_ids).await,
+                }
+            }
+            continue;
+        }
+        let Ok(envelope) = serde_json::from_str::<Envelope>(&payload) else {
+            continue;
+        };
+        __deliver(message.get_channel_name(), envelope).await;
+    }
+    Err(redis::RedisError::from((
+        redis::ErrorKind::IoError,
+        "Subscription closed",
+    )))
+}
+
+async fn __deliver(channel: &str, envelope: Envelope) {
+    match channel {
+        CHAT_CHANNEL => deliver_chat(&envelope.user_ids, envelope.payload).await,
+        NOTIFICATION_CHANNEL => deliver_notification(&envelope.user_ids, envelope.payload).await,
+        MARKET_CHANNEL => {
+            if let Ok(snapshot) = serde_json::from_str(&envelope.payload) {
+                PRICE_BROADCASTER.do_send(Broadcast { snapshot });
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,110 @@
+use crate::structs::order_structs::OrderType;
+use crate::utils::app_error::AppError;
+use chrono::{Duration, Utc};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use sea_orm::prelude::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuoteClaims {
+    pub sub: i32,
+    pub iat: usize,
+    pub exp: usize,
+    pub asset_id: i32,
+    pub side: String,
+    pub amount: Decimal,
+    pub price: Decimal,
+    pub token_type: String, // всегда "quote"
+}
+
+pub fn side_name(side: &OrderType) -> &'static str {
+    match side {
+        OrderType::Buy => "buy",
+        OrderType::Sell => "sell",
+    }
+}
+
+pub fn quote_ttl() -> i64 {
+    std::env::var("QUOTE_TTL")
+        .ok()
+        .and_then(|ttl| ttl.parse().ok())
+        .filter(|ttl| *ttl > 0)
+        .unwrap_or(5)
+}
+
+pub fn generate_quote(
+    user_id: i32,
+    asset_id: i32,
+    side: &OrderType,
+    amount: Decimal,
+    price: Decimal,
+    secret: &str,
+) -> Result<(String, QuoteClaims), jsonwebtoken::errors::Error> {
+    let now = Utc::now();
+    let exp = now + Duration::seconds(quote_ttl());
+    let claims = QuoteClaims {
+        sub: user_id,
+        iat: now.timestamp() as usize,
+        exp: exp.timestamp() as usize,
+        asset_id,
+        side: side_name(side).to_owned(),
+        amount,
+        price,
+        token_type: "quote".to_owned(),
+    };
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_ref()),
+    )?;
+    Ok((token, claims))
+}
+
+/// Checks that the quote was issued to this user for exactly this trade and returns its price.
+pub fn verify_quote(
+    quote: &str,
+    secret: &str,
+    user_id: i32,
+    asset_id: i32,
+    side: &OrderType,
+    amount: Decimal,
+) -> Result<Decimal, AppError> {
+    let mut validation = Validation::default();
+    validation.leeway = 0;
+    let claims = decode::<QuoteClaims>(
+        quote,
+        &DecodingKey::from_secret(secret.as_ref()),
+        &validation,
+    )
+    .map_err(|err| match err.kind() {
+        ErrorKind::ExpiredSignature => AppError::QuoteExpired,
+        _ => AppError::Validation("Invalid quote".into()),
+    })?
+    .claims;
+
+    if claims.token_type != "quote"
+        || claims.sub != user_id
+        || claims.asset_id != asset_id
+        || claims.side != side_name(side)
+        || claims.amount != amount
+    {
+        return Err(AppError::Validation("Quote doesn't match the order".into()));
+    }
+    Ok(claims.price)
+}
+
+/// Combines a quoted price with an explicit limit, keeping whichever is stricter for the side.
+pub fn price_limit(
+    side: &OrderType,
+    quoted: Option<Decimal>,
+    limit: Option<Decimal>,
+) -> Option<Decimal> {
+    match (quoted, limit) {
+        (Some(quoted), Some(limit)) => Some(match side {
+            OrderType::Buy => quoted.min(limit),
+            OrderType::Sell => quoted.max(limit),
+        }),
+        (quoted, limit) => quoted.or(limit),
+    }
+}
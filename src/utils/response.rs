@@ -1,3 +1,4 @@
+use crate::utils::app_error::ErrorCode;
 use serde::Serialize;
 
 #[derive(Serialize, Debug)]
@@ -5,6 +6,7 @@ pub struct CommonResponse<T> {
     pub status: ResponseStatus,
     pub data: T,
     pub error: Option<String>,
+    pub code: Option<ErrorCode>,
 }
 
 
@@ -0,0 +1,171 @@
+use crate::structs::notification_structs::NotificationKind;
+use crate::structs::ws_structs::{BookEvent, Topic};
+use crate::utils::alerts::deactivate_order_alerts;
+use crate::utils::leaderboard::update_leaderboards_executor;
+use crate::utils::net_worth::{net_worth_all, ranked};
+use crate::utils::net_worth_snapshot::save_net_worth_executor;
+use crate::utils::notifications::{notify, OrderNotification};
+use crate::utils::pubsub::publish_event;
+use crate::utils::portfolio::STARTING_BALANCE;
+use chrono::{Duration as ChronoDuration, Utc};
+use entity::{orders, season_results, seasons, user_balances};
+use sea_orm::prelude::{Decimal, Expr};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DbConn, DbErr, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, Set, Statement, TransactionTrait,
+};
+use std::error::Error;
+use std::time::Duration;
+use tokio::time::interval;
+
+pub fn season_length() -> i64 {
+    std::env::var("SEASON_LENGTH_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(30)
+}
+
+/// Whether a new season starts by itself once the last one is finished, off by default so
+/// balances are only ever reset after an admin scheduled a season.
+pub fn season_auto_rollover() -> bool {
+    std::env::var("SEASON_AUTO_ROLLOVER")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(false)
+}
+
+/// The season being played right now, `None` between seasons.
+pub async fn current_season<C: ConnectionTrait>(db: &C) -> Result<Option<seasons::Model>, DbErr> {
+    let now = Utc::now().naive_utc();
+    seasons::Entity::find()
+        .filter(seasons::Column::StartsAt.lte(now))
+        .filter(seasons::Column::FinishedAt.is_null())
+        .order_by_desc(seasons::Column::StartsAt)
+        .one(db)
+        .await
+}
+
+pub async fn run_seasons(db: DbConn, redis_client: redis::Client, n: u64) {
+    let mut interval = interval(Duration::from_secs(n));
+    loop {
+        interval.tick().await;
+        if let Err(err) = run_seasons_executor(&db, &redis_client).await {
+            eprintln!("Error updating seasons: {err}");
+        }
+    }
+}
+
+/// Finishes every season past its end. With `SEASON_AUTO_ROLLOVER` it also starts the next
+/// one when nothing is scheduled.
+pub async fn run_seasons_executor(db: &DbConn, redis_client: &redis::Client) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let now = Utc::now().naive_utc();
+    let ended = seasons::Entity::find()
+        .filter(seasons::Column::FinishedAt.is_null())
+        .filter(seasons::Column::EndsAt.lte(now))
+        .order_by_asc(seasons::Column::StartsAt)
+        .all(db)
+        .await?;
+    for season in ended {
+        __finish_season(db, redis_client, season).await?;
+    }
+
+    if !season_auto_rollover() {
+        return Ok(());
+    }
+    let unfinished = seasons::Entity::find()
+        .filter(seasons::Column::FinishedAt.is_null())
+        .count(db)
+        .await?;
+    if unfinished == 0 {
+        let number = seasons::Entity::find().count(db).await? + 1;
+        seasons::ActiveModel {
+            name: Set(format!("Season {number}")),
+            starts_at: Set(now),
+            ends_at: Set(now + ChronoDuration::days(season_length())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Archives the final standings, then cancels pending orders, wipes holdings and puts every
+/// player back to the starting balance. Bots go back to their allocation.
+async fn __finish_season(
+    db: &DbConn,
+    redis_client: &redis::Client,
+    season: seasons::Model,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let txn = db.begin().await?;
+    // Trades would move money between the valuation and the reset, writers wait until commit
+    txn.execute_unprepared("LOCK TABLE users, user_balances, orders IN EXCLUSIVE MODE")
+        .await?;
+    let worths = net_worth_all(&txn, redis_client)
+        .await
+        .map_err(|err| err.to_string())?;
+    let starting_balance = Decimal::from(STARTING_BALANCE);
+    let results: Vec<season_results::ActiveModel> = ranked(worths)
+        .into_iter()
+        .filter_map(|(user, worth, place)| {
+            Some(season_results::ActiveModel {
+                season_id: Set(season.id),
+                user_id: Set(user.id),
+                place: Set(place?),
+                total_balance: Set(worth.total),
+                return_percent: Set(((worth.total - starting_balance) / starting_balance
+                    * Decimal::from(100))
+                .round_dp(2)),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    for chunk in results.chunks(1000) {
+        season_results::Entity::insert_many(chunk.to_vec())
+            .exec(&txn)
+            .await?;
+    }
+    let expired = orders::Entity::find()
+        .filter(orders::Column::Status.eq("pending"))
+        .all(&txn)
+        .await?;
+    deactivate_order_alerts(&txn, expired.iter().map(|order| order.id).collect())
+        .await
+        .map_err(|err| err.to_string())?;
+    orders::Entity::update_many()
+        .col_expr(orders::Column::Status, Expr::value("cancel"))
+        .filter(orders::Column::Status.eq("pending"))
+        .exec(&txn)
+        .await?;
+    user_balances::Entity::delete_many().exec(&txn).await?;
+    txn.execute(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "UPDATE users SET balance = COALESCE((SELECT bot_configs.allocation FROM bot_configs WHERE bot_configs.user_id = users.id), $1)",
+        [starting_balance.into()],
+    ))
+    .await?;
+    let mut active_season = season.into_active_model();
+    active_season.finished_at = Set(Some(Utc::now().naive_utc()));
+    active_season.update(&txn).await?;
+    txn.commit().await?;
+
+    for order in &expired {
+        let notification = OrderNotification::new(order, Decimal::ZERO);
+        notify(db, redis_client, order.user_id, NotificationKind::OrderExpired, &notification).await;
+        let cancelled = orders::Model {
+            status: "cancel".into(),
+            ..order.clone()
+        };
+        publish_event(redis_client, &Topic::Book(order.asset_id), &BookEvent::from(&cancelled)).await;
+    }
+
+    // Equity curves and leaderboards should start the new season from the reset balances
+    if let Err(err) = save_net_worth_executor(db, redis_client).await {
+        eprintln!("Error saving net worth snapshots: {err}");
+    }
+    if let Err(err) = update_leaderboards_executor(db, redis_client).await {
+        eprintln!("Error updating leaderboards: {err}");
+    }
+    Ok(())
+}